@@ -12,19 +12,30 @@ pub fn yas_window_info(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
             let name = field.ident.as_ref().unwrap();
 
             let mut window_info_key: String = name.to_string();
+            let mut default_value: Option<syn::Expr> = None;
             for attr in field.attrs.iter() {
                 if attr.path().is_ident("window_info") {
                     let nested_attributes = WindowInfoNestedAttributes::from_attr(attr).unwrap();
                     if nested_attributes.rename.is_some() {
                         window_info_key = nested_attributes.rename.clone().unwrap().value();
                     }
+                    if let Some(default) = nested_attributes.default.as_ref() {
+                        default_value = Some(default.parse().unwrap());
+                    }
                 }
             }
 
+            let missing = match default_value {
+                Some(expr) => quote! { #expr },
+                None => quote! {
+                    return Err(repo.missing_key_error(#window_info_key, window_size, ui, platform));
+                },
+            };
+
             fields.push(quote! {
                 #name: match repo.get_auto_scale(#window_info_key, window_size, ui, platform) {
                     None => {
-                        return Err(anyhow::anyhow!("cannot find window info key \"{}\"", #window_info_key));
+                        #missing
                     },
                     Some(value) => value
                 }
@@ -3,6 +3,8 @@ use syn::{token, Token};
 #[derive(Default)]
 pub struct WindowInfoNestedAttributes {
     pub rename: Option<syn::LitStr>,
+    /// expression used when the key is missing from the repository
+    pub default: Option<syn::LitStr>,
 }
 
 impl WindowInfoNestedAttributes {
@@ -20,6 +22,13 @@ impl WindowInfoNestedAttributes {
                 return Ok(());
             }
 
+            if meta.path.is_ident("default") {
+                let _eq: Token![=] = meta.input.parse()?;
+                let expr: syn::LitStr = meta.input.parse()?;
+                result.default = Some(expr);
+                return Ok(());
+            }
+
             Err(meta.error("unrecognized window_info"))
         })?;
 
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{ArgMatches, Args, FromArgMatches};
+
+use crate::export::artifact::{load_artifacts, ArtifactDiff};
+
+#[derive(Clone, Args)]
+pub struct ArtifactDiffConfig {
+    /// The earlier export, Mona (extended) or GOOD json
+    #[arg(id = "a", value_name = "A_JSON", help = "较早的导出文件（莫娜或GOOD格式）")]
+    pub a: PathBuf,
+
+    /// The later export
+    #[arg(id = "b", value_name = "B_JSON", help = "较新的导出文件（莫娜或GOOD格式）")]
+    pub b: PathBuf,
+}
+
+/// `yas genshin diff a.json b.json`, prints which artifacts were added, removed or changed between two exports
+pub struct ArtifactDiffApplication {
+    arg_matches: ArgMatches,
+}
+
+impl ArtifactDiffApplication {
+    pub fn new(matches: ArgMatches) -> Self {
+        ArtifactDiffApplication {
+            arg_matches: matches
+        }
+    }
+
+    pub fn build_command() -> clap::Command {
+        let cmd = clap::Command::new("diff").about("比较两次导出的圣遗物");
+        <ArtifactDiffConfig as Args>::augment_args(cmd)
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let config = ArtifactDiffConfig::from_arg_matches(&self.arg_matches)?;
+        let a = load_artifacts(&config.a)?;
+        let b = load_artifacts(&config.b)?;
+
+        let diff = ArtifactDiff::new(&a, &b);
+        print!("{}", diff);
+
+        Ok(())
+    }
+}
@@ -1,15 +1,16 @@
 use anyhow::Result;
 use anyhow::anyhow;
-use clap::{command, ArgMatches, Args};
+use clap::{command, ArgMatches, Args, FromArgMatches};
 use log::info;
 
 use yas::export::{AssetEmitter, ExportAssets};
 use yas::game_info::{GameInfo, GameInfoBuilder};
 use yas::window_info::{load_window_info_repo, WindowInfoRepository};
 
+use crate::application::{ArtifactDiffApplication, LockCalibrationApplication, WeaponScannerApplication};
 use crate::artifact::GenshinArtifact;
-use crate::export::artifact::{ExportArtifactConfig, GenshinArtifactExporter};
-use crate::scanner::{GenshinArtifactScanner, GenshinArtifactScannerConfig};
+use crate::export::artifact::{filter_by_character, load_artifacts, ArtifactDiff, ExportArtifactConfig, ExportLockFilter, GenshinArtifactExporter};
+use crate::scanner::{GenshinArtifactScanner, GenshinArtifactScannerConfig, ScanSummary};
 use crate::scanner_controller::repository_layout::GenshinRepositoryScannerLogicConfig;
 
 pub struct ArtifactScannerApplication {
@@ -28,7 +29,9 @@ impl ArtifactScannerApplication {
         cmd = <ExportArtifactConfig as Args>::augment_args_for_update(cmd);
         cmd = <GenshinArtifactScannerConfig as Args>::augment_args_for_update(cmd);
         cmd = <GenshinRepositoryScannerLogicConfig as Args>::augment_args_for_update(cmd);
-        cmd
+        cmd.subcommand(ArtifactDiffApplication::build_command())
+            .subcommand(LockCalibrationApplication::build_command())
+            .subcommand(WeaponScannerApplication::build_command())
     }
 
     fn get_window_info_repository() -> WindowInfoRepository {
@@ -60,6 +63,17 @@ impl ArtifactScannerApplication {
 impl ArtifactScannerApplication {
     pub fn run(&self) -> Result<()> {
         let arg_matches = &self.arg_matches;
+        match arg_matches.subcommand() {
+            Some(("diff", matches)) => return ArtifactDiffApplication::new(matches.clone()).run(),
+            Some(("calibrate-lock", matches)) => {
+                return LockCalibrationApplication::new(matches.clone()).run(&Self::get_window_info_repository(), Self::get_game_info()?);
+            },
+            Some(("weapon", matches)) => {
+                return WeaponScannerApplication::new(matches.clone()).run(&Self::get_window_info_repository(), Self::get_game_info()?);
+            },
+            _ => {},
+        }
+
         let window_info_repository = Self::get_window_info_repository();
         let game_info = Self::get_game_info()?;
 
@@ -68,6 +82,10 @@ impl ArtifactScannerApplication {
         info!("cloud: {}", game_info.is_cloud);
         info!("resolution family: {:?}", game_info.resolution_family);
 
+        let window_size = game_info.window.to_rect_usize().size();
+        let (base, factor) = window_info_repository.select_profile(window_size, game_info.ui, game_info.platform)?;
+        info!("window info: {}x{}, scale {:.3}", base.width, base.height, factor);
+
         #[cfg(target_os = "windows")]
         {
             // assure admin
@@ -82,21 +100,87 @@ impl ArtifactScannerApplication {
             game_info.clone()
         )?;
 
+        let scanner_config = GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?;
+        scanner_config.validate()?;
+        if scanner_config.preview_regions {
+            let path = std::path::Path::new("region_preview.png");
+            scanner.preview_regions(path)?;
+            info!("识别区域预览已保存至 {:?}", path);
+            return Ok(());
+        }
+
+        if scanner_config.list_only {
+            let cells = scanner.scan_list()?;
+            let export_config = ExportArtifactConfig::from_arg_matches(arg_matches)?;
+            let path = std::path::Path::new(&export_config.output_dir).join("artifact_list.json");
+            std::fs::write(&path, serde_json::to_string_pretty(&cells)?)?;
+            info!("列表扫描结果已保存至 {:?}", path);
+            return Ok(());
+        }
+
+        // read the previous export before scanning, so a bad path does not waste a whole scan
+        let previous = match ExportArtifactConfig::from_arg_matches(arg_matches)?.diff_against {
+            Some(path) => Some(load_artifacts(&path)?),
+            None => None,
+        };
+
         let result = scanner.scan()?;
+
+        if scanner_config.dry_run {
+            let summary = ScanSummary::new(scanner.scanned_count(), &result);
+            info!("试运行，不导出结果：");
+            for line in format!("{}", summary).lines() {
+                info!("{}", line);
+            }
+            return Ok(());
+        }
+
         let mut artifacts = Vec::new();
         for scan_result in result.iter() {
             match GenshinArtifact::try_from(scan_result) {
-                Ok(art) => artifacts.push(art),
+                Ok(art) => {
+                    let warnings = art.validate();
+                    if !warnings.is_empty() {
+                        let warnings = warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+                        log::warn!("圣遗物数据异常，可能识别有误: {} {}", scan_result.name, warnings.join("; "));
+                    }
+                    artifacts.push(art)
+                },
                 Err(_) => {
                     log::error!("Failed to parse artifact: {:?}", scan_result);
                 }
             }
         }
 
-        let exporter = GenshinArtifactExporter::new(arg_matches, &artifacts)?;
+        // against the whole bag, the filters below only narrow what is exported
+        let diff = previous.as_ref().map(|previous| ArtifactDiff::new(previous, &artifacts));
+
+        let export_config = ExportArtifactConfig::from_arg_matches(arg_matches)?;
+        if let Some(name) = export_config.export_character.as_ref() {
+            artifacts = filter_by_character(&artifacts, name);
+            info!("只导出 {} 装备的圣遗物，共 {} 件", name, artifacts.len());
+        }
+        if export_config.export_lock != ExportLockFilter::All {
+            artifacts = export_config.export_lock.apply(&artifacts);
+            info!("按锁定状态筛选后导出 {} 件圣遗物", artifacts.len());
+        }
+
+        let mut exporter = GenshinArtifactExporter::new(arg_matches, &artifacts)?;
+        if export_config.include_raw {
+            exporter.raw = Some(result.as_slice());
+        }
         let mut export_assets = ExportAssets::new();
         exporter.emit(&mut export_assets);
 
+        if let Some(diff) = diff {
+            info!("与之前的导出相比：新增 {} 件，移除 {} 件，变化 {} 件", diff.added.len(), diff.removed.len(), diff.changed.len());
+            export_assets.add_asset(
+                Some(String::from("diff")),
+                exporter.path("diff", "json"),
+                serde_json::to_string(&diff)?.into_bytes(),
+                Some(String::from("与之前导出的差异")));
+        }
+
         let stats = export_assets.save();
         info!("保存结果：");
         let table = format!("{}", stats);
@@ -104,7 +188,12 @@ impl ArtifactScannerApplication {
         for line in table.lines() {
             info!("{}", line);
         }
-        info!("Yas 识别结束，共识别到 {} 件圣遗物。", result.len());
+
+        if let Some(bundle) = export_config.bundle.as_ref() {
+            export_assets.save_bundle(bundle)?;
+            info!("已打包至 {:?}", bundle);
+        }
+        info!("Yas 识别结束，共识别到 {} 件圣遗物，导出 {} 件。", result.len(), artifacts.len());
 
         Ok(())
     }
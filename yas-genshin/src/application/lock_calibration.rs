@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, Args, FromArgMatches};
+use image::GenericImageView;
+
+use yas::game_info::GameInfo;
+use yas::positioning::Size;
+use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
+
+use crate::scanner::{sample_lock_color, ArtifactScannerWindowInfo};
+
+#[derive(Clone, Args)]
+pub struct LockCalibrationConfig {
+    /// A full-window screenshot of the artifact list
+    #[arg(id = "screenshot", value_name = "SCREENSHOT", help = "背包圣遗物列表的游戏窗口截图")]
+    pub screenshot: PathBuf,
+
+    /// Row (starting from 0) of a locked artifact in the screenshot
+    #[arg(long = "row", default_value_t = 0, help = "已锁定圣遗物所在的行（从0开始）")]
+    pub row: i32,
+
+    /// Column (starting from 0) of a locked artifact in the screenshot
+    #[arg(long = "col", default_value_t = 0, help = "已锁定圣遗物所在的列（从0开始）")]
+    pub col: i32,
+}
+
+/// `yas genshin calibrate-lock screenshot.png`, samples the list view lock icon color from a locked artifact
+/// and prints the window info entries to use it. The game window is looked up for its UI and platform
+pub struct LockCalibrationApplication {
+    arg_matches: ArgMatches,
+}
+
+impl LockCalibrationApplication {
+    pub fn new(matches: ArgMatches) -> Self {
+        LockCalibrationApplication {
+            arg_matches: matches
+        }
+    }
+
+    pub fn build_command() -> clap::Command {
+        let cmd = clap::Command::new("calibrate-lock").about("从已锁定的圣遗物截图中取样锁定图标颜色");
+        <LockCalibrationConfig as Args>::augment_args(cmd)
+    }
+
+    pub fn run(&self, window_info_repo: &WindowInfoRepository, game_info: GameInfo) -> Result<()> {
+        let config = LockCalibrationConfig::from_arg_matches(&self.arg_matches)?;
+        let image = image::open(&config.screenshot)?.to_rgb8();
+
+        let size = Size {
+            width: image.width() as usize,
+            height: image.height() as usize,
+        };
+        let window_info = ArtifactScannerWindowInfo::from_window_info_repository(
+            size,
+            game_info.ui,
+            game_info.platform,
+            window_info_repo,
+        )?;
+
+        let margin = window_info.scan_margin_pos;
+        let (left, top) = (margin.x as u32, margin.y as u32);
+        if left >= image.width() || top >= image.height() {
+            return Err(anyhow!("截图尺寸 {}x{} 与列表位置不符", image.width(), image.height()));
+        }
+        let list_image = image.view(left, top, image.width() - left, image.height() - top).to_image();
+
+        let (color, distance) = sample_lock_color(&list_image, &window_info, config.row, config.col)
+            .ok_or_else(|| anyhow!("第 {} 行第 {} 列不在截图范围内", config.row, config.col))?;
+
+        println!("取样颜色: {:?}，与当前锁定颜色的距离: {}", color.0, distance);
+        println!("\"genshin_repository_lock_color\": {{ \"Color\": [{}, {}, {}] }}", color[0], color[1], color[2]);
+        println!("\"genshin_repository_lock_threshold\": {{ \"InvariantFloat\": {:.1} }}", window_info.list_lock_threshold);
+
+        Ok(())
+    }
+}
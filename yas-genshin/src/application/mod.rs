@@ -1,4 +1,9 @@
+pub use artifact_diff::ArtifactDiffApplication;
 pub use artifact_scanner::ArtifactScannerApplication;
+pub use lock_calibration::LockCalibrationApplication;
+pub use weapon_scanner::WeaponScannerApplication;
 
+mod artifact_diff;
 mod artifact_scanner;
-
+mod lock_calibration;
+mod weapon_scanner;
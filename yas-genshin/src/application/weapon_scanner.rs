@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+#[cfg(target_os = "windows")]
+use anyhow::anyhow;
+use clap::{ArgMatches, Args, FromArgMatches};
+use log::{error, info};
+
+use yas::game_info::GameInfo;
+use yas::window_info::WindowInfoRepository;
+
+use crate::export::weapon::MonaWeaponFormat;
+use crate::scanner::{GenshinWeaponScanner, GenshinWeaponScannerConfig};
+use crate::scanner_controller::repository_layout::GenshinRepositoryScannerLogicConfig;
+use crate::weapon::GenshinWeapon;
+
+#[derive(Clone, Args)]
+pub struct WeaponExportConfig {
+    #[arg(id = "output-dir", long = "output-dir", short, default_value_t = String::from("."), help = "输出目录")]
+    pub output_dir: String,
+}
+
+/// `yas genshin weapon`, scans the weapon inventory and writes `mona_weapons.json`
+pub struct WeaponScannerApplication {
+    arg_matches: ArgMatches,
+}
+
+impl WeaponScannerApplication {
+    pub fn new(matches: ArgMatches) -> Self {
+        WeaponScannerApplication {
+            arg_matches: matches
+        }
+    }
+
+    pub fn build_command() -> clap::Command {
+        let mut cmd = clap::Command::new("weapon").about("扫描背包中的武器");
+        cmd = <WeaponExportConfig as Args>::augment_args(cmd);
+        cmd = <GenshinWeaponScannerConfig as Args>::augment_args(cmd);
+        <GenshinRepositoryScannerLogicConfig as Args>::augment_args(cmd)
+    }
+
+    pub fn run(&self, window_info_repo: &WindowInfoRepository, game_info: GameInfo) -> Result<()> {
+        #[cfg(target_os = "windows")]
+        {
+            // assure admin
+            if !yas::utils::is_admin() {
+                return Err(anyhow!("请使用管理员运行"));
+            }
+        }
+
+        let arg_matches = &self.arg_matches;
+        let scanner_config = GenshinWeaponScannerConfig::from_arg_matches(arg_matches)?;
+        let preview_regions = scanner_config.preview_regions;
+        let mut scanner = GenshinWeaponScanner::new(
+            window_info_repo,
+            scanner_config,
+            GenshinRepositoryScannerLogicConfig::from_arg_matches(arg_matches)?,
+            game_info,
+        )?;
+
+        if preview_regions {
+            let path = std::path::Path::new("weapon_region_preview.png");
+            scanner.preview_regions(path)?;
+            info!("识别区域预览已保存至 {:?}", path);
+            return Ok(());
+        }
+
+        let results = scanner.scan()?;
+        let mut weapons = Vec::new();
+        for result in results.iter() {
+            match GenshinWeapon::try_from(result) {
+                Ok(weapon) => weapons.push(weapon),
+                Err(e) => error!("无法解析武器 {:?}: {}", result, e),
+            }
+        }
+
+        let export_config = WeaponExportConfig::from_arg_matches(arg_matches)?;
+        let path = PathBuf::from(&export_config.output_dir).join("mona_weapons.json");
+        std::fs::write(&path, serde_json::to_string(&MonaWeaponFormat::new(&weapons))?)?;
+        info!("已保存 {} 把武器至 {:?}", weapons.len(), path);
+
+        Ok(())
+    }
+}
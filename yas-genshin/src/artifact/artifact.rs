@@ -1,13 +1,31 @@
 use std::hash::{Hash, Hasher};
 
-use log::error;
+use log::{error, warn};
 use regex::Regex;
-use strum_macros::Display;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
 
-use crate::character::CHARACTER_NAMES;
+use crate::artifact::{ArtifactIdentity, ArtifactParseError, Language};
+use crate::character::equip_from_zh_cn_raw;
 use crate::scanner::GenshinArtifactScanResult;
 
-#[derive(Debug, Hash, Clone, PartialEq, Eq, Display)]
+/// The largest value of each stat as a substat: 6 maximum rolls of a 5 star artifact.
+/// Pass another table to `GenshinArtifact::from_scan_result_with_caps` to check against different caps
+#[rustfmt::skip]
+pub const SUB_STAT_CAPS: [(ArtifactStatName, f64); 10] = [
+    (ArtifactStatName::Hp, 1793.0),
+    (ArtifactStatName::Atk, 117.0),
+    (ArtifactStatName::Def, 139.0),
+    (ArtifactStatName::HpPercentage, 0.350),
+    (ArtifactStatName::AtkPercentage, 0.350),
+    (ArtifactStatName::DefPercentage, 0.438),
+    (ArtifactStatName::ElementalMastery, 140.0),
+    (ArtifactStatName::Recharge, 0.389),
+    (ArtifactStatName::Critical, 0.234),
+    (ArtifactStatName::CriticalDamage, 0.467),
+];
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq, Display, EnumIter)]
 pub enum ArtifactStatName {
     HealingBonus,
     CriticalDamage,
@@ -30,7 +48,7 @@ pub enum ArtifactStatName {
     DendroBonus,
 }
 
-#[derive(Debug, Hash, Clone, PartialEq, Eq, Display)]
+#[derive(Debug, Hash, Clone, PartialEq, Eq, Display, EnumIter)]
 pub enum ArtifactSlot {
     Flower,
     Feather,
@@ -39,7 +57,7 @@ pub enum ArtifactSlot {
     Head,
 }
 
-#[derive(Debug, Hash, Clone, PartialEq, Eq, Display)]
+#[derive(Debug, Hash, Clone, PartialEq, Eq, Display, EnumIter)]
 pub enum ArtifactSetName {
     ArchaicPetra,
     HeartOfDepth,
@@ -109,6 +127,17 @@ pub struct ArtifactStat {
     pub pending: bool,
 }
 
+/// A substat line whose name was clipped, only the value was recognized.
+/// The name is unknown, so it is not part of the artifact and needs a manual look
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnnamedSubStat {
+    /// which substat line, 0 to 3
+    pub index: usize,
+    pub value: f64,
+    pub is_percentage: bool,
+    pub pending: bool,
+}
+
 #[derive(Debug, Hash, Clone, PartialEq, Eq)]
 pub struct GenshinArtifact {
     pub set_name: ArtifactSetName,
@@ -124,6 +153,18 @@ pub struct GenshinArtifact {
     pub equip: Option<String>,
 }
 
+/// Namespace of the name based UUIDs of artifacts
+const ARTIFACT_UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_u128(0x6f1c_2a4e_9b3d_4c57_8e21_d0a9_53b7_c8f4);
+
+impl GenshinArtifact {
+    /// A UUID derived from the artifact's identity, so scanning the same artifact again yields the same id.
+    /// Lock and equip state do not affect it, but leveling the artifact does
+    pub fn deterministic_uuid(&self) -> uuid::Uuid {
+        let key = ArtifactIdentity::new(self).canonical_key();
+        uuid::Uuid::new_v5(&ARTIFACT_UUID_NAMESPACE, key.as_bytes())
+    }
+}
+
 impl Hash for ArtifactStat {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.name.hash(state);
@@ -148,6 +189,14 @@ impl PartialEq for ArtifactStat {
 impl Eq for ArtifactStat {}
 
 impl ArtifactStatName {
+    /// Whether the value is a fraction shown with `%`, false for flat stats and elemental mastery
+    pub fn is_percentage(&self) -> bool {
+        !matches!(
+            self,
+            ArtifactStatName::Atk | ArtifactStatName::Hp | ArtifactStatName::Def | ArtifactStatName::ElementalMastery
+        )
+    }
+
     #[rustfmt::skip]
     pub fn from_zh_cn(name: &str, is_percentage: bool) -> Option<ArtifactStatName> {
         match name {
@@ -170,11 +219,155 @@ impl ArtifactStatName {
             _ => None,
         }
     }
+
+    /// The largest value this stat can reach as a substat, looked up in `SUB_STAT_CAPS`.
+    /// None for stats that never appear as substats
+    pub fn max_sub_stat_value(&self) -> Option<f64> {
+        self.sub_stat_cap(&SUB_STAT_CAPS)
+    }
+
+    /// The cap of this stat in `caps`, None for stats the table leaves out
+    pub fn sub_stat_cap(&self, caps: &[(ArtifactStatName, f64)]) -> Option<f64> {
+        caps.iter().find(|(name, _)| name == self).map(|(_, cap)| *cap)
+    }
+
+    /// OCR may mangle the suffix of `暴击率` / `暴击伤害`, e.g. `暴击`, `暴击伤`
+    fn is_ambiguous_critical(name: &str, is_percentage: bool) -> bool {
+        is_percentage && name.starts_with("暴击")
+    }
+
+    /// Decide between crit rate and crit dmg by the value (as a fraction), when the name is ambiguous.
+    /// A crit rate substat is at most 6 rolls of 3.9%, while a single crit dmg roll is at least 4.4%,
+    /// values in between can be either and are left undecided
+    fn disambiguate_critical(value: f64) -> Option<ArtifactStatName> {
+        const MAX_CRITICAL: f64 = 0.234;
+        const MIN_CRITICAL_DAMAGE: f64 = 0.044;
+
+        if value > MAX_CRITICAL {
+            Some(ArtifactStatName::CriticalDamage)
+        } else if value < MIN_CRITICAL_DAMAGE {
+            Some(ArtifactStatName::Critical)
+        } else {
+            None
+        }
+    }
+}
+
+/// Outcome of checking a substat value against its plausible maximum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubStatCheck {
+    Plausible,
+    /// The value had a dropped decimal point and was divided, holds the original value
+    Corrected(f64),
+    /// Over the cap and no decimal placement brings it under, the value is left as is
+    Suspect,
 }
 
 impl ArtifactStat {
+    /// Check the value of a substat against `cap`, e.g. from `ArtifactStatName::max_sub_stat_value`.
+    /// An over-cap value is assumed to have lost its decimal point (`暴击率+39%` for `3.9%`) and is divided by 10 up to twice
+    pub fn check_sub_stat_value(&mut self, cap: f64) -> SubStatCheck {
+        if self.value <= cap {
+            return SubStatCheck::Plausible;
+        }
+
+        let original = self.value;
+        let mut value = original;
+        for _ in 0..2 {
+            value /= 10.0;
+            if value <= cap {
+                self.value = value;
+                return SubStatCheck::Corrected(original);
+            }
+        }
+
+        SubStatCheck::Suspect
+    }
+
+    /// Whether an artifact of this level can still show a gray (待激活) fourth substat.
+    /// The line unlocks at level 4, and may still be rendered gray right after leveling up to it
+    pub fn may_be_pending(level: i32) -> bool {
+        level <= 4
+    }
+
+    /// The value counted in maximum 5 star substat rolls, so that flat and percentage stats compare on one scale,
+    /// e.g. 3.9% crit rate and 23 elemental mastery are both about 1.0. None for stats that never appear as substats
+    pub fn normalized_value(&self) -> Option<f64> {
+        let max_roll = self.name.max_sub_stat_value()? / 6.0;
+        Some(self.value / max_roll)
+    }
+
+    /// The value as the game shows it, e.g. "7.8%" or "4,780"
+    pub fn value_to_zh_cn_raw(&self) -> String {
+        if self.name.is_percentage() {
+            return format!("{:.1}%", self.value * 100.0);
+        }
+
+        let digits = format!("{}", self.value.round() as i64);
+        let mut grouped = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+        grouped
+    }
+
+    /// The line as the game shows it, e.g. "生命值+4,780", "暴击伤害+7.8%", "暴击率+3.9% (待激活)".
+    /// Parsed back by `from_zh_cn_raw`
+    pub fn to_zh_cn_raw(&self) -> String {
+        let mut s = format!("{}+{}", self.name.to_zh_cn(), self.value_to_zh_cn_raw());
+        if self.pending {
+            s.push_str(" (待激活)");
+        }
+        s
+    }
+
     // e.g "生命值+4,123", "暴击率+10%", "暴击伤害+7.8% (待激活)"
     pub fn from_zh_cn_raw(s: &str) -> Option<ArtifactStat> {
+        Self::from_raw_with(s, ArtifactStatName::from_zh_cn, None)
+    }
+
+    // e.g "HP+4,123", "CRIT Rate+10%", "CRIT DMG+7.8% (unactivated)"
+    pub fn from_en_raw(s: &str) -> Option<ArtifactStat> {
+        Self::from_raw_with(s, ArtifactStatName::from_en, None)
+    }
+
+    /// Parse a stat line recognized from a game client in `language`
+    pub fn from_raw(s: &str, language: Language) -> Option<ArtifactStat> {
+        match language {
+            Language::ZhCn => Self::from_zh_cn_raw(s),
+            Language::En => Self::from_en_raw(s),
+        }
+    }
+
+    /// Same as `from_zh_cn_raw`, but a mangled crit name is decided by the value.
+    /// Only for sub stats, a crit main stat can have any value up to 62.2%
+    pub fn from_zh_cn_sub_raw(s: &str) -> Option<ArtifactStat> {
+        Self::from_raw_with(s, ArtifactStatName::from_zh_cn, Some(ArtifactStatName::is_ambiguous_critical))
+    }
+
+    /// Same as `from_en_raw`, but a mangled crit name is decided by the value, see `from_zh_cn_sub_raw`
+    pub fn from_en_sub_raw(s: &str) -> Option<ArtifactStat> {
+        Self::from_raw_with(s, ArtifactStatName::from_en, Some(ArtifactStatName::is_ambiguous_critical_en))
+    }
+
+    /// Parse a sub stat line recognized from a game client in `language`
+    pub fn from_sub_raw(s: &str, language: Language) -> Option<ArtifactStat> {
+        match language {
+            Language::ZhCn => Self::from_zh_cn_sub_raw(s),
+            Language::En => Self::from_en_sub_raw(s),
+        }
+    }
+
+    /// `name+value` with the name looked up by `from_name`. With `is_ambiguous_critical`,
+    /// a mangled crit name it accepts is decided by the value
+    fn from_raw_with(
+        s: &str,
+        from_name: fn(&str, bool) -> Option<ArtifactStatName>,
+        is_ambiguous_critical: Option<fn(&str, bool) -> bool>,
+    ) -> Option<ArtifactStat> {
         let temp: Vec<&str> = s.split('+').collect();
         if temp.len() != 2 {
             return None;
@@ -184,13 +377,15 @@ impl ArtifactStat {
         let value_str = temp[1].trim();
 
         let is_percentage = value_str.contains('%');
-        let stat_name = match ArtifactStatName::from_zh_cn(stat_name_str, is_percentage) {
-            Some(v) => v,
-            None => return None,
-        };
+        let stat_name = from_name(stat_name_str, is_percentage);
+        let guess_critical = is_ambiguous_critical.is_some_and(|f| f(stat_name_str, is_percentage));
+        if stat_name.is_none() && !guess_critical {
+            return None;
+        }
 
         // Determine pending status (robust check)
-        let pending = value_str.contains("待") || value_str.to_lowercase().contains("pending");
+        let lower = value_str.to_lowercase();
+        let pending = value_str.contains("待") || lower.contains("pending") || lower.contains("unactivated");
 
         // Extract value using Regex to ignore noise/suffixes
         let re = Regex::new(r"[\d,]+(\.\d+)?").unwrap();
@@ -214,6 +409,17 @@ impl ArtifactStat {
             value /= 100.0;
         }
 
+        let stat_name = match stat_name {
+            Some(v) => v,
+            None => match ArtifactStatName::disambiguate_critical(value) {
+                Some(v) => {
+                    warn!("stat `{}` name corrected to {}", s, v);
+                    v
+                },
+                None => return None,
+            },
+        };
+
         Some(ArtifactStat {
             name: stat_name,
             value,
@@ -222,37 +428,114 @@ impl ArtifactStat {
     }
 }
 
+impl UnnamedSubStat {
+    /// A line that is only a value, e.g. "+3.9%" or "19". Lines with a name are left to `ArtifactStat::from_raw`
+    pub fn from_value_only(index: usize, s: &str) -> Option<UnnamedSubStat> {
+        let re = Regex::new(r"^\+?\s*([\d,]+(\.\d+)?)\s*(%?)\s*(.*)$").unwrap();
+        let captures = re.captures(s.trim())?;
+
+        let suffix = captures[4].to_lowercase();
+        let pending = suffix.contains("待") || suffix.contains("pending") || suffix.contains("unactivated");
+        // anything else after the value means this is not a bare value
+        if !suffix.is_empty() && !pending {
+            return None;
+        }
+
+        let is_percentage = !captures[3].is_empty();
+        let mut value = captures[1].replace(',', "").parse::<f64>().ok()?;
+        if is_percentage {
+            value /= 100.0;
+        }
+
+        Some(UnnamedSubStat {
+            index,
+            value,
+            is_percentage,
+            pending,
+        })
+    }
+}
+
+impl GenshinArtifact {
+    /// Substat lines of `result` that were recognized without their name, see `UnnamedSubStat`
+    pub fn unnamed_sub_stats(result: &GenshinArtifactScanResult) -> Vec<UnnamedSubStat> {
+        result.sub_stat.iter()
+            .enumerate()
+            .filter(|(_, line)| ArtifactStat::from_zh_cn_sub_raw(line).is_none())
+            .filter_map(|(index, line)| UnnamedSubStat::from_value_only(index, line))
+            .collect()
+    }
+}
+
 impl TryFrom<&GenshinArtifactScanResult> for GenshinArtifact {
     type Error = ();
 
     fn try_from(value: &GenshinArtifactScanResult) -> Result<Self, Self::Error> {
-        let set_name = ArtifactSetName::from_zh_cn(&value.name).ok_or(())?;
-        let slot = ArtifactSlot::from_zh_cn(&value.name).ok_or(())?;
+        GenshinArtifact::from_scan_result(value).map_err(|_| ())
+    }
+}
+
+impl GenshinArtifact {
+    /// Same as `TryFrom`, with an error naming the field that failed. Sub stats that cannot be parsed are left out
+    pub(crate) fn from_scan_result(value: &GenshinArtifactScanResult) -> Result<Self, ArtifactParseError> {
+        Self::from_scan_result_with_caps(value, &SUB_STAT_CAPS)
+    }
+
+    /// Same as `from_scan_result`, with over-cap substat values checked against `caps` instead of `SUB_STAT_CAPS`
+    pub fn from_scan_result_with_caps(
+        value: &GenshinArtifactScanResult,
+        caps: &[(ArtifactStatName, f64)],
+    ) -> Result<Self, ArtifactParseError> {
+        let description = Some(value.description.as_str()).filter(|s| !s.is_empty());
+        let set_name = ArtifactSetName::from_zh_cn_with_description(&value.name, description)
+            .ok_or_else(|| ArtifactParseError::UnknownSetName(value.name.clone()))?;
         let star = value.star;
         let lock = value.lock;
 
-        let main_stat = ArtifactStat::from_zh_cn_raw(
-            (value.main_stat_name.clone() + "+" + value.main_stat_value.as_str()).as_str(),
-        )
-        .ok_or(())?;
+        let main_stat_str = value.main_stat_name.clone() + "+" + value.main_stat_value.as_str();
+        let main_stat = ArtifactStat::from_zh_cn_raw(main_stat_str.as_str())
+            .ok_or(ArtifactParseError::BadMainStat(main_stat_str))?;
 
-        let sub1 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[0]);
-        let sub2 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[1]);
-        let sub3 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[2]);
-        let sub4 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[3]);
+        // the piece name decides the slot, the main stat only helps for names too far from every piece name
+        let slot = ArtifactSlot::from_zh_cn(&value.name)
+            .or_else(|| ArtifactSlot::from_main_stat(&main_stat.name))
+            .ok_or_else(|| ArtifactParseError::UnknownSlot(value.name.clone()))?;
 
-        let equip = if value.equip.ends_with("已装备") {
-            let chars = value.equip.chars().collect::<Vec<_>>();
-            let equip_name = chars[..chars.len() - 3].iter().collect::<String>();
+        let mut sub1 = ArtifactStat::from_zh_cn_sub_raw(&value.sub_stat[0]);
+        let mut sub2 = ArtifactStat::from_zh_cn_sub_raw(&value.sub_stat[1]);
+        let mut sub3 = ArtifactStat::from_zh_cn_sub_raw(&value.sub_stat[2]);
+        let mut sub4 = ArtifactStat::from_zh_cn_sub_raw(&value.sub_stat[3]);
 
-            if CHARACTER_NAMES.contains(equip_name.as_str()) {
-                Some(equip_name)
-            } else {
-                None
+        for unnamed in Self::unnamed_sub_stats(value) {
+            warn!(
+                "sub stat {} of {} has no name, value {} kept for review",
+                unnamed.index + 1, value.name, unnamed.value
+            );
+        }
+
+        for stat in [&mut sub1, &mut sub2, &mut sub3, &mut sub4].into_iter().flatten() {
+            let cap = match stat.name.sub_stat_cap(caps) {
+                Some(v) => v,
+                None => continue,
+            };
+            match stat.check_sub_stat_value(cap) {
+                SubStatCheck::Plausible => {},
+                SubStatCheck::Corrected(original) => warn!("sub stat {} value {} over cap, corrected to {}", stat.name, original, stat.value),
+                SubStatCheck::Suspect => error!("sub stat {} value {} over cap {}, please check", stat.name, stat.value, cap),
             }
-        } else {
-            None
-        };
+        }
+
+        // only the fourth line can be pending, and only until leveling unlocks it
+        for (index, stat) in [&mut sub1, &mut sub2, &mut sub3, &mut sub4].into_iter().enumerate() {
+            if let Some(stat) = stat {
+                if stat.pending && (index != 3 || !ArtifactStat::may_be_pending(value.level)) {
+                    warn!("sub stat {} of a level {} artifact cannot be pending, flag ignored", index + 1, value.level);
+                    stat.pending = false;
+                }
+            }
+        }
+
+        let equip = equip_from_zh_cn_raw(&value.equip);
 
         Ok(GenshinArtifact {
             set_name,
@@ -270,9 +553,71 @@ impl TryFrom<&GenshinArtifactScanResult> for GenshinArtifact {
     }
 }
 
+/// Every artifact piece name, each belongs to the set `ArtifactSetName::from_zh_cn_exact` and the slot `ArtifactSlot::from_zh_cn` give
+#[rustfmt::skip]
+const PIECE_NAMES_ZH_CN: &[&str] = &[
+    "磐陀裂生之花", "嵯峨群峰之翼", "星罗圭壁之晷", "星罗圭璧之晷", "巉岩琢塑之樽", "不动玄石之相",
+    "历经风雪的思念", "摧冰而行的执望", "冰雪故园的终期", "遍结寒霜的傲骨", "破冰踏雪的回音",
+    "染血的铁之心", "染血的黑之羽", "骑士染血之时", "染血骑士之杯", "染血的铁假面",
+    "魔女的炎之花", "魔女常燃之羽", "魔女破灭之时", "魔女的心之火", "焦灼的魔女帽",
+    "角斗士的留恋", "角斗士的归宿", "角斗士的希冀", "角斗士的酣醉", "角斗士的凯旋",
+    "饰金胸花", "追忆之风", "坚铜罗盘", "沉波之盏", "酒渍船帽",
+    "渡火者的决绝", "渡火者的解脱", "渡火者的煎熬", "渡火者的醒悟", "渡火者的智慧",
+    "远方的少女之心", "少女飘摇的思念", "少女苦短的良辰", "少女片刻的闲暇", "少女易逝的芳颜",
+    "宗室之花", "宗室之翎", "宗室时计", "宗室银瓮", "宗室面具",
+    "夏祭之花", "夏祭终末", "夏祭之刻", "夏祭水玉", "夏祭之面",
+    "平雷之心", "平雷之羽", "平雷之刻", "平雷之器", "平雷之冠",
+    "雷鸟的怜悯", "雷灾的孑遗", "雷霆的时计", "降雷的凶兆", "唤雷的头冠",
+    "野花记忆的绿野", "猎人青翠的箭羽", "翠绿猎人的笃定", "翠绿猎人的容器", "翠绿的猎人之冠",
+    "乐团的晨光", "琴师的箭羽", "终幕的时计", "终末的时计", "吟游者之壶", "指挥的礼帽",
+    "战狂的蔷薇", "战狂的翎羽", "战狂的时计", "战狂的骨杯", "战狂的鬼面",
+    "勇士的勋章", "勇士的期许", "勇士的坚毅", "勇士的壮行", "勇士的冠冕",
+    "守护之花", "守护徽印", "守护座钟", "守护之皿", "守护束带",
+    "流放者之花", "流放者之羽", "流放者怀表", "流放者之杯", "流放者头冠",
+    "赌徒的胸花", "赌徒的羽饰", "赌徒的怀表", "赌徒的骰盅", "赌徒的耳环",
+    "教官的胸花", "教官的羽饰", "教官的怀表", "教官的茶杯", "教官的帽子",
+    "武人的红花", "武人的羽饰", "武人的水漏", "武人的酒杯", "武人的头巾",
+    "祭水礼冠", "祭火礼冠", "祭雷礼冠", "祭冰礼冠",
+    "故人之心", "归乡之羽", "逐光之石", "异国之盏", "感别之冠",
+    "学士的书签", "学士的羽笔", "学士的时钟", "学士的墨杯", "学士的镜片",
+    "奇迹之花", "奇迹之羽", "奇迹之沙", "奇迹之杯", "奇迹耳坠",
+    "冒险家之花", "冒险家尾羽", "冒险家怀表", "冒险家金杯", "冒险家头带",
+    "幸运儿绿花", "幸运儿鹰羽", "幸运儿沙漏", "幸运儿之杯", "幸运儿银冠",
+    "游医的银莲", "游医的枭羽", "游医的怀钟", "游医的药壶", "游医的方巾",
+    "勋绩之花", "昭武翎羽", "金铜时晷", "盟誓金爵", "将帅兜鍪",
+    "无垢之花", "贤医之羽", "停摆之刻", "超越之盏", "嗤笑之面",
+    "明威之镡", "切落之羽", "雷云之笼", "绯花之壶", "华饰之兜",
+    "羁缠之花", "思忆之矢", "朝露之时", "祈望之心", "无常之面",
+    "荣花之期", "华馆之羽", "众生之谣", "梦醒之瓢", "形骸之笠",
+    "海染之花", "渊宫之羽", "离别之贝", "真珠之笼", "海祇之冠",
+    "生灵之华", "阳辔之遗", "潜光片羽", "结契之刻", "虺雷之姿",
+    "魂香之花", "祝祀之凭", "垂玉之叶", "涌泉之盏", "浮溯之珏",
+    "迷宫的游人", "翠蔓的智者", "贤智的定期", "迷误者之灯", "月桂的宝冠",
+    "梦中的铁花", "裁断的翎羽", "沉金的岁月", "如蜜的终宴", "沙王的投影",
+    "月女的华彩", "谢落的筵席", "凝结的时刻", "守秘的魔瓶", "紫晶的花冠",
+    "众王之都的开端", "黄金邦国的结末", "失落迷途的机芯", "迷醉长梦的守护", "流沙贵嗣的遗宝",
+    "恶龙的单片镜", "坏巫师的羽杖", "旅途中的鲜花", "水仙的时时刻刻", "勇者们的茶会",
+    "灵光明烁之心", "琦色灵彩之羽", "灵光源起之蕊", "久远花落之时", "无边酣乐之筵",
+    "猎人的胸花", "杰作的序曲", "裁判的时刻", "遗忘的容器", "老兵的容颜",
+    "黄金乐曲的变奏", "黄金飞鸟的落羽", "黄金时代的先声", "黄金之夜的喧嚣", "黄金剧团的奖赏",
+    "昔时传奏之诗", "昔时浮想之思", "昔时遗落之誓", "昔时回映之音", "昔时应许之梦",
+    "慈爱的淑女帽", "诚恳的蘸水笔", "无私的妆饰花", "忠实的砂时计", "慷慨的墨水瓶",
+    "异想零落的圆舞", "古海玄幽的夜想", "谐律交响的前奏", "命途轮转的谐谑", "灵露倾洒的狂诗",
+    "失冕的宝冠", "褪光的翠尾", "暗结的明花", "举业的识刻", "筹谋的共樽",
+    "魔战士的羽面", "巡山客的信标", "驯兽师的护符", "秘术家的金盘", "游学者的爪杯",
+    "诸圣的礼冠", "灵髓的根脉", "异种的期许", "夜域的迷思", "夜鸣莺的尾羽", "纷争的前宴",
+    "被浸染的缨盔", "执灯人的誓词", "不死者的哀铃", "未吹响的号角",
+    "深廊的遂失之冕", "深廊的漫远之约", "深廊的回奏之歌", "深廊的湮落之刻", "深廊的饫赐之宴",
+    "永劫之冕", "深罪之羽", "渴真之花", "谕告之钟", "满溢之壶",
+    "司信者的圣冕", "受福者的白羽", "流离者的晶泪", "祭霜者的迷狂", "至纯者的欢荣",
+    "哀慕的恋歌", "晨光的明誓", "风花的箴铭", "春律的片刻", "未言的宴话",
+    "献与月的银冕", "献与月的离光", "献与月的华梦", "献与月的终时", "献与月的酹祭",
+];
+
 impl ArtifactSetName {
+    /// Only piece names that match exactly
     #[rustfmt::skip]
-    pub fn from_zh_cn(s: &str) -> Option<ArtifactSetName> {
+    pub fn from_zh_cn_exact(s: &str) -> Option<ArtifactSetName> {
         match s {
             "磐陀裂生之花" | "嵯峨群峰之翼" | "星罗圭壁之晷" | "星罗圭璧之晷" | "巉岩琢塑之樽" | "不动玄石之相" => Some(ArtifactSetName::ArchaicPetra),
             "历经风雪的思念" | "摧冰而行的执望" | "冰雪故园的终期" | "遍结寒霜的傲骨" | "破冰踏雪的回音" => Some(ArtifactSetName::BlizzardStrayer),
@@ -334,92 +679,97 @@ impl ArtifactSetName {
             "哀慕的恋歌"| "晨光的明誓" | "风花的箴铭" | "春律的片刻" | "未言的宴话" => Some(ArtifactSetName::ADayCarvedFromRisingWinds),
             "献与月的银冕"| "献与月的离光" | "献与月的华梦" | "献与月的终时" | "献与月的酹祭" => Some(ArtifactSetName::AubadeOfMorningstarAndMoon),
 
-            _ => {
-                // Fuzzy matching
-                let mut best_match = None;
-                let mut min_dist = usize::MAX;
+            _ => None,
+        }
+    }
 
-                let all_names = vec![
-                    "磐陀裂生之花", "嵯峨群峰之翼", "星罗圭壁之晷", "星罗圭璧之晷", "巉岩琢塑之樽", "不动玄石之相",
-                    "历经风雪的思念", "摧冰而行的执望", "冰雪故园的终期", "遍结寒霜的傲骨", "破冰踏雪的回音",
-                    "染血的铁之心", "染血的黑之羽", "骑士染血之时", "染血骑士之杯", "染血的铁假面",
-                    "魔女的炎之花", "魔女常燃之羽", "魔女破灭之时", "魔女的心之火", "焦灼的魔女帽",
-                    "角斗士的留恋", "角斗士的归宿", "角斗士的希冀", "角斗士的酣醉", "角斗士的凯旋",
-                    "饰金胸花", "追忆之风", "坚铜罗盘", "沉波之盏", "酒渍船帽",
-                    "渡火者的决绝", "渡火者的解脱", "渡火者的煎熬", "渡火者的醒悟", "渡火者的智慧",
-                    "远方的少女之心", "少女飘摇的思念", "少女苦短的良辰", "少女片刻的闲暇", "少女易逝的芳颜",
-                    "宗室之花", "宗室之翎", "宗室时计", "宗室银瓮", "宗室面具",
-                    "夏祭之花", "夏祭终末", "夏祭之刻", "夏祭水玉", "夏祭之面",
-                    "平雷之心", "平雷之羽", "平雷之刻", "平雷之器", "平雷之冠",
-                    "雷鸟的怜悯", "雷灾的孑遗", "雷霆的时计", "降雷的凶兆", "唤雷的头冠",
-                    "野花记忆的绿野", "猎人青翠的箭羽", "翠绿猎人的笃定", "翠绿猎人的容器", "翠绿的猎人之冠",
-                    "乐团的晨光", "琴师的箭羽", "终幕的时计", "终末的时计", "吟游者之壶", "指挥的礼帽",
-                    "战狂的蔷薇", "战狂的翎羽", "战狂的时计", "战狂的骨杯", "战狂的鬼面",
-                    "勇士的勋章", "勇士的期许", "勇士的坚毅", "勇士的壮行", "勇士的冠冕",
-                    "守护之花", "守护徽印", "守护座钟", "守护之皿", "守护束带",
-                    "流放者之花", "流放者之羽", "流放者怀表", "流放者之杯", "流放者头冠",
-                    "赌徒的胸花", "赌徒的羽饰", "赌徒的怀表", "赌徒的骰盅", "赌徒的耳环",
-                    "教官的胸花", "教官的羽饰", "教官的怀表", "教官的茶杯", "教官的帽子",
-                    "武人的红花", "武人的羽饰", "武人的水漏", "武人的酒杯", "武人的头巾",
-                    "祭水礼冠", "祭火礼冠", "祭雷礼冠", "祭冰礼冠",
-                    "故人之心", "归乡之羽", "逐光之石", "异国之盏", "感别之冠",
-                    "学士的书签", "学士的羽笔", "学士的时钟", "学士的墨杯", "学士的镜片",
-                    "奇迹之花", "奇迹之羽", "奇迹之沙", "奇迹之杯", "奇迹耳坠",
-                    "冒险家之花", "冒险家尾羽", "冒险家怀表", "冒险家金杯", "冒险家头带",
-                    "幸运儿绿花", "幸运儿鹰羽", "幸运儿沙漏", "幸运儿之杯", "幸运儿银冠",
-                    "游医的银莲", "游医的枭羽", "游医的怀钟", "游医的药壶", "游医的方巾",
-                    "勋绩之花", "昭武翎羽", "金铜时晷", "盟誓金爵", "将帅兜鍪",
-                    "无垢之花", "贤医之羽", "停摆之刻", "超越之盏", "嗤笑之面",
-                    "明威之镡", "切落之羽", "雷云之笼", "绯花之壶", "华饰之兜",
-                    "羁缠之花", "思忆之矢", "朝露之时", "祈望之心", "无常之面",
-                    "荣花之期", "华馆之羽", "众生之谣", "梦醒之瓢", "形骸之笠",
-                    "海染之花", "渊宫之羽", "离别之贝", "真珠之笼", "海祇之冠",
-                    "生灵之华", "阳辔之遗", "潜光片羽", "结契之刻", "虺雷之姿",
-                    "魂香之花", "祝祀之凭", "垂玉之叶", "涌泉之盏", "浮溯之珏",
-                    "迷宫的游人", "翠蔓的智者", "贤智的定期", "迷误者之灯", "月桂的宝冠",
-                    "梦中的铁花", "裁断的翎羽", "沉金的岁月", "如蜜的终宴", "沙王的投影",
-                    "月女的华彩", "谢落的筵席", "凝结的时刻", "守秘的魔瓶", "紫晶的花冠",
-                    "众王之都的开端", "黄金邦国的结末", "失落迷途的机芯", "迷醉长梦的守护", "流沙贵嗣的遗宝",
-                    "恶龙的单片镜", "坏巫师的羽杖", "旅途中的鲜花", "水仙的时时刻刻", "勇者们的茶会",
-                    "灵光明烁之心", "琦色灵彩之羽", "灵光源起之蕊", "久远花落之时", "无边酣乐之筵",
-                    "猎人的胸花", "杰作的序曲", "裁判的时刻", "遗忘的容器", "老兵的容颜",
-                    "黄金乐曲的变奏", "黄金飞鸟的落羽", "黄金时代的先声", "黄金之夜的喧嚣", "黄金剧团的奖赏",
-                    "昔时传奏之诗", "昔时浮想之思", "昔时遗落之誓", "昔时回映之音", "昔时应许之梦",
-                    "慈爱的淑女帽", "诚恳的蘸水笔", "无私的妆饰花", "忠实的砂时计", "慷慨的墨水瓶",
-                    "异想零落的圆舞", "古海玄幽的夜想", "谐律交响的前奏", "命途轮转的谐谑", "灵露倾洒的狂诗",
-                    "失冕的宝冠", "褪光的翠尾", "暗结的明花", "举业的识刻", "筹谋的共樽",
-                    "魔战士的羽面", "巡山客的信标", "驯兽师的护符", "秘术家的金盘", "游学者的爪杯",
-                    "诸圣的礼冠", "灵髓的根脉", "异种的期许", "夜鸣莺的尾羽", "纷争的前宴",
-                    "被浸染的缨盔", "执灯人的誓词", "不死者的哀铃", "未吹响的号角",
-                    "深廊的遂失之冕", "深廊的漫远之约", "深廊的回奏之歌", "深廊的湮落之刻", "深廊的饫赐之宴",
-                    "永劫之冕", "深罪之羽", "渴真之花", "谕告之钟", "满溢之壶",
-                    "司信者的圣冕", "受福者的白羽", "流离者的晶泪", "祭霜者的迷狂", "至纯者的欢荣",
-                    "哀慕的恋歌", "晨光的明誓", "风花的箴铭", "春律的片刻", "未言的宴话",
-                    "献与月的银冕", "献与月的离光", "献与月的华梦", "献与月的终时", "献与月的酹祭",
-                ];
-
-                for name in all_names {
-                    let dist = edit_distance::edit_distance(s, name);
-                    if dist < min_dist {
-                        min_dist = dist;
-                        best_match = Some(name);
-                    }
-                }
+    #[rustfmt::skip]
+    pub fn from_zh_cn(s: &str) -> Option<ArtifactSetName> {
+        if let Some(v) = Self::from_zh_cn_exact(s) {
+            return Some(v);
+        }
 
-                if let Some(best) = best_match {
-                    let limit = if s.len() < 4 { 1 } else { 2 };
-                    if min_dist <= limit {
-                        return Self::from_zh_cn(best);
-                    }
+        // Fuzzy matching
+        let mut best_match = None;
+        let mut min_dist = usize::MAX;
+
+        for name in PIECE_NAMES_ZH_CN.iter().copied() {
+            let dist = edit_distance::edit_distance(s, name);
+            if dist < min_dist {
+                min_dist = dist;
+                best_match = Some(name);
+            }
+        }
+
+        if let Some(best) = best_match {
+            let limit = if s.len() < 4 { 1 } else { 2 };
+            if min_dist <= limit {
+                return Self::from_zh_cn_exact(best);
+            }
+        }
+
+        None
+    }
+
+    /// The piece of this set in `slot`, e.g. `角斗士的留恋` for the flower of `GladiatorFinale`
+    pub fn piece_name_zh_cn(&self, slot: &ArtifactSlot) -> Option<&'static str> {
+        PIECE_NAMES_ZH_CN.iter().copied().find(|name| {
+            Self::from_zh_cn_exact(name).as_ref() == Some(self) && ArtifactSlot::from_zh_cn(name).as_ref() == Some(slot)
+        })
+    }
+
+    /// Match the set name line above the set bonus description, e.g. `角斗士的终幕礼：`
+    pub fn from_zh_cn_description(s: &str) -> Option<ArtifactSetName> {
+        let name = s.split([':', '：']).next().unwrap_or("").trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let (best, dist) = ArtifactSetName::iter()
+            .map(|set| {
+                let dist = edit_distance::edit_distance(name, set.to_zh_cn());
+                (set, dist)
+            })
+            .min_by_key(|(_, dist)| *dist)?;
+
+        let limit = if name.chars().count() < 4 { 0 } else { 1 };
+        if dist <= limit {
+            Some(best)
+        } else {
+            None
+        }
+    }
+
+    /// Resolve the set from the title, cross-checked with the set name line of the description (if read).
+    /// A title that matches a piece name exactly is trusted, otherwise a recognized description decides
+    pub fn from_zh_cn_with_description(title: &str, description: Option<&str>) -> Option<ArtifactSetName> {
+        if let Some(v) = Self::from_zh_cn_exact(title) {
+            return Some(v);
+        }
+
+        let from_title = Self::from_zh_cn(title);
+        match description.and_then(Self::from_zh_cn_description) {
+            Some(set) => {
+                if from_title.as_ref() != Some(&set) {
+                    warn!("title `{}` matched {:?}, set description says {}", title, from_title, set);
                 }
-                
-                None
+                Some(set)
             },
+            None => from_title,
         }
     }
 }
 
 impl ArtifactSlot {
+    /// Only flowers and plumes have a fixed main stat, flat HP and flat ATK
+    pub fn from_main_stat(name: &ArtifactStatName) -> Option<ArtifactSlot> {
+        match name {
+            ArtifactStatName::Hp => Some(ArtifactSlot::Flower),
+            ArtifactStatName::Atk => Some(ArtifactSlot::Feather),
+            _ => None,
+        }
+    }
+
     pub fn from_zh_cn(s: &str) -> Option<ArtifactSlot> {
         match s {
             "磐陀裂生之花" => Some(ArtifactSlot::Flower),
@@ -708,66 +1058,7 @@ impl ArtifactSlot {
                 let mut best_match = None;
                 let mut min_dist = usize::MAX;
 
-                let all_slots = vec![
-                    "磐陀裂生之花", "嵯峨群峰之翼", "星罗圭壁之晷", "星罗圭璧之晷", "巉岩琢塑之樽", "不动玄石之相",
-                    "历经风雪的思念", "摧冰而行的执望", "冰雪故园的终期", "遍结寒霜的傲骨", "破冰踏雪的回音",
-                    "染血的铁之心", "染血的黑之羽", "骑士染血之时", "染血骑士之杯", "染血的铁假面",
-                    "魔女的炎之花", "魔女常燃之羽", "魔女破灭之时", "魔女的心之火", "焦灼的魔女帽",
-                    "角斗士的留恋", "角斗士的归宿", "角斗士的希冀", "角斗士的酣醉", "角斗士的凯旋",
-                    "饰金胸花", "追忆之风", "坚铜罗盘", "沉波之盏", "酒渍船帽",
-                    "渡火者的决绝", "渡火者的解脱", "渡火者的煎熬", "渡火者的醒悟", "渡火者的智慧",
-                    "远方的少女之心", "少女飘摇的思念", "少女苦短的良辰", "少女片刻的闲暇", "少女易逝的芳颜",
-                    "宗室之花", "宗室之翎", "宗室时计", "宗室银瓮", "宗室面具",
-                    "夏祭之花", "夏祭终末", "夏祭之刻", "夏祭水玉", "夏祭之面",
-                    "平雷之心", "平雷之羽", "平雷之刻", "平雷之器", "平雷之冠",
-                    "雷鸟的怜悯", "雷灾的孑遗", "雷霆的时计", "降雷的凶兆", "唤雷的头冠",
-                    "野花记忆的绿野", "猎人青翠的箭羽", "翠绿猎人的笃定", "翠绿猎人的容器", "翠绿的猎人之冠",
-                    "乐团的晨光", "琴师的箭羽", "终幕的时计", "终末的时计", "吟游者之壶", "指挥的礼帽",
-                    "战狂的蔷薇", "战狂的翎羽", "战狂的时计", "战狂的骨杯", "战狂的鬼面",
-                    "勇士的勋章", "勇士的期许", "勇士的坚毅", "勇士的壮行", "勇士的冠冕",
-                    "守护之花", "守护徽印", "守护座钟", "守护之皿", "守护束带",
-                    "流放者之花", "流放者之羽", "流放者怀表", "流放者之杯", "流放者头冠",
-                    "赌徒的胸花", "赌徒的羽饰", "赌徒的怀表", "赌徒的骰盅", "赌徒的耳环",
-                    "教官的胸花", "教官的羽饰", "教官的怀表", "教官的茶杯", "教官的帽子",
-                    "武人的红花", "武人的羽饰", "武人的水漏", "武人的酒杯", "武人的头巾",
-                    "祭水礼冠", "祭火礼冠", "祭雷礼冠", "祭冰礼冠",
-                    "故人之心", "归乡之羽", "逐光之石", "异国之盏", "感别之冠",
-                    "学士的书签", "学士的羽笔", "学士的时钟", "学士的墨杯", "学士的镜片",
-                    "奇迹之花", "奇迹之羽", "奇迹之沙", "奇迹之杯", "奇迹耳坠",
-                    "冒险家之花", "冒险家尾羽", "冒险家怀表", "冒险家金杯", "冒险家头带",
-                    "幸运儿绿花", "幸运儿鹰羽", "幸运儿沙漏", "幸运儿之杯", "幸运儿银冠",
-                    "游医的银莲", "游医的枭羽", "游医的怀钟", "游医的药壶", "游医的方巾",
-                    "勋绩之花", "昭武翎羽", "金铜时晷", "盟誓金爵", "将帅兜鍪",
-                    "无垢之花", "贤医之羽", "停摆之刻", "超越之盏", "嗤笑之面",
-                    "明威之镡", "切落之羽", "雷云之笼", "绯花之壶", "华饰之兜",
-                    "羁缠之花", "思忆之矢", "朝露之时", "祈望之心", "无常之面",
-                    "荣花之期", "华馆之羽", "众生之谣", "梦醒之瓢", "形骸之笠",
-                    "海染之花", "渊宫之羽", "离别之贝", "真珠之笼", "海祇之冠",
-                    "生灵之华", "阳辔之遗", "潜光片羽", "结契之刻", "虺雷之姿",
-                    "魂香之花", "祝祀之凭", "垂玉之叶", "涌泉之盏", "浮溯之珏",
-                    "迷宫的游人", "翠蔓的智者", "贤智的定期", "迷误者之灯", "月桂的宝冠",
-                    "梦中的铁花", "裁断的翎羽", "沉金的岁月", "如蜜的终宴", "沙王的投影",
-                    "月女的华彩", "谢落的筵席", "凝结的时刻", "守秘的魔瓶", "紫晶的花冠",
-                    "众王之都的开端", "黄金邦国的结末", "失落迷途的机芯", "迷醉长梦的守护", "流沙贵嗣的遗宝",
-                    "恶龙的单片镜", "坏巫师的羽杖", "旅途中的鲜花", "水仙的时时刻刻", "勇者们的茶会",
-                    "灵光明烁之心", "琦色灵彩之羽", "灵光源起之蕊", "久远花落之时", "无边酣乐之筵",
-                    "猎人的胸花", "杰作的序曲", "裁判的时刻", "遗忘的容器", "老兵的容颜",
-                    "黄金乐曲的变奏", "黄金飞鸟的落羽", "黄金时代的先声", "黄金之夜的喧嚣", "黄金剧团的奖赏",
-                    "昔时传奏之诗", "昔时浮想之思", "昔时遗落之誓", "昔时回映之音", "昔时应许之梦",
-                    "慈爱的淑女帽", "诚恳的蘸水笔", "无私的妆饰花", "忠实的砂时计", "慷慨的墨水瓶",
-                    "异想零落的圆舞", "古海玄幽的夜想", "谐律交响的前奏", "命途轮转的谐谑", "灵露倾洒的狂诗",
-                    "失冕的宝冠", "褪光的翠尾", "暗结的明花", "举业的识刻", "筹谋的共樽",
-                    "魔战士的羽面", "巡山客的信标", "驯兽师的护符", "秘术家的金盘", "游学者的爪杯",
-                    "诸圣的礼冠", "灵髓的根脉", "异种的期许", "夜鸣莺的尾羽", "纷争的前宴",
-                    "被浸染的缨盔", "执灯人的誓词", "不死者的哀铃", "未吹响的号角",
-                    "深廊的遂失之冕", "深廊的漫远之约", "深廊的回奏之歌", "深廊的湮落之刻", "深廊的饫赐之宴",
-                    "永劫之冕", "深罪之羽", "渴真之花", "谕告之钟", "满溢之壶",
-                    "司信者的圣冕", "受福者的白羽", "流离者的晶泪", "祭霜者的迷狂", "至纯者的欢荣",
-                    "哀慕的恋歌", "晨光的明誓", "风花的箴铭", "春律的片刻", "未言的宴话",
-                    "献与月的银冕", "献与月的离光", "献与月的华梦", "献与月的终时", "献与月的酹祭",
-                ];
-
-                for slot in all_slots {
+                for slot in PIECE_NAMES_ZH_CN.iter().copied() {
                     let dist = edit_distance::edit_distance(s, slot);
                     if dist < min_dist {
                         min_dist = dist;
@@ -791,6 +1082,165 @@ impl ArtifactSlot {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_is_percentage() {
+        use ArtifactStatName::*;
+
+        let table = [
+            (HealingBonus, true),
+            (CriticalDamage, true),
+            (Critical, true),
+            (Atk, false),
+            (AtkPercentage, true),
+            (ElementalMastery, false),
+            (Recharge, true),
+            (HpPercentage, true),
+            (Hp, false),
+            (DefPercentage, true),
+            (Def, false),
+            (ElectroBonus, true),
+            (PyroBonus, true),
+            (HydroBonus, true),
+            (CryoBonus, true),
+            (AnemoBonus, true),
+            (GeoBonus, true),
+            (PhysicalBonus, true),
+            (DendroBonus, true),
+        ];
+        for name in ArtifactStatName::iter() {
+            let expected = table.iter().find(|(n, _)| *n == name).map(|(_, p)| *p);
+            assert_eq!(Some(name.is_percentage()), expected, "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_normalized_value() {
+        let stat = |s: &str| ArtifactStat::from_zh_cn_raw(s).unwrap().normalized_value().unwrap();
+        assert!((stat("暴击率+3.9%") - 1.0).abs() < 0.01);
+        assert!((stat("元素精通+23") - 1.0).abs() < 0.02);
+        assert!((stat("攻击力+19") - 1.0).abs() < 0.03);
+        assert!((stat("暴击伤害+15.5%") - 2.0).abs() < 0.01);
+        // a main stat only
+        assert_eq!(ArtifactStat::from_zh_cn_raw("火元素伤害加成+46.6%").unwrap().normalized_value(), None);
+    }
+
+    #[test]
+    fn test_to_zh_cn_raw_round_trip() {
+        for name in ArtifactStatName::iter() {
+            let value = if name.is_percentage() { "7.8%" } else { "4,780" };
+            let raw = format!("{}+{}", name.to_zh_cn(), value);
+            let stat = ArtifactStat::from_zh_cn_raw(&raw).unwrap();
+            assert_eq!(stat.name, name);
+            assert_eq!(stat.to_zh_cn_raw(), raw);
+            assert_eq!(ArtifactStat::from_zh_cn_raw(&stat.to_zh_cn_raw()), Some(stat.clone()));
+
+            let pending = ArtifactStat { pending: true, ..stat };
+            let back = ArtifactStat::from_zh_cn_raw(&pending.to_zh_cn_raw()).unwrap();
+            assert_eq!(back, pending);
+            assert!(back.pending);
+        }
+
+        let stat = ArtifactStat { name: ArtifactStatName::Def, value: 65.0, pending: false };
+        assert_eq!(stat.to_zh_cn_raw(), "防御力+65");
+    }
+
+    #[test]
+    fn test_sub_stat_over_cap() {
+        let mut stat = ArtifactStat::from_zh_cn_raw("暴击率+39%").unwrap();
+        let cap = stat.name.max_sub_stat_value().unwrap();
+        assert_eq!(stat.check_sub_stat_value(cap), SubStatCheck::Corrected(0.39));
+        assert!((stat.value - 0.039).abs() < 1e-6);
+
+        let mut stat = ArtifactStat::from_zh_cn_raw("暴击伤害+78%").unwrap();
+        let cap = stat.name.max_sub_stat_value().unwrap();
+        assert_eq!(stat.check_sub_stat_value(cap), SubStatCheck::Corrected(0.78));
+        assert!((stat.value - 0.078).abs() < 1e-6);
+
+        let mut stat = ArtifactStat::from_zh_cn_raw("元素精通+99999").unwrap();
+        let cap = stat.name.max_sub_stat_value().unwrap();
+        assert_eq!(stat.check_sub_stat_value(cap), SubStatCheck::Suspect);
+        assert_eq!(stat.value, 99999.0);
+
+        let mut stat = ArtifactStat::from_zh_cn_raw("生命值+209").unwrap();
+        assert_eq!(stat.check_sub_stat_value(1793.0), SubStatCheck::Plausible);
+    }
+
+    #[test]
+    fn test_replaced_sub_stat_caps() {
+        let mut scan_result = fixtures::scan_result();
+        scan_result.sub_stat[0] = String::from("暴击率+3.9%");
+
+        let artifact = GenshinArtifact::from_scan_result_with_caps(&scan_result, &SUB_STAT_CAPS).unwrap();
+        assert!((artifact.sub_stat_1.unwrap().value - 0.039).abs() < 1e-6);
+
+        // a table with a lower crit rate cap takes the value for a dropped decimal
+        let caps = [(ArtifactStatName::Critical, 0.01)];
+        let artifact = GenshinArtifact::from_scan_result_with_caps(&scan_result, &caps).unwrap();
+        assert!((artifact.sub_stat_1.unwrap().value - 0.0039).abs() < 1e-6);
+
+        assert_eq!(ArtifactStatName::Critical.sub_stat_cap(&caps), Some(0.01));
+        assert_eq!(ArtifactStatName::Hp.sub_stat_cap(&caps), None);
+    }
+
+    #[test]
+    fn test_deterministic_uuid() {
+        let scan_result = GenshinArtifactScanResult {
+            sub_stat: [
+                String::from("暴击率+3.5%"),
+                String::from("暴击伤害+7.8%"),
+                String::from("攻击力+9.9%"),
+                String::from("元素精通+23"),
+            ],
+            ..fixtures::scan_result()
+        };
+        let rescanned = GenshinArtifactScanResult {
+            sub_stat: [
+                String::from("暴击率+3.5%"),
+                String::from("暴击伤害+7.80%"),
+                String::from("攻击力+9.9%"),
+                String::from("元素精通+23"),
+            ],
+            equip: String::from("胡桃已装备"),
+            lock: true,
+            ..scan_result.clone()
+        };
+        let different = GenshinArtifactScanResult {
+            level: 16,
+            ..scan_result.clone()
+        };
+
+        let uuid = GenshinArtifact::try_from(&scan_result).unwrap().deterministic_uuid();
+        assert_eq!(uuid, GenshinArtifact::try_from(&rescanned).unwrap().deterministic_uuid());
+        assert_ne!(uuid, GenshinArtifact::try_from(&different).unwrap().deterministic_uuid());
+    }
+
+    #[test]
+    fn test_level_4_pending_fourth_sub_stat() {
+        let scan_result = GenshinArtifactScanResult {
+            main_stat_value: String::from("1,893"),
+            sub_stat: [
+                String::from("暴击率+3.9%（待激活）"),
+                String::from("攻击力+5.8%"),
+                String::from("元素充能效率+6.5%"),
+                String::from("暴击伤害+7.8%（待激活）"),
+            ],
+            level: 4,
+            ..fixtures::scan_result()
+        };
+
+        let artifact = GenshinArtifact::try_from(&scan_result).unwrap();
+        assert!(!artifact.sub_stat_1.unwrap().pending);
+        let sub4 = artifact.sub_stat_4.unwrap();
+        assert_eq!(sub4.name, ArtifactStatName::CriticalDamage);
+        assert!((sub4.value - 0.078).abs() < 1e-6);
+        assert!(sub4.pending);
+
+        let leveled = GenshinArtifactScanResult { level: 8, ..scan_result };
+        let artifact = GenshinArtifact::try_from(&leveled).unwrap();
+        assert!(!artifact.sub_stat_4.unwrap().pending);
+    }
 
     #[test]
     fn test_pending_activation() {
@@ -828,6 +1278,97 @@ mod tests {
         assert!(stat_malformed.pending);
     }
 
+    #[test]
+    fn test_from_en_raw() {
+        let stat = ArtifactStat::from_en_raw("CRIT DMG+7.8%").unwrap();
+        assert_eq!(stat.name, ArtifactStatName::CriticalDamage);
+        assert!((stat.value - 0.078).abs() < 1e-6);
+
+        let stat = ArtifactStat::from_en_raw("ATK+5.8% (unactivated)").unwrap();
+        assert_eq!(stat.name, ArtifactStatName::AtkPercentage);
+        assert!(stat.pending);
+
+        let stat = ArtifactStat::from_raw("HP+4,780", Language::En).unwrap();
+        assert_eq!(stat.name, ArtifactStatName::Hp);
+        assert_eq!(stat.value, 4780.0);
+
+        assert!(ArtifactStat::from_en_raw("生命值+4,780").is_none());
+        assert!(ArtifactStat::from_raw("生命值+4,780", Language::ZhCn).is_some());
+    }
+
+    #[test]
+    fn test_ambiguous_critical_name() {
+        let stat = ArtifactStat::from_zh_cn_sub_raw("暴击+25.6%").unwrap();
+        assert_eq!(stat.name, ArtifactStatName::CriticalDamage);
+        assert!((stat.value - 0.256).abs() < 1e-6);
+
+        let stat = ArtifactStat::from_zh_cn_sub_raw("暴击害+3.1%").unwrap();
+        assert_eq!(stat.name, ArtifactStatName::Critical);
+
+        // both stats can have this value, so it stays unparsed
+        assert!(ArtifactStat::from_zh_cn_sub_raw("暴击+10.5%").is_none());
+        // exact names are never touched
+        let stat = ArtifactStat::from_zh_cn_sub_raw("暴击率+25.6%").unwrap();
+        assert_eq!(stat.name, ArtifactStatName::Critical);
+    }
+
+    #[test]
+    fn test_ambiguous_critical_main_stat_not_guessed() {
+        // a crit rate circlet at level 20, the value alone would say crit damage
+        assert!(ArtifactStat::from_zh_cn_raw("暴击+31.1%").is_none());
+
+        let result = GenshinArtifactScanResult {
+            name: String::from("角斗士的凯旋"),
+            main_stat_name: String::from("暴击"),
+            main_stat_value: String::from("31.1%"),
+            sub_stat: [
+                String::from("暴击+3.9%"),
+                String::from("攻击力+19"),
+                String::from("防御力+5.8%"),
+                String::from("元素精通+23"),
+            ],
+            ..fixtures::scan_result()
+        };
+        assert!(matches!(
+            GenshinArtifact::from_scan_result(&result),
+            Err(ArtifactParseError::BadMainStat(_))
+        ));
+
+        // the same line as a sub stat is still guessed
+        let mut result = result;
+        result.main_stat_name = String::from("暴击率");
+        let artifact = GenshinArtifact::from_scan_result(&result).unwrap();
+        assert_eq!(artifact.main_stat.name, ArtifactStatName::Critical);
+        assert_eq!(artifact.sub_stat_1.unwrap().name, ArtifactStatName::Critical);
+    }
+
+    #[test]
+    fn test_value_only_sub_stat() {
+        let result = GenshinArtifactScanResult {
+            sub_stat: [
+                String::from("暴击率+3.9%"),
+                String::from("+7.8%"),
+                String::from("攻击力+19"),
+                String::from(""),
+            ],
+            ..fixtures::scan_result()
+        };
+
+        // the rest of the artifact is still parsed
+        let artifact = GenshinArtifact::try_from(&result).unwrap();
+        assert!(artifact.sub_stat_1.is_some());
+        assert!(artifact.sub_stat_2.is_none());
+        assert!(artifact.sub_stat_3.is_some());
+
+        let unnamed = GenshinArtifact::unnamed_sub_stats(&result);
+        assert_eq!(unnamed, vec![UnnamedSubStat { index: 1, value: 0.078, is_percentage: true, pending: false }]);
+
+        assert_eq!(UnnamedSubStat::from_value_only(3, "19").map(|s| s.value), Some(19.0));
+        assert!(UnnamedSubStat::from_value_only(3, "7.8%（待激活）").unwrap().pending);
+        assert_eq!(UnnamedSubStat::from_value_only(0, "暴击+"), None);
+        assert_eq!(UnnamedSubStat::from_value_only(0, ""), None);
+    }
+
     #[test]
     fn test_fuzzy_match_name() {
         // "明威之" -> "明威之镡"
@@ -841,6 +1382,44 @@ mod tests {
         assert_eq!(name2, Some(ArtifactSetName::TenacityOfTheMillelith));
     }
 
+    #[test]
+    fn test_set_confirmed_by_description() {
+        // too far from any piece name to be matched by the title alone
+        assert_eq!(ArtifactSetName::from_zh_cn_with_description("的留恋", None), None);
+        assert_eq!(
+            ArtifactSetName::from_zh_cn_with_description("的留恋", Some("角斗士的终幕礼：")),
+            Some(ArtifactSetName::GladiatorFinale)
+        );
+        // one mangled character in the description is tolerated
+        assert_eq!(
+            ArtifactSetName::from_zh_cn_with_description("的留恋", Some("角斗土的终幕礼:")),
+            Some(ArtifactSetName::GladiatorFinale)
+        );
+        // an exact title is trusted over the description
+        assert_eq!(
+            ArtifactSetName::from_zh_cn_with_description("角斗士的留恋", Some("翠绿之影：")),
+            Some(ArtifactSetName::GladiatorFinale)
+        );
+    }
+
+    #[test]
+    fn test_slot_from_zh_cn_full_sets() {
+        let gladiator = ["角斗士的留恋", "角斗士的归宿", "角斗士的希冀", "角斗士的酣醉", "角斗士的凯旋"];
+        let heart_of_depth = ["饰金胸花", "追忆之风", "坚铜罗盘", "沉波之盏", "酒渍船帽"];
+        for names in [gladiator, heart_of_depth] {
+            let slots = names.map(ArtifactSlot::from_zh_cn);
+            assert_eq!(slots.to_vec(), ArtifactSlot::iter().map(Some).collect::<Vec<_>>());
+        }
+        assert_eq!(ArtifactSlot::from_zh_cn("abcdefgh"), None);
+    }
+
+    #[test]
+    fn test_slot_from_main_stat_fallback() {
+        assert_eq!(ArtifactSlot::from_main_stat(&ArtifactStatName::Hp), Some(ArtifactSlot::Flower));
+        assert_eq!(ArtifactSlot::from_main_stat(&ArtifactStatName::Atk), Some(ArtifactSlot::Feather));
+        assert_eq!(ArtifactSlot::from_main_stat(&ArtifactStatName::AtkPercentage), None);
+    }
+
     #[test]
     fn test_fuzzy_match_slot_debug() {
         // "明威之" -> "明威之镡" (Flower? No, 明威之镡 is Emblem Flower)
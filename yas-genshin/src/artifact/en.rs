@@ -0,0 +1,31 @@
+use crate::artifact::ArtifactStatName;
+
+impl ArtifactStatName {
+    #[rustfmt::skip]
+    pub fn from_en(name: &str, is_percentage: bool) -> Option<ArtifactStatName> {
+        match name.to_lowercase().as_str() {
+            "healing bonus" => Some(ArtifactStatName::HealingBonus),
+            "crit dmg" => Some(ArtifactStatName::CriticalDamage),
+            "crit rate" => Some(ArtifactStatName::Critical),
+            "atk" => if is_percentage { Some(ArtifactStatName::AtkPercentage) } else { Some(ArtifactStatName::Atk) },
+            "elemental mastery" => Some(ArtifactStatName::ElementalMastery),
+            "energy recharge" => Some(ArtifactStatName::Recharge),
+            "hp" => if is_percentage { Some(ArtifactStatName::HpPercentage) } else { Some(ArtifactStatName::Hp) },
+            "def" => if is_percentage { Some(ArtifactStatName::DefPercentage) } else { Some(ArtifactStatName::Def) },
+            "electro dmg bonus" => Some(ArtifactStatName::ElectroBonus),
+            "pyro dmg bonus" => Some(ArtifactStatName::PyroBonus),
+            "hydro dmg bonus" => Some(ArtifactStatName::HydroBonus),
+            "cryo dmg bonus" => Some(ArtifactStatName::CryoBonus),
+            "anemo dmg bonus" => Some(ArtifactStatName::AnemoBonus),
+            "geo dmg bonus" => Some(ArtifactStatName::GeoBonus),
+            "dendro dmg bonus" => Some(ArtifactStatName::DendroBonus),
+            "physical dmg bonus" => Some(ArtifactStatName::PhysicalBonus),
+            _ => None,
+        }
+    }
+
+    /// OCR may mangle the suffix of `CRIT Rate` / `CRIT DMG`, e.g. `CRIT`, `CRIT D`
+    pub(crate) fn is_ambiguous_critical_en(name: &str, is_percentage: bool) -> bool {
+        is_percentage && name.to_lowercase().starts_with("crit")
+    }
+}
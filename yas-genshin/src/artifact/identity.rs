@@ -0,0 +1,121 @@
+use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStatName, GenshinArtifact};
+
+/// What identifies an artifact across scans: everything except its lock and equip state.
+///
+/// Stat values are compared with an absolute `epsilon` (percentages are stored as fractions, so 7.8% is 0.078),
+/// so that the same artifact read with slightly different rounding compares equal.
+/// Too large an epsilon merges artifacts that are really different,
+/// it should stay well below the smallest roll difference of a substat (0.001 for percentages).
+/// Because of the tolerance the comparison is not transitive, so identities are not hashable
+#[derive(Debug, Clone)]
+pub struct ArtifactIdentity {
+    pub set_name: ArtifactSetName,
+    pub slot: ArtifactSlot,
+    pub star: i32,
+    pub level: i32,
+    pub main_stat_name: ArtifactStatName,
+    pub sub_stats: Vec<(ArtifactStatName, f64)>,
+    pub epsilon: f64,
+}
+
+impl ArtifactIdentity {
+    pub const DEFAULT_EPSILON: f64 = 0.0005;
+
+    pub fn new(artifact: &GenshinArtifact) -> Self {
+        Self::with_epsilon(artifact, Self::DEFAULT_EPSILON)
+    }
+
+    pub fn with_epsilon(artifact: &GenshinArtifact, epsilon: f64) -> Self {
+        let sub_stats = [
+            &artifact.sub_stat_1,
+            &artifact.sub_stat_2,
+            &artifact.sub_stat_3,
+            &artifact.sub_stat_4,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|stat| (stat.name.clone(), stat.value))
+        .collect();
+
+        ArtifactIdentity {
+            set_name: artifact.set_name.clone(),
+            slot: artifact.slot.clone(),
+            star: artifact.star,
+            level: artifact.level,
+            main_stat_name: artifact.main_stat.name.clone(),
+            sub_stats,
+            epsilon,
+        }
+    }
+}
+
+impl ArtifactIdentity {
+    /// A stable textual form of the identity, with values rounded to the precision shown in game.
+    /// The epsilon takes no part in it
+    pub fn canonical_key(&self) -> String {
+        let mut key = format!(
+            "{}|{}|{}|{}|{}",
+            self.set_name, self.slot, self.star, self.level, self.main_stat_name
+        );
+        for (name, value) in self.sub_stats.iter() {
+            key += &format!("|{}={}", name, (value * 1000.0).round() as i64);
+        }
+        key
+    }
+}
+
+impl PartialEq for ArtifactIdentity {
+    fn eq(&self, other: &Self) -> bool {
+        let epsilon = self.epsilon.max(other.epsilon);
+
+        self.set_name == other.set_name
+            && self.slot == other.slot
+            && self.star == other.star
+            && self.level == other.level
+            && self.main_stat_name == other.main_stat_name
+            && self.sub_stats.len() == other.sub_stats.len()
+            && self.sub_stats.iter().zip(other.sub_stats.iter()).all(|(a, b)| {
+                a.0 == b.0 && (a.1 - b.1).abs() <= epsilon
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, stat};
+
+    fn artifact(crit_damage: f64) -> GenshinArtifact {
+        GenshinArtifact {
+            sub_stat_1: Some(stat(ArtifactStatName::Critical, 0.035)),
+            sub_stat_2: Some(stat(ArtifactStatName::CriticalDamage, crit_damage)),
+            sub_stat_3: Some(stat(ArtifactStatName::AtkPercentage, 0.099)),
+            sub_stat_4: Some(stat(ArtifactStatName::ElementalMastery, 23.0)),
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_identity_ignores_lock_and_equip() {
+        let mut other = artifact(0.078);
+        other.lock = true;
+        other.equip = Some(String::from("胡桃"));
+
+        assert_eq!(ArtifactIdentity::new(&artifact(0.078)), ArtifactIdentity::new(&other));
+    }
+
+    #[test]
+    fn test_identity_epsilon_boundary() {
+        let epsilon = 0.001;
+        let base = ArtifactIdentity::with_epsilon(&artifact(0.078), epsilon);
+
+        let inside = ArtifactIdentity::with_epsilon(&artifact(0.078 + 0.0009), epsilon);
+        assert_eq!(base, inside);
+
+        let outside = ArtifactIdentity::with_epsilon(&artifact(0.078 + 0.0011), epsilon);
+        assert_ne!(base, outside);
+
+        let exact = ArtifactIdentity::with_epsilon(&artifact(0.0781), 0.0);
+        assert_ne!(ArtifactIdentity::with_epsilon(&artifact(0.078), 0.0), exact);
+    }
+}
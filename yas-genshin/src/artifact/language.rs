@@ -0,0 +1,36 @@
+use clap::ValueEnum;
+
+/// Language of the game client, decides how recognized text is parsed.
+/// Set and piece names are only known in Simplified Chinese for now,
+/// so `GenshinArtifactScannerConfig::validate` rejects the others until scan results can be converted in them
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    #[value(name = "zh-cn")]
+    ZhCn,
+    #[value(name = "en")]
+    En,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self::ZhCn
+    }
+}
+
+impl Language {
+    /// The word in front of the item count in the artifact inventory, e.g. `圣遗物 1500/2400`
+    pub fn item_count_keyword(&self) -> &'static str {
+        match *self {
+            Language::ZhCn => "圣遗物",
+            Language::En => "Artifacts",
+        }
+    }
+
+    /// What the game writes after a gray fourth substat that is not active yet, e.g. `暴击率+3.9% (待激活)`
+    pub fn pending_suffix(&self) -> &'static str {
+        match *self {
+            Language::ZhCn => " (待激活)",
+            Language::En => " (unactivated)",
+        }
+    }
+}
@@ -3,6 +3,18 @@ pub use artifact::ArtifactSlot;
 pub use artifact::ArtifactStat;
 pub use artifact::ArtifactStatName;
 pub use artifact::GenshinArtifact;
+pub use artifact::SubStatCheck;
+pub use artifact::SUB_STAT_CAPS;
+pub use artifact::UnnamedSubStat;
+pub use identity::ArtifactIdentity;
+pub use language::Language;
+pub use parse_error::ArtifactParseError;
+pub use validation::ArtifactWarning;
 
 mod artifact;
+mod en;
+mod identity;
+mod language;
+mod parse_error;
+mod validation;
 mod zh_cn;
@@ -0,0 +1,29 @@
+use std::fmt::{Display, Formatter};
+
+/// Why a scan result could not be converted to an artifact, with the raw text that failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactParseError {
+    /// the title is not a known artifact
+    UnknownSetName(String),
+    /// the set is known but the piece is not
+    UnknownSlot(String),
+    /// main stat name and value, joined with `+`
+    BadMainStat(String),
+    /// index (0-3) and text of a sub stat line
+    BadSubStat(usize, String),
+    BadLevel(i32),
+}
+
+impl Display for ArtifactParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactParseError::UnknownSetName(s) => write!(f, "unknown set name `{}`", s),
+            ArtifactParseError::UnknownSlot(s) => write!(f, "unknown slot `{}`", s),
+            ArtifactParseError::BadMainStat(s) => write!(f, "bad main stat `{}`", s),
+            ArtifactParseError::BadSubStat(index, s) => write!(f, "bad sub stat {} `{}`", index + 1, s),
+            ArtifactParseError::BadLevel(level) => write!(f, "bad level {}", level),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactParseError {}
@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter};
+
+use crate::artifact::{ArtifactStatName, GenshinArtifact};
+
+/// Something about an artifact that cannot happen in game, most likely an OCR error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactWarning {
+    /// index (0-3) of a sub stat with the same name as the main stat
+    SubStatSameAsMain(usize, ArtifactStatName),
+    /// a sub stat name that appears more than once
+    DuplicateSubStat(ArtifactStatName),
+    LevelTooHigh { star: i32, level: i32, max_level: i32 },
+}
+
+impl Display for ArtifactWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactWarning::SubStatSameAsMain(index, name) => write!(f, "sub stat {} {} is the main stat", index + 1, name),
+            ArtifactWarning::DuplicateSubStat(name) => write!(f, "sub stat {} appears more than once", name),
+            ArtifactWarning::LevelTooHigh { star, level, max_level } => {
+                write!(f, "level {} over the max level {} of a {} star artifact", level, max_level, star)
+            },
+        }
+    }
+}
+
+/// The highest level of an artifact with `star` stars
+fn max_level(star: i32) -> i32 {
+    match star {
+        5 => 20,
+        4 => 16,
+        3 => 12,
+        _ => 4,
+    }
+}
+
+impl GenshinArtifact {
+    /// Checks that the main stat is not also a sub stat, sub stats are unique and the level fits the star.
+    /// Empty if nothing is wrong
+    pub fn validate(&self) -> Vec<ArtifactWarning> {
+        let mut warnings = Vec::new();
+
+        let sub_stats = [&self.sub_stat_1, &self.sub_stat_2, &self.sub_stat_3, &self.sub_stat_4];
+        let mut seen: Vec<&ArtifactStatName> = Vec::new();
+        for (index, stat) in sub_stats.iter().enumerate() {
+            let stat = match stat {
+                Some(v) => v,
+                None => continue,
+            };
+            if stat.name == self.main_stat.name {
+                warnings.push(ArtifactWarning::SubStatSameAsMain(index, stat.name.clone()));
+            }
+            if seen.contains(&&stat.name) {
+                warnings.push(ArtifactWarning::DuplicateSubStat(stat.name.clone()));
+            }
+            seen.push(&stat.name);
+        }
+
+        let max_level = max_level(self.star);
+        if self.level > max_level {
+            warnings.push(ArtifactWarning::LevelTooHigh { star: self.star, level: self.level, max_level });
+        }
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::{ArtifactSlot, ArtifactStat};
+    use crate::fixtures;
+
+    fn stat(name: ArtifactStatName, value: f64) -> Option<ArtifactStat> {
+        Some(fixtures::stat(name, value))
+    }
+
+    fn artifact() -> GenshinArtifact {
+        GenshinArtifact {
+            slot: ArtifactSlot::Sand,
+            main_stat: fixtures::stat(ArtifactStatName::AtkPercentage, 0.466),
+            sub_stat_1: stat(ArtifactStatName::Critical, 0.035),
+            sub_stat_2: stat(ArtifactStatName::CriticalDamage, 0.078),
+            sub_stat_3: stat(ArtifactStatName::Atk, 19.0),
+            sub_stat_4: stat(ArtifactStatName::ElementalMastery, 23.0),
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_valid_artifact() {
+        assert!(artifact().validate().is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_artifacts() {
+        let sub_stat_as_main = GenshinArtifact {
+            sub_stat_3: stat(ArtifactStatName::AtkPercentage, 0.058),
+            ..artifact()
+        };
+        assert_eq!(
+            sub_stat_as_main.validate(),
+            vec![ArtifactWarning::SubStatSameAsMain(2, ArtifactStatName::AtkPercentage)]
+        );
+
+        let duplicate = GenshinArtifact {
+            sub_stat_4: stat(ArtifactStatName::Critical, 0.031),
+            ..artifact()
+        };
+        assert_eq!(duplicate.validate(), vec![ArtifactWarning::DuplicateSubStat(ArtifactStatName::Critical)]);
+
+        let over_level = GenshinArtifact { star: 4, level: 20, ..artifact() };
+        assert_eq!(
+            over_level.validate(),
+            vec![ArtifactWarning::LevelTooHigh { star: 4, level: 20, max_level: 16 }]
+        );
+        let over_level = GenshinArtifact { level: 24, ..artifact() };
+        assert_eq!(over_level.validate().len(), 1);
+    }
+}
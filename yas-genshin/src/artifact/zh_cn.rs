@@ -1,4 +1,4 @@
-use crate::artifact::ArtifactSlot;
+use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStatName};
 
 impl ArtifactSlot {
     pub fn to_zh_cn(&self) -> &'static str {
@@ -11,3 +11,94 @@ impl ArtifactSlot {
         }
     }
 }
+
+impl ArtifactStatName {
+    /// The stat name shown in the artifact panel, parsed back by `from_zh_cn`
+    pub fn to_zh_cn(&self) -> &'static str {
+        match *self {
+            ArtifactStatName::HealingBonus => "治疗加成",
+            ArtifactStatName::CriticalDamage => "暴击伤害",
+            ArtifactStatName::Critical => "暴击率",
+            ArtifactStatName::Atk | ArtifactStatName::AtkPercentage => "攻击力",
+            ArtifactStatName::ElementalMastery => "元素精通",
+            ArtifactStatName::Recharge => "元素充能效率",
+            ArtifactStatName::Hp | ArtifactStatName::HpPercentage => "生命值",
+            ArtifactStatName::Def | ArtifactStatName::DefPercentage => "防御力",
+            ArtifactStatName::ElectroBonus => "雷元素伤害加成",
+            ArtifactStatName::PyroBonus => "火元素伤害加成",
+            ArtifactStatName::HydroBonus => "水元素伤害加成",
+            ArtifactStatName::CryoBonus => "冰元素伤害加成",
+            ArtifactStatName::AnemoBonus => "风元素伤害加成",
+            ArtifactStatName::GeoBonus => "岩元素伤害加成",
+            ArtifactStatName::DendroBonus => "草元素伤害加成",
+            ArtifactStatName::PhysicalBonus => "物理伤害加成",
+        }
+    }
+}
+
+impl ArtifactSetName {
+    /// The set name shown above the set bonus description in the artifact panel
+    pub fn to_zh_cn(&self) -> &'static str {
+        match *self {
+            ArtifactSetName::ArchaicPetra => "悠古的磐岩",
+            ArtifactSetName::HeartOfDepth => "沉沦之心",
+            ArtifactSetName::BlizzardStrayer => "冰风迷途的勇士",
+            ArtifactSetName::RetracingBolide => "逆飞的流星",
+            ArtifactSetName::NoblesseOblige => "昔日宗室之仪",
+            ArtifactSetName::GladiatorFinale => "角斗士的终幕礼",
+            ArtifactSetName::MaidenBeloved => "被怜爱的少女",
+            ArtifactSetName::ViridescentVenerer => "翠绿之影",
+            ArtifactSetName::LavaWalker => "渡过烈火的贤人",
+            ArtifactSetName::CrimsonWitch => "炽烈的炎之魔女",
+            ArtifactSetName::ThunderSmoother => "平息鸣雷的尊者",
+            ArtifactSetName::ThunderingFury => "如雷的盛怒",
+            ArtifactSetName::BloodstainedChivalry => "染血的骑士道",
+            ArtifactSetName::WandererTroupe => "流浪大地的乐团",
+            ArtifactSetName::Scholar => "学士",
+            ArtifactSetName::Gambler => "赌徒",
+            ArtifactSetName::TinyMiracle => "奇迹",
+            ArtifactSetName::MartialArtist => "武人",
+            ArtifactSetName::BraveHeart => "勇士之心",
+            ArtifactSetName::ResolutionOfSojourner => "行者之心",
+            ArtifactSetName::DefenderWill => "守护之心",
+            ArtifactSetName::Berserker => "战狂",
+            ArtifactSetName::Instructor => "教官",
+            ArtifactSetName::Exile => "流放者",
+            ArtifactSetName::Adventurer => "冒险家",
+            ArtifactSetName::LuckyDog => "幸运儿",
+            ArtifactSetName::TravelingDoctor => "游医",
+            ArtifactSetName::PrayersForWisdom => "祭雷之人",
+            ArtifactSetName::PrayersToSpringtime => "祭冰之人",
+            ArtifactSetName::PrayersForIllumination => "祭火之人",
+            ArtifactSetName::PrayersForDestiny => "祭水之人",
+            ArtifactSetName::PaleFlame => "苍白之火",
+            ArtifactSetName::TenacityOfTheMillelith => "千岩牢固",
+            ArtifactSetName::EmblemOfSeveredFate => "绝缘之旗印",
+            ArtifactSetName::ShimenawaReminiscence => "追忆之注连",
+            ArtifactSetName::HuskOfOpulentDreams => "华馆梦醒形骸记",
+            ArtifactSetName::OceanHuedClam => "海染砗磲",
+            ArtifactSetName::VermillionHereafter => "辰砂往生录",
+            ArtifactSetName::EchoesOfAnOffering => "来歆余响",
+            ArtifactSetName::DeepwoodMemories => "深林的记忆",
+            ArtifactSetName::GildedDreams => "饰金之梦",
+            ArtifactSetName::FlowerOfParadiseLost => "乐园遗落之花",
+            ArtifactSetName::DesertPavilionChronicle => "沙上楼阁史话",
+            ArtifactSetName::NymphsDream => "水仙之梦",
+            ArtifactSetName::VourukashasGlow => "花海甘露之光",
+            ArtifactSetName::MarechausseeHunter => "逐影猎人",
+            ArtifactSetName::GoldenTroupe => "黄金剧团",
+            ArtifactSetName::SongOfDaysPast => "昔时之歌",
+            ArtifactSetName::NighttimeWhispersInTheEchoingWoods => "回声之林夜话",
+            ArtifactSetName::FragmentOfHarmonicWhimsy => "谐律异想断章",
+            ArtifactSetName::UnfinishedReverie => "未竟的遐思",
+            ArtifactSetName::ScrollOfTheHeroOfCinderCity => "烬城勇者绘卷",
+            ArtifactSetName::ObsidianCodex => "黑曜秘典",
+            ArtifactSetName::LongNightsOath => "长夜之誓",
+            ArtifactSetName::FinaleOfTheDeepGalleries => "深廊终曲",
+            ArtifactSetName::NightOfTheSkysUnveiling => "穹境示现之夜",
+            ArtifactSetName::SilkenMoonsSerenade => "纺月的夜歌",
+            ArtifactSetName::ADayCarvedFromRisingWinds => "风起之日",
+            ArtifactSetName::AubadeOfMorningstarAndMoon => "晨星与月的晓歌",
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! 从一张已知星级的圣遗物全窗口截图中取星级背景色，用于自定义星级颜色（HDR、滤镜等导致星级识别错误时）。
+//!
+//! 将输出的颜色填入对应分辨率 window_info JSON 的 `genshin_artifact_star_color_{星级}`，
+//! 例如 `"genshin_artifact_star_color_5": { "Color": [188, 105, 50] }`。未填写时使用内置默认值。
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use yas::game_info::{Platform, UI};
+use yas::positioning::Size;
+use yas::window_info::{load_window_info_repo, FromWindowInfoRepository};
+use yas_scanner_genshin::scanner::ArtifactScannerWindowInfo;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Full window screenshot with an artifact panel opened
+    #[arg(short, long)]
+    image: String,
+
+    /// Star of the artifact in the screenshot
+    #[arg(short, long, default_value_t = 5)]
+    star: usize,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let image = yas::utils::load_rgb8(&args.image)?;
+    let repo = load_window_info_repo!(
+        "../../window_info/windows1600x900.json",
+        "../../window_info/windows1280x960.json",
+        "../../window_info/windows1440x900.json",
+        "../../window_info/windows2100x900.json",
+        "../../window_info/windows3440x1440.json",
+    );
+    let size = Size::new(image.width() as usize, image.height() as usize);
+    let window_info = ArtifactScannerWindowInfo::from_window_info_repository(size, UI::Desktop, Platform::Windows, &repo)
+        .with_context(|| format!("no window info for {}x{}", size.width, size.height))?;
+
+    let color = window_info
+        .sample_star_color(&image)
+        .context("star_pos is outside the screenshot")?;
+    let matched = window_info.match_star(&color);
+
+    println!("sampled color: {:?}", color.0);
+    println!("matched star with current colors: {}", matched);
+    if matched != args.star {
+        println!("mismatch, add to window info:");
+    }
+    println!("\"genshin_artifact_star_color_{}\": {{ \"Color\": {:?} }}", args.star, color.0);
+
+    Ok(())
+}
@@ -1,5 +1,4 @@
 use anyhow::Result;
-use image::io::Reader as ImageReader;
 use yas::ocr::yas_ocr_model;
 use yas::ocr::ImageToText;
 
@@ -15,14 +14,13 @@ fn main() -> Result<()> {
     let path = r"QQ图片20260209170604.png";
     println!("Reading image from {}...", path);
     // Handle image reading error gracefully or let it simple fail
-    let image = match ImageReader::open(path) {
-        Ok(reader) => reader.decode()?,
+    let rgb_image = match yas::utils::load_rgb8(path) {
+        Ok(image) => image,
         Err(e) => {
-            println!("Failed to open image: {}", e);
+            println!("Failed to open image: {:#}", e);
             return Ok(());
         },
     };
-    let rgb_image = image.to_rgb8();
     println!("Running inference...");
     let result = model.image_to_text(&rgb_image, false)?;
     println!("Result: {}", result);
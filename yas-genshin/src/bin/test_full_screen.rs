@@ -5,6 +5,8 @@
 //! - **window_info JSON** 里所有坐标/尺寸都是针对某一基准分辨率（如 1600x900）标定的。
 //! - **Repository** 按 (width, height, ui, platform) 存多套配置；`get_auto_scale` 在宽高比一致时
 //!   用 `factor = target_width / base_width` 对 Rect/Pos/Size/Float 做线性缩放，**InvariantInt/InvariantFloat 不缩放**。
+//!   没有同比例配置时（如 2560x1080），取 16:9 配置缩放到窗口居中的 16:9 区域（`content_box`）并平移。
+//!   基准分辨率对所有键只选一次（`base_resolution`），某个键在该基准下没有标定时直接报错，不会换用别的分辨率。
 //! - **from_window_info_repository** 根据当前窗口尺寸取配置（或同比例缩放），得到的是**已换算到当前分辨率**的 rect/pos，单位是像素。
 //!
 //! ## 新标点（新 ROI）要不要做坐标变换？
@@ -15,13 +17,12 @@
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use image::io::Reader as ImageReader;
 use image::{GenericImageView, RgbImage};
 use yas::game_info::{Platform, UI};
 use yas::positioning::Size;
-use yas::window_info::{load_window_info_repo, FromWindowInfoRepository};
+use yas::window_info::{load_window_info_repo, FromWindowInfoRepository, WindowInfoRepository};
 use yas_scanner_genshin::scanner::{
-    get_page_locks_from_list_image, ArtifactScannerWindowInfo, ArtifactScannerWorker,
+    crop_panel_image, get_page_locks_from_list_image, ArtifactScannerWindowInfo, ArtifactScannerWorker,
     GenshinArtifactScannerConfig,
 };
 
@@ -30,6 +31,10 @@ use yas_scanner_genshin::scanner::{
 struct Args {
     #[arg(short, long)]
     image: String,
+
+    /// Read windowsWxH.json from this directory instead of the ones built in, e.g. while calibrating a new resolution
+    #[arg(long)]
+    window_info_dir: Option<std::path::PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -39,19 +44,21 @@ fn main() -> Result<()> {
 
     // 1. Load full-window image
     println!("Loading image: {}", args.image);
-    let image = ImageReader::open(&args.image)?.decode()?;
-    let rgb_image = image.to_rgb8();
+    let rgb_image = yas::utils::load_rgb8(&args.image)?;
     let (width, height) = rgb_image.dimensions();
     println!("Image dimensions: {}x{}", width, height);
 
     // 2. Window info repo (same as application) and scale to current image size
-    let repo = load_window_info_repo!(
-        "../../window_info/windows1600x900.json",
-        "../../window_info/windows1280x960.json",
-        "../../window_info/windows1440x900.json",
-        "../../window_info/windows2100x900.json",
-        "../../window_info/windows3440x1440.json",
-    );
+    let repo = match args.window_info_dir.as_ref() {
+        Some(dir) => WindowInfoRepository::load_from_dir(dir)?,
+        None => load_window_info_repo!(
+            "../../window_info/windows1600x900.json",
+            "../../window_info/windows1280x960.json",
+            "../../window_info/windows1440x900.json",
+            "../../window_info/windows2100x900.json",
+            "../../window_info/windows3440x1440.json",
+        ),
+    };
 
     let target_size = Size {
         width: width as usize,
@@ -69,21 +76,7 @@ fn main() -> Result<()> {
     println!("Panel rect: {:?}", window_info.panel_rect);
 
     // 3. Crop panel from full-window image (same coordinate system: (0,0) = top-left of window)
-    let pr = &window_info.panel_rect;
-    let px = pr.left as u32;
-    let py = pr.top as u32;
-    let pw = pr.width as u32;
-    let ph = pr.height as u32;
-
-    if px + pw > width || py + ph > height {
-        anyhow::bail!(
-            "Panel rect ({}, {}, {}, {}) out of image bounds {}x{}",
-            px, py, pw, ph,
-            width, height
-        );
-    }
-
-    let panel_image: RgbImage = rgb_image.view(px, py, pw, ph).to_image();
+    let panel_image = crop_panel_image(&rgb_image, &window_info)?;
     panel_image.save("panel_image.png")?;
     println!("Cropped panel size: {}x{}", panel_image.width(), panel_image.height());
 
@@ -95,30 +88,21 @@ fn main() -> Result<()> {
         let list_w = width - list_left;
         let list_h = height - list_top;
         let list_image: RgbImage = rgb_image.view(list_left, list_top, list_w, list_h).to_image();
-        let locks = get_page_locks_from_list_image(&list_image, &window_info, Some(std::path::Path::new("debug")));
-        let row = window_info.row;
-        let col = window_info.col;
+        let locks = get_page_locks_from_list_image(&list_image, &window_info, 0.75, Some(std::path::Path::new("debug")));
         println!("\n=== List-view lock state (0=unlocked, 1=locked), row x col ===");
-        for r in 0..row {
-            for c in 0..col {
-                let i = (r * col + c) as usize;
-                if i < locks.len() {
-                    print!("{}", if locks[i] { 1 } else { 0 });
-                }
+        for (i, cell) in locks.iter().enumerate() {
+            if i > 0 && cell.col == 0 {
+                println!();
             }
-            println!();
+            print!("{}", if cell.locked { 1 } else { 0 });
         }
+        println!();
     }
 
     // 4. Use the same worker as the live scanner: one call does all rects + OCR
-    let config = GenshinArtifactScannerConfig {
-        min_star: 4,
-        min_level: 0,
-        ignore_dup: false,
-        verbose: true,
-        number: -1,
-        lock_list_path: None,
-    };
+    let config = GenshinArtifactScannerConfig::builder()
+        .verbose(true)
+        .build()?;
     let worker = ArtifactScannerWorker::new(window_info, config)?;
     let result = worker.scan_panel_image(&panel_image, false)?;
 
@@ -1,6 +1,5 @@
 use anyhow::Result;
 use clap::Parser;
-use image::io::Reader as ImageReader;
 use image::RgbImage;
 use yas::ocr::{ImageToText, PPOCRChV4RecInfer};
 use yas_scanner_genshin::artifact::ArtifactStat;
@@ -24,8 +23,7 @@ fn main() -> Result<()> {
 
     // Load image
     println!("Loading image: {}", args.image);
-    let image = ImageReader::open(&args.image)?.decode()?;
-    let rgb_image = image.to_rgb8();
+    let rgb_image = yas::utils::load_rgb8(&args.image)?;
 
     // Run OCR
     println!("Running OCR...");
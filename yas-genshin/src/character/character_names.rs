@@ -88,7 +88,7 @@ lazy_static! {
         "赛索斯",
         "克洛琳德",
         "希格雯",
-        "艾梅丽埃",
+        "艾梅莉埃",
         "卡齐娜",
         "玛拉妮",
         "基尼奇",
@@ -2,3 +2,8 @@ pub use character_names::CHARACTER_NAMES;
 
 mod character_names;
 
+/// The character of an equip line, e.g. `胡桃已装备`. None when not equipped or the name is not a known character
+pub fn equip_from_zh_cn_raw(s: &str) -> Option<String> {
+    let name = s.strip_suffix("已装备")?;
+    CHARACTER_NAMES.contains(name).then(|| String::from(name))
+}
@@ -0,0 +1,65 @@
+use crate::artifact::GenshinArtifact;
+
+use super::good::equip_from_zh_cn;
+
+/// Lowercase with spaces, `_` and `-` removed, so that `Hu Tao`, `hu_tao` and `HuTao` compare equal
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Whether `equip` (a Chinese character name as recognized) is the character `name`,
+/// given either in Chinese or as the English GOOD key, e.g. `胡桃`, `HuTao`, `hu tao`
+pub fn is_character(equip: &str, name: &str) -> bool {
+    let name = normalize_name(name);
+    if normalize_name(equip) == name {
+        return true;
+    }
+
+    let key = equip_from_zh_cn(Some(equip));
+    !key.is_empty() && normalize_name(key) == name
+}
+
+/// The artifacts equipped on the character `name`, see `is_character`
+pub fn filter_by_character(artifacts: &[GenshinArtifact], name: &str) -> Vec<GenshinArtifact> {
+    artifacts.iter()
+        .filter(|artifact| artifact.equip.as_deref().is_some_and(|equip| is_character(equip, name)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::artifact::{ArtifactSetName, ArtifactSlot};
+    use crate::fixtures;
+
+    use super::*;
+
+    fn artifact(slot: ArtifactSlot, equip: Option<&str>) -> GenshinArtifact {
+        GenshinArtifact {
+            set_name: ArtifactSetName::CrimsonWitch,
+            slot,
+            equip: equip.map(String::from),
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_filter_by_character() {
+        let artifacts = vec![
+            artifact(ArtifactSlot::Flower, Some("胡桃")),
+            artifact(ArtifactSlot::Feather, Some("行秋")),
+            artifact(ArtifactSlot::Sand, None),
+            artifact(ArtifactSlot::Goblet, Some("胡桃")),
+        ];
+
+        for name in ["胡桃", "HuTao", "hu tao", "Hu_Tao"] {
+            let filtered = filter_by_character(&artifacts, name);
+            let slots: Vec<ArtifactSlot> = filtered.into_iter().map(|a| a.slot).collect();
+            assert_eq!(slots, vec![ArtifactSlot::Flower, ArtifactSlot::Goblet], "{}", name);
+        }
+        assert!(filter_by_character(&artifacts, "Diluc").is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::export::artifact::GenshinArtifactExportFormat;
+use crate::export::artifact::{ExportLockFilter, GenshinArtifactCSVLayout, GenshinArtifactExportFormat};
 
 #[derive(clap::Args)]
 pub struct ExportArtifactConfig {
@@ -8,4 +8,34 @@ pub struct ExportArtifactConfig {
 
     #[arg(id = "output-dir", long = "output-dir", short, default_value_t = String::from("."), help = "输出目录")]
     pub output_dir: String,
+
+    /// Write into this directory, created if needed, with the time of the run in every file name
+    /// (e.g. `good_20240102_030405.json`), so earlier exports are kept. Takes the place of `--output-dir`
+    #[arg(id = "export-dir", long = "export-dir", help = "导出到该目录（不存在时自动创建），文件名带有时间戳以免覆盖之前的结果，指定后忽略 --output-dir", value_name = "DIR")]
+    pub export_dir: Option<std::path::PathBuf>,
+
+    /// A previous Mona (extended) or GOOD export, the artifacts added and removed since then are written to `diff.json`
+    #[arg(id = "diff-against", long = "diff-against", help = "与之前的导出文件（莫娜或GOOD格式）比较，新增和移除的圣遗物保存至 diff.json", value_name = "PREVIOUS_JSON")]
+    pub diff_against: Option<std::path::PathBuf>,
+
+    /// Add the recognized text of each artifact as `_raw` to the mona_extended export, for looking into misreads
+    #[arg(id = "include-raw", long = "include-raw", help = "莫娜扩展格式中为每件圣遗物附带识别出的原始文本（_raw 字段），用于排查识别错误")]
+    pub include_raw: bool,
+
+    /// Also pack all exported files into a single zip
+    #[arg(id = "bundle", long = "bundle", help = "将所有导出文件打包为一个 zip 文件", value_name = "BUNDLE_ZIP")]
+    pub bundle: Option<std::path::PathBuf>,
+
+    /// Wide: one row per artifact, long: one row per sub stat
+    #[arg(id = "csv-layout", long = "csv-layout", default_value = "wide", help = "CSV布局，wide为每个圣遗物一行，long为每个副词条一行")]
+    #[arg(value_enum)]
+    pub csv_layout: GenshinArtifactCSVLayout,
+
+    /// Only export the artifacts equipped on this character, Chinese name or GOOD key (e.g. 胡桃, HuTao)
+    #[arg(id = "export-character", long = "export-character", help = "只导出该角色装备的圣遗物（中文名或英文名，如 胡桃、HuTao）", value_name = "NAME")]
+    pub export_character: Option<String>,
+
+    /// Only export the locked (or unlocked) artifacts, e.g. the ones kept after an auto-lock run
+    #[arg(id = "export-lock", long = "export-lock", value_enum, default_value_t = ExportLockFilter::All, help = "按锁定状态导出：all 全部，locked 只导出已锁定的，unlocked 只导出未锁定的")]
+    pub export_lock: ExportLockFilter,
 }
@@ -1,70 +1,130 @@
+use clap::ValueEnum;
 use serde::{Serialize, Serializer};
-use crate::artifact::GenshinArtifact;
+
+use crate::artifact::{ArtifactStat, GenshinArtifact};
+
+/// How sub stats are laid out in the CSV
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum GenshinArtifactCSVLayout {
+    /// One row per artifact, sub stats in separate columns
+    Wide,
+    /// One row per sub stat
+    Long,
+}
+
+impl Default for GenshinArtifactCSVLayout {
+    fn default() -> Self {
+        Self::Wide
+    }
+}
+
+/// Excel needs the BOM to open UTF-8 (Chinese) text correctly
+const UTF8_BOM: &str = "\u{feff}";
 
 pub struct GenshinArtifactCSVFormat<'a> {
     artifacts: &'a [GenshinArtifact],
+    layout: GenshinArtifactCSVLayout,
 }
 
-/// CSV format:
-/// set name, slot, star, level, main stat name, main stat value, [sub state name, sub state value]*4, equip
-fn single_artifact_to_string(artifact: &GenshinArtifact) -> String {
-    let mut s = String::new();
-    s = s + &artifact.set_name.to_string();
-    s = s + "," + &artifact.slot.to_string();
-    s = s + "," + &format!("{}", artifact.star);
-    s = s + "," + &format!("{}", artifact.level);
-    s = s + "," + &artifact.main_stat.name.to_string();
-    s = s + "," + &format!("{}", artifact.main_stat.value);
-    if let Some(sub) = &artifact.sub_stat_1 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
-    } else {
-        s += ",,";
-    }
-    if let Some(sub) = &artifact.sub_stat_2 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
-    } else {
-        s += ",,";
-    }
-    if let Some(sub) = &artifact.sub_stat_3 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
-    } else {
-        s += ",,";
-    }
-    if let Some(sub) = &artifact.sub_stat_4 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
-    } else {
-        s += ",,";
-    }
-    if let Some(e) = &artifact.equip {
-        s = s + "," + e;
-    } else {
-        s += ","
-    }
+/// set name, slot, star, level, main stat name, main stat value
+fn artifact_columns(artifact: &GenshinArtifact) -> Vec<String> {
+    vec![
+        artifact.set_name.to_string(),
+        artifact.slot.to_string(),
+        format!("{}", artifact.star),
+        format!("{}", artifact.level),
+        artifact.main_stat.name.to_string(),
+        format!("{}", artifact.main_stat.value),
+    ]
+}
 
-    s
+fn sub_stats(artifact: &GenshinArtifact) -> [Option<&ArtifactStat>; 4] {
+    [
+        artifact.sub_stat_1.as_ref(),
+        artifact.sub_stat_2.as_ref(),
+        artifact.sub_stat_3.as_ref(),
+        artifact.sub_stat_4.as_ref(),
+    ]
+}
+
+fn sub_stat_columns(stat: Option<&ArtifactStat>) -> [String; 2] {
+    match stat {
+        Some(sub) => [sub.name.to_string(), format!("{}", sub.value)],
+        None => [String::new(), String::new()],
+    }
 }
 
 impl<'a> GenshinArtifactCSVFormat<'a> {
-    pub fn new(artifacts: &'a [GenshinArtifact]) -> Self {
+    pub fn new(artifacts: &'a [GenshinArtifact], layout: GenshinArtifactCSVLayout) -> Self {
         Self {
-            artifacts
+            artifacts,
+            layout,
         }
     }
 
-    pub fn to_csv_string(&self) -> String {
-        let header = "套装,部位,星级,等级,主词条名,主词条值,副词条名1,副词条值1,副词条名2,副词条值2,副词条名3,副词条值3,副词条名4,副词条值4,装备";
-        let mut result = String::from(header) + "\n";
+    fn rows(&self) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
 
-        for artifact in self.artifacts.iter() {
-            let line = single_artifact_to_string(artifact);
-            result = result + &line + "\n";
+        match self.layout {
+            GenshinArtifactCSVLayout::Wide => {
+                rows.push(
+                    ["套装", "部位", "星级", "等级", "主词条名", "主词条值",
+                        "副词条名1", "副词条值1", "副词条名2", "副词条值2", "副词条名3", "副词条值3", "副词条名4", "副词条值4", "装备"]
+                        .iter().map(|s| s.to_string()).collect()
+                );
+                for artifact in self.artifacts.iter() {
+                    let mut row = artifact_columns(artifact);
+                    for stat in sub_stats(artifact) {
+                        row.extend(sub_stat_columns(stat));
+                    }
+                    row.push(artifact.equip.clone().unwrap_or_default());
+                    rows.push(row);
+                }
+            },
+            GenshinArtifactCSVLayout::Long => {
+                rows.push(
+                    ["套装", "部位", "星级", "等级", "主词条名", "主词条值", "装备", "副词条序号", "副词条名", "副词条值"]
+                        .iter().map(|s| s.to_string()).collect()
+                );
+                for artifact in self.artifacts.iter() {
+                    let mut prefix = artifact_columns(artifact);
+                    prefix.push(artifact.equip.clone().unwrap_or_default());
+
+                    let present: Vec<(usize, &ArtifactStat)> = sub_stats(artifact)
+                        .into_iter()
+                        .enumerate()
+                        .filter_map(|(i, stat)| stat.map(|s| (i, s)))
+                        .collect();
+                    // keep artifacts without sub stats in the output
+                    if present.is_empty() {
+                        let mut row = prefix.clone();
+                        row.extend([String::new(), String::new(), String::new()]);
+                        rows.push(row);
+                    }
+                    for (index, stat) in present {
+                        let mut row = prefix.clone();
+                        row.push(format!("{}", index + 1));
+                        row.extend(sub_stat_columns(Some(stat)));
+                        rows.push(row);
+                    }
+                }
+            },
         }
 
-        result
+        rows
+    }
+
+    /// The CSV with a UTF-8 BOM, fields containing commas or quotes are quoted
+    pub fn to_csv_string(&self) -> String {
+        let mut writer = ::csv::WriterBuilder::new()
+            .flexible(false)
+            .from_writer(Vec::from(UTF8_BOM.as_bytes()));
+        for row in self.rows() {
+            // writing to a Vec does not fail
+            writer.write_record(&row).unwrap();
+        }
+
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
     }
 }
 
@@ -74,3 +134,53 @@ impl<'a> Serialize for GenshinArtifactCSVFormat<'a> {
         serializer.serialize_str(&s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::artifact::ArtifactStatName;
+    use crate::fixtures::{self, stat};
+
+    use super::*;
+
+    fn artifact(equip: Option<&str>, sub_stat_2: Option<ArtifactStat>) -> GenshinArtifact {
+        GenshinArtifact {
+            sub_stat_1: Some(stat(ArtifactStatName::Critical, 0.039)),
+            sub_stat_2,
+            equip: equip.map(String::from),
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_wide_layout() {
+        let artifacts = vec![artifact(Some("胡桃"), None)];
+        let csv = GenshinArtifactCSVFormat::new(&artifacts, GenshinArtifactCSVLayout::Wide).to_csv_string();
+
+        assert!(csv.starts_with(UTF8_BOM));
+        let lines: Vec<&str> = csv[UTF8_BOM.len()..].lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("套装,部位,星级"));
+        assert_eq!(lines[1].split(',').count(), 15);
+        assert!(lines[1].ends_with(",胡桃"));
+    }
+
+    #[test]
+    fn test_long_layout() {
+        let crit_damage = ArtifactStat { name: ArtifactStatName::CriticalDamage, value: 0.078, pending: false };
+        let artifacts = vec![artifact(None, Some(crit_damage))];
+        let csv = GenshinArtifactCSVFormat::new(&artifacts, GenshinArtifactCSVLayout::Long).to_csv_string();
+
+        let lines: Vec<&str> = csv[UTF8_BOM.len()..].lines().collect();
+        // header and one row for each of the two sub stats
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains(",1,"));
+        assert!(lines[2].contains(",2,"));
+    }
+
+    #[test]
+    fn test_comma_quoted() {
+        let artifacts = vec![artifact(Some("a,b"), None)];
+        let csv = GenshinArtifactCSVFormat::new(&artifacts, GenshinArtifactCSVLayout::Wide).to_csv_string();
+        assert!(csv.contains("\"a,b\""));
+    }
+}
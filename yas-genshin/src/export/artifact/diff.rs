@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+
+use crate::artifact::{ArtifactIdentity, GenshinArtifact};
+
+/// Differences between two exports. Artifacts are matched by `ArtifactIdentity`,
+/// so an artifact that was leveled up counts as one removed and one added
+#[derive(Debug, Default, Clone)]
+pub struct ArtifactDiff {
+    /// only in the second export
+    pub added: Vec<GenshinArtifact>,
+    /// only in the first export
+    pub removed: Vec<GenshinArtifact>,
+    /// in both, but locked / unlocked or moved to another character, (before, after)
+    pub changed: Vec<(GenshinArtifact, GenshinArtifact)>,
+}
+
+impl ArtifactDiff {
+    pub fn new(before: &[GenshinArtifact], after: &[GenshinArtifact]) -> Self {
+        // the same artifact can appear more than once, e.g. two identical unleveled pieces
+        let mut remaining: HashMap<String, Vec<&GenshinArtifact>> = HashMap::new();
+        for artifact in before.iter() {
+            remaining.entry(ArtifactIdentity::new(artifact).canonical_key()).or_default().push(artifact);
+        }
+
+        let mut diff = ArtifactDiff::default();
+        for artifact in after.iter() {
+            let key = ArtifactIdentity::new(artifact).canonical_key();
+            let old = remaining.get_mut(&key).and_then(|list| {
+                // prefer an unchanged copy, so duplicates are not reported as changed
+                let index = list.iter().position(|old| !is_changed(old, artifact)).unwrap_or(0);
+                (!list.is_empty()).then(|| list.remove(index))
+            });
+            match old {
+                Some(old) if is_changed(old, artifact) => diff.changed.push((old.clone(), artifact.clone())),
+                Some(_) => {},
+                None => diff.added.push(artifact.clone()),
+            }
+        }
+
+        // keep the order of the first export
+        for artifact in before.iter() {
+            let key = ArtifactIdentity::new(artifact).canonical_key();
+            if let Some(list) = remaining.get_mut(&key) {
+                if let Some(index) = list.iter().position(|old| std::ptr::eq(*old, artifact)) {
+                    diff.removed.push(list.remove(index).clone());
+                }
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn is_changed(before: &GenshinArtifact, after: &GenshinArtifact) -> bool {
+    before.lock != after.lock || before.equip != after.equip
+}
+
+fn describe(artifact: &GenshinArtifact) -> String {
+    let sub_stats = [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4]
+        .into_iter()
+        .flatten()
+        .map(|stat| format!("{}={}", stat.name, stat.value))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{} {} {}★ +{} {} [{}]",
+        artifact.set_name, artifact.slot, artifact.star, artifact.level, artifact.main_stat.name, sub_stats
+    )
+}
+
+fn describe_state(artifact: &GenshinArtifact) -> String {
+    format!(
+        "{}, {}",
+        if artifact.lock { "锁定" } else { "未锁定" },
+        artifact.equip.as_deref().unwrap_or("未装备")
+    )
+}
+
+impl Display for ArtifactDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for artifact in self.added.iter() {
+            writeln!(f, "+ {}", describe(artifact))?;
+        }
+        for artifact in self.removed.iter() {
+            writeln!(f, "- {}", describe(artifact))?;
+        }
+        for (before, after) in self.changed.iter() {
+            writeln!(f, "~ {}: {} -> {}", describe(after), describe_state(before), describe_state(after))?;
+        }
+        writeln!(f, "新增: {}，移除: {}，变化: {}", self.added.len(), self.removed.len(), self.changed.len())
+    }
+}
+
+/// A changed artifact as `{"before": ..., "after": ..., "lock": [before, after]}`,
+/// the Mona format has no lock state of its own
+struct Change<'a>(&'a GenshinArtifact, &'a GenshinArtifact);
+
+impl Serialize for Change<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut root = serializer.serialize_map(Some(3))?;
+        root.serialize_entry("before", self.0)?;
+        root.serialize_entry("after", self.1)?;
+        root.serialize_entry("lock", &[self.0.lock, self.1.lock])?;
+        root.end()
+    }
+}
+
+/// Artifacts are written in the Mona format, so `added` can be imported on its own
+impl Serialize for ArtifactDiff {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let changed = self.changed.iter().map(|(before, after)| Change(before, after)).collect::<Vec<_>>();
+        let mut root = serializer.serialize_struct("ArtifactDiff", 3)?;
+        root.serialize_field("added", &self.added)?;
+        root.serialize_field("removed", &self.removed)?;
+        root.serialize_field("changed", &changed)?;
+        root.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::{ArtifactSlot, ArtifactStatName};
+    use crate::fixtures::{self, stat};
+
+    fn artifact(slot: ArtifactSlot, crit_damage: f64, lock: bool, equip: Option<&str>) -> GenshinArtifact {
+        GenshinArtifact {
+            slot,
+            lock,
+            sub_stat_1: Some(stat(ArtifactStatName::CriticalDamage, crit_damage)),
+            equip: equip.map(String::from),
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_diff_two_exports() {
+        let before = vec![
+            artifact(ArtifactSlot::Flower, 0.078, false, None),
+            artifact(ArtifactSlot::Flower, 0.078, false, None),
+            artifact(ArtifactSlot::Feather, 0.140, false, None),
+            artifact(ArtifactSlot::Sand, 0.210, true, Some("胡桃")),
+        ];
+        let after = vec![
+            // one of the two identical flowers is gone
+            artifact(ArtifactSlot::Flower, 0.078, false, None),
+            artifact(ArtifactSlot::Feather, 0.140, true, Some("行秋")),
+            artifact(ArtifactSlot::Sand, 0.210, true, Some("胡桃")),
+            artifact(ArtifactSlot::Goblet, 0.062, false, None),
+        ];
+
+        let diff = ArtifactDiff::new(&before, &after);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].slot, ArtifactSlot::Goblet);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].slot, ArtifactSlot::Flower);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].1.equip.as_deref(), Some("行秋"));
+
+        let text = format!("{}", diff);
+        assert!(text.ends_with("新增: 1，移除: 1，变化: 1\n"));
+        assert!(ArtifactDiff::new(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_diff_json() {
+        let before = vec![
+            artifact(ArtifactSlot::Flower, 0.078, false, None),
+            artifact(ArtifactSlot::Feather, 0.140, false, None),
+        ];
+        let after = vec![
+            artifact(ArtifactSlot::Feather, 0.140, true, None),
+            artifact(ArtifactSlot::Head, 0.132, false, None),
+        ];
+
+        let json = serde_json::to_value(ArtifactDiff::new(&before, &after)).unwrap();
+        assert_eq!(json["added"].as_array().unwrap().len(), 1);
+        assert_eq!(json["added"][0]["position"], "head");
+        assert_eq!(json["removed"].as_array().unwrap().len(), 1);
+        assert_eq!(json["removed"][0]["position"], "flower");
+        assert_eq!(json["changed"][0]["before"]["position"], "feather");
+        assert_eq!(json["changed"][0]["after"]["position"], "feather");
+        assert_eq!(json["changed"][0]["lock"], serde_json::json!([false, true]));
+    }
+}
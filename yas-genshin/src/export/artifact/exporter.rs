@@ -1,13 +1,14 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::FromArgMatches;
 
 use yas::export::{AssetEmitter, ExportAssets};
 
 use crate::artifact::GenshinArtifact;
 use crate::export::artifact::{ExportArtifactConfig, GenshinArtifactExportFormat};
-use crate::export::artifact::csv::GenshinArtifactCSVFormat;
+use crate::export::artifact::csv::{GenshinArtifactCSVFormat, GenshinArtifactCSVLayout};
+use crate::scanner::GenshinArtifactScanResult;
 
 use super::good::GOODFormat;
 use super::mingyu_lab::MingyuLabFormat;
@@ -18,17 +19,62 @@ pub struct GenshinArtifactExporter<'a> {
     pub format: GenshinArtifactExportFormat,
     pub results: Option<&'a [GenshinArtifact]>,
     pub output_dir: PathBuf,
+    pub csv_layout: GenshinArtifactCSVLayout,
+    /// appended to every file name, set with `--export-dir` so that runs do not overwrite each other
+    pub timestamp: Option<String>,
+    /// the scan results `results` were parsed from, written as `_raw` into mona_extended when set (`--include-raw`)
+    pub raw: Option<&'a [GenshinArtifactScanResult]>,
+}
+
+/// `{stem}_{timestamp}.{extension}`, or `{stem}.{extension}` without a timestamp
+fn export_file_name(stem: &str, extension: &str, timestamp: Option<&str>) -> String {
+    match timestamp {
+        Some(timestamp) => format!("{}_{}.{}", stem, timestamp, extension),
+        None => format!("{}.{}", stem, extension),
+    }
+}
+
+/// Create `dir` if needed and make sure files can be written into it
+fn prepare_export_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("无法创建导出目录 {:?}", dir))?;
+    let probe = dir.join(".yas_write_test");
+    std::fs::write(&probe, b"")
+        .with_context(|| format!("导出目录 {:?} 不可写", dir))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
 }
 
 impl <'a> GenshinArtifactExporter<'a> {
     pub fn new(arg_matches: &clap::ArgMatches, results: &'a [GenshinArtifact]) -> Result<Self> {
         let config = ExportArtifactConfig::from_arg_matches(arg_matches)?;
+        let (output_dir, timestamp) = match config.export_dir {
+            Some(dir) => {
+                prepare_export_dir(&dir)?;
+                (dir, Some(chrono::Local::now().format("%Y%m%d_%H%M%S").to_string()))
+            },
+            None => (PathBuf::from(&config.output_dir), None),
+        };
         Ok(Self {
             format: config.format,
             results: Some(results),
-            output_dir: PathBuf::from(&config.output_dir)
+            output_dir,
+            csv_layout: config.csv_layout,
+            timestamp,
+            raw: None,
         })
     }
+
+    fn mona_extended(&self, results: &'a [GenshinArtifact]) -> MonaExtendedFormat<'a> {
+        match self.raw {
+            Some(raw) => MonaExtendedFormat::with_raw(results, raw),
+            None => MonaExtendedFormat::new(results),
+        }
+    }
+
+    pub fn path(&self, stem: &str, extension: &str) -> PathBuf {
+        self.output_dir.join(export_file_name(stem, extension, self.timestamp.as_deref()))
+    }
 }
 
 impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
@@ -41,7 +87,7 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
 
         match self.format {
             GenshinArtifactExportFormat::Mona => {
-                let path = self.output_dir.join("mona.json");
+                let path = self.path("mona", "json");
                 let value = MonaFormat::new(results);
                 let contents = serde_json::to_string(&value).unwrap();
 
@@ -52,8 +98,8 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                     Some(String::from("莫娜圣遗物格式")));
             },
             GenshinArtifactExportFormat::MonaExtended => {
-                let path = self.output_dir.join("mona_extended.json");
-                let value = MonaExtendedFormat::new(results);
+                let path = self.path("mona_extended", "json");
+                let value = self.mona_extended(results);
                 let contents = serde_json::to_string(&value).unwrap();
 
                 export_assets.add_asset(
@@ -63,7 +109,7 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                     Some(String::from("莫娜圣遗物格式(扩展)")));
             },
             GenshinArtifactExportFormat::MingyuLab => {
-                let path = self.output_dir.join("mingyulab.json");
+                let path = self.path("mingyulab", "json");
                 let value = MingyuLabFormat::new(results);
                 let contents = serde_json::to_string(&value).unwrap();
 
@@ -74,7 +120,7 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                     Some(String::from("原魔计算器圣遗物格式")));
             },
             GenshinArtifactExportFormat::Good => {
-                let path = self.output_dir.join("good.json");
+                let path = self.path("good", "json");
                 let value = GOODFormat::new(results);
                 let contents = serde_json::to_string(&value).unwrap();
 
@@ -85,8 +131,8 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                     Some(String::from("GOOD圣遗物格式")));
             },
             GenshinArtifactExportFormat::CSV => {
-                let path = self.output_dir.join("artifacts.csv");
-                let value = GenshinArtifactCSVFormat::new(results);
+                let path = self.path("artifacts", "csv");
+                let value = GenshinArtifactCSVFormat::new(results, self.csv_layout);
                 let contents = value.to_csv_string();
                 export_assets.add_asset(
                     Some(String::from("artifacts csv format")),
@@ -98,7 +144,7 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
             GenshinArtifactExportFormat::All => {
                 // mona
                 {
-                    let path = self.output_dir.join("mona.json");
+                    let path = self.path("mona", "json");
                     let value = MonaFormat::new(results);
                     let contents = serde_json::to_string(&value).unwrap();
 
@@ -110,8 +156,8 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                 }
                 // mona extended
                 {
-                    let path = self.output_dir.join("mona_extended.json");
-                    let value = MonaExtendedFormat::new(results);
+                    let path = self.path("mona_extended", "json");
+                    let value = self.mona_extended(results);
                     let contents = serde_json::to_string(&value).unwrap();
 
                     export_assets.add_asset(
@@ -122,7 +168,7 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                 }
                 // mingyulab
                 {
-                    let path = self.output_dir.join("mingyulab.json");
+                    let path = self.path("mingyulab", "json");
                     let value = MingyuLabFormat::new(results);
                     let contents = serde_json::to_string(&value).unwrap();
 
@@ -134,7 +180,7 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                 }
                 // good
                 {
-                    let path = self.output_dir.join("good.json");
+                    let path = self.path("good", "json");
                     let value = GOODFormat::new(results);
                     let contents = serde_json::to_string(&value).unwrap();
 
@@ -146,8 +192,8 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
                 }
                 // csv
                 {
-                    let path = self.output_dir.join("artifacts.csv");
-                    let value = GenshinArtifactCSVFormat::new(results);
+                    let path = self.path("artifacts", "csv");
+                    let value = GenshinArtifactCSVFormat::new(results, self.csv_layout);
                     let contents = value.to_csv_string();
                     export_assets.add_asset(
                         Some(String::from("csv")),
@@ -160,3 +206,30 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_file_name() {
+        assert_eq!(export_file_name("good", "json", None), "good.json");
+        assert_eq!(export_file_name("good", "json", Some("20240102_030405")), "good_20240102_030405.json");
+        assert_eq!(export_file_name("artifacts", "csv", Some("20240102_030405")), "artifacts_20240102_030405.csv");
+    }
+
+    #[test]
+    fn test_prepare_export_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("nested");
+
+        prepare_export_dir(&dir).unwrap();
+        assert!(dir.is_dir());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        // a file where the directory should be
+        let file = dir.join("file");
+        std::fs::write(&file, b"").unwrap();
+        assert!(prepare_export_dir(&file.join("sub")).is_err());
+    }
+}
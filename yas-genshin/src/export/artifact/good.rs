@@ -34,7 +34,8 @@ impl<'a> Serialize for GOODArtifact<'a> {
             substats.push(good_stat)
         }
 
-        let mut root = serializer.serialize_map(Some(8))?;
+        let mut root = serializer.serialize_map(Some(9))?;
+        root.serialize_entry("id", &artifact.deterministic_uuid().to_string())?;
         root.serialize_entry("setKey", artifact.set_name.to_good())?;
         root.serialize_entry("slotKey", artifact.slot.to_good())?;
         root.serialize_entry("level", &artifact.level)?;
@@ -178,7 +179,7 @@ impl ArtifactSetName {
     }
 }
 
-fn equip_from_zh_cn(equip: Option<&str>) -> &'static str {
+pub(crate) fn equip_from_zh_cn(equip: Option<&str>) -> &'static str {
     match equip {
         Some("旅行者") => "Traveler",
         Some("神里绫华") => "KamisatoAyaka",
@@ -265,6 +266,10 @@ fn equip_from_zh_cn(equip: Option<&str>) -> &'static str {
         Some("赛索斯") => "Sethos",
         Some("克洛琳德") => "Clorinde",
         Some("艾梅莉埃") => "Emilie",
+        Some("卡齐娜") => "Kachina",
+        Some("玛拉妮") => "Mualani",
+        Some("基尼奇") => "Kinich",
+        Some("希诺宁") => "Xilonen",
         _ => "",
     }
 }
@@ -291,3 +296,103 @@ impl<'a> GOODFormat<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::fixtures::{self, stat};
+
+    fn artifact(set_name: ArtifactSetName, slot: ArtifactSlot, equip: Option<&str>) -> GenshinArtifact {
+        GenshinArtifact {
+            set_name,
+            slot,
+            lock: true,
+            main_stat: stat(ArtifactStatName::Critical, 0.311),
+            sub_stat_1: Some(stat(ArtifactStatName::CriticalDamage, 0.218)),
+            sub_stat_2: Some(stat(ArtifactStatName::Atk, 19.0)),
+            sub_stat_3: Some(stat(ArtifactStatName::ElementalMastery, 40.0)),
+            sub_stat_4: Some(ArtifactStat { pending: true, ..stat(ArtifactStatName::Recharge, 0.065) }),
+            equip: equip.map(String::from),
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_good_schema_keys() {
+        let artifacts = vec![
+            artifact(ArtifactSetName::GladiatorFinale, ArtifactSlot::Head, Some("胡桃")),
+            artifact(ArtifactSetName::CrimsonWitch, ArtifactSlot::Feather, None),
+        ];
+        let json = serde_json::to_value(GOODFormat::new(&artifacts)).unwrap();
+
+        assert_eq!(json["format"], "GOOD");
+        assert_eq!(json["version"], 1);
+
+        let good = &json["artifacts"][0];
+        let keys: HashSet<&str> = good.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        let expected: HashSet<&str> = [
+            "id", "setKey", "slotKey", "level", "rarity", "mainStatKey", "location", "lock", "substats",
+        ].into_iter().collect();
+        assert_eq!(keys, expected);
+
+        assert_eq!(good["setKey"], "GladiatorsFinale");
+        assert_eq!(good["slotKey"], "circlet");
+        assert_eq!(good["mainStatKey"], "critRate_");
+        assert_eq!(good["rarity"], 5);
+        assert_eq!(good["level"], 20);
+        assert_eq!(good["location"], "HuTao");
+        assert_eq!(good["lock"], true);
+
+        let substats = good["substats"].as_array().unwrap();
+        let substat_keys: Vec<&str> = substats.iter().map(|s| s["key"].as_str().unwrap()).collect();
+        assert_eq!(substat_keys, vec!["critDMG_", "atk", "eleMas", "enerRech_"]);
+        // percentages are written as percent numbers, flat stats as is
+        assert!((substats[0]["value"].as_f64().unwrap() - 21.8).abs() < 1e-9);
+        assert_eq!(substats[1]["value"], 19.0);
+        assert_eq!(substats[3]["pending"], true);
+        assert!(substats[0].get("pending").is_none());
+
+        let unequipped = &json["artifacts"][1];
+        assert_eq!(unequipped["setKey"], "CrimsonWitchOfFlames");
+        assert_eq!(unequipped["slotKey"], "plume");
+        assert_eq!(unequipped["location"], "");
+    }
+
+    #[test]
+    fn test_equip_to_good_location() {
+        assert_eq!(equip_from_zh_cn(Some("胡桃")), "HuTao");
+        assert_eq!(equip_from_zh_cn(Some("雷电将军")), "RaidenShogun");
+        assert_eq!(equip_from_zh_cn(Some("枫原万叶")), "KaedeharaKazuha");
+        assert_eq!(equip_from_zh_cn(Some("纳西妲")), "Nahida");
+        assert_eq!(equip_from_zh_cn(Some("芙宁娜")), "Furina");
+        assert_eq!(equip_from_zh_cn(None), "");
+
+        // every character an equip line is read as has a key of its own
+        let mut keys = HashSet::new();
+        for name in crate::character::CHARACTER_NAMES.iter() {
+            let key = equip_from_zh_cn(Some(name));
+            assert!(!key.is_empty(), "no GOOD key for {}", name);
+            assert!(keys.insert(key), "duplicated GOOD key {}", key);
+        }
+
+        // a name that is not known is exported as not equipped
+        let artifacts = vec![artifact(ArtifactSetName::GladiatorFinale, ArtifactSlot::Head, Some("不存在的角色"))];
+        let json = serde_json::to_value(GOODFormat::new(&artifacts)).unwrap();
+        assert_eq!(json["artifacts"][0]["location"], "");
+    }
+
+    #[test]
+    fn test_good_set_keys_unique() {
+        let mut keys = HashSet::new();
+        for set_name in ArtifactSetName::iter() {
+            let key = set_name.to_good();
+            assert!(key.chars().all(|c| c.is_ascii_alphanumeric()), "{}", key);
+            assert!(key.starts_with(|c: char| c.is_ascii_uppercase()), "{}", key);
+            assert!(keys.insert(key), "duplicated GOOD key {}", key);
+        }
+    }
+}
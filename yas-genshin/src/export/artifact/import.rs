@@ -0,0 +1,245 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, GenshinArtifact};
+use crate::character::CHARACTER_NAMES;
+
+use super::good::equip_from_zh_cn;
+
+#[derive(Deserialize)]
+struct MonaStat {
+    name: String,
+    value: f64,
+    #[serde(default)]
+    pending: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MonaArtifact {
+    set_name: String,
+    position: String,
+    main_tag: MonaStat,
+    normal_tags: Vec<MonaStat>,
+    level: i32,
+    star: i32,
+    equip: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MonaExport {
+    flower: Vec<MonaArtifact>,
+    feather: Vec<MonaArtifact>,
+    sand: Vec<MonaArtifact>,
+    cup: Vec<MonaArtifact>,
+    head: Vec<MonaArtifact>,
+}
+
+#[derive(Deserialize)]
+struct GOODStat {
+    key: String,
+    value: f64,
+    #[serde(default)]
+    pending: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GOODArtifact {
+    set_key: String,
+    slot_key: String,
+    level: i32,
+    rarity: i32,
+    main_stat_key: String,
+    location: String,
+    #[serde(default)]
+    lock: bool,
+    substats: Vec<GOODStat>,
+}
+
+#[derive(Deserialize)]
+struct GOODExport {
+    artifacts: Vec<GOODArtifact>,
+}
+
+fn set_from_mona(name: &str) -> Result<ArtifactSetName> {
+    ArtifactSetName::iter().find(|set| set.to_mona() == name)
+        .ok_or_else(|| anyhow!("unknown mona set name {:?}", name))
+}
+
+fn slot_from_mona(name: &str) -> Result<ArtifactSlot> {
+    ArtifactSlot::iter().find(|slot| slot.to_mona() == name)
+        .ok_or_else(|| anyhow!("unknown mona position {:?}", name))
+}
+
+fn stat_from_mona(stat: &MonaStat) -> Result<ArtifactStat> {
+    let name = ArtifactStatName::from_mona(&stat.name)
+        .ok_or_else(|| anyhow!("unknown mona stat {:?}", stat.name))?;
+    Ok(ArtifactStat { name, value: stat.value, pending: stat.pending })
+}
+
+fn stat_name_from_good(key: &str) -> Result<ArtifactStatName> {
+    ArtifactStatName::iter().find(|s| s.to_good() == key)
+        .ok_or_else(|| anyhow!("unknown GOOD stat key {:?}", key))
+}
+
+/// GOOD writes percentages as e.g. 7.8 for 7.8%, see `GOODStat::new`
+fn stat_from_good(stat: &GOODStat) -> Result<ArtifactStat> {
+    let name = stat_name_from_good(&stat.key)?;
+    let value = match name {
+        ArtifactStatName::Atk
+        | ArtifactStatName::ElementalMastery
+        | ArtifactStatName::Hp
+        | ArtifactStatName::Def => stat.value,
+        _ => stat.value / 100.0,
+    };
+    Ok(ArtifactStat { name, value, pending: stat.pending })
+}
+
+/// The Chinese character name of a GOOD location key, None for unequipped or unknown keys
+fn equip_from_good(location: &str) -> Option<String> {
+    if location.is_empty() {
+        return None;
+    }
+    CHARACTER_NAMES.iter()
+        .find(|name| equip_from_zh_cn(Some(**name)) == location)
+        .map(|name| name.to_string())
+}
+
+/// An artifact without sub stats, they are filled in by `with_sub_stats`
+fn artifact(set_name: ArtifactSetName, slot: ArtifactSlot, star: i32, level: i32, main_stat: ArtifactStat) -> GenshinArtifact {
+    GenshinArtifact {
+        set_name,
+        slot,
+        star,
+        lock: false,
+        level,
+        main_stat,
+        sub_stat_1: None,
+        sub_stat_2: None,
+        sub_stat_3: None,
+        sub_stat_4: None,
+        equip: None,
+    }
+}
+
+fn with_sub_stats(artifact: GenshinArtifact, sub_stats: Vec<ArtifactStat>) -> GenshinArtifact {
+    let mut sub_stats = sub_stats.into_iter();
+    GenshinArtifact {
+        sub_stat_1: sub_stats.next(),
+        sub_stat_2: sub_stats.next(),
+        sub_stat_3: sub_stats.next(),
+        sub_stat_4: sub_stats.next(),
+        ..artifact
+    }
+}
+
+fn from_mona(export: MonaExport) -> Result<Vec<GenshinArtifact>> {
+    let all = export.flower.iter()
+        .chain(export.feather.iter())
+        .chain(export.sand.iter())
+        .chain(export.cup.iter())
+        .chain(export.head.iter());
+
+    all.map(|artifact| {
+        let sub_stats = artifact.normal_tags.iter().map(stat_from_mona).collect::<Result<Vec<_>>>()?;
+        let parsed = GenshinArtifact {
+            equip: artifact.equip.clone(),
+            ..self::artifact(
+                set_from_mona(&artifact.set_name)?,
+                slot_from_mona(&artifact.position)?,
+                artifact.star,
+                artifact.level,
+                stat_from_mona(&artifact.main_tag)?,
+            )
+        };
+        Ok(with_sub_stats(parsed, sub_stats))
+    }).collect()
+}
+
+/// GOOD does not record the main stat value, it is left as 0
+fn from_good(export: GOODExport) -> Result<Vec<GenshinArtifact>> {
+    export.artifacts.iter().map(|artifact| {
+        let set_name = ArtifactSetName::iter().find(|set| set.to_good() == artifact.set_key)
+            .ok_or_else(|| anyhow!("unknown GOOD set key {:?}", artifact.set_key))?;
+        let slot = ArtifactSlot::iter().find(|slot| slot.to_good() == artifact.slot_key)
+            .ok_or_else(|| anyhow!("unknown GOOD slot key {:?}", artifact.slot_key))?;
+        let main_stat = ArtifactStat {
+            name: stat_name_from_good(&artifact.main_stat_key)?,
+            value: 0.0,
+            pending: false,
+        };
+        let sub_stats = artifact.substats.iter().map(stat_from_good).collect::<Result<Vec<_>>>()?;
+
+        let parsed = GenshinArtifact {
+            lock: artifact.lock,
+            equip: equip_from_good(&artifact.location),
+            ..self::artifact(set_name, slot, artifact.rarity, artifact.level, main_stat)
+        };
+        Ok(with_sub_stats(parsed, sub_stats))
+    }).collect()
+}
+
+/// Read artifacts back from a Mona (or Mona extended) or GOOD export
+pub fn parse_artifacts(json: &str) -> Result<Vec<GenshinArtifact>> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    if value.get("format").and_then(|f| f.as_str()) == Some("GOOD") {
+        let export: GOODExport = serde_json::from_value(value).context("parse GOOD export")?;
+        from_good(export)
+    } else {
+        let export: MonaExport = serde_json::from_value(value).context("parse mona export")?;
+        from_mona(export)
+    }
+}
+
+pub fn load_artifacts(path: &Path) -> Result<Vec<GenshinArtifact>> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("read {:?}", path))?;
+    parse_artifacts(&json).with_context(|| format!("parse {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::good::GOODFormat;
+    use super::super::mona_extended::MonaExtendedFormat;
+    use super::*;
+    use crate::fixtures;
+
+    fn artifacts() -> Vec<GenshinArtifact> {
+        let main_stat = ArtifactStat { name: ArtifactStatName::PyroBonus, value: 0.466, pending: false };
+        let sub_stats = vec![
+            ArtifactStat { name: ArtifactStatName::Critical, value: 0.039, pending: false },
+            ArtifactStat { name: ArtifactStatName::Atk, value: 19.0, pending: false },
+        ];
+        let parsed = GenshinArtifact {
+            set_name: ArtifactSetName::CrimsonWitch,
+            slot: ArtifactSlot::Goblet,
+            lock: true,
+            main_stat,
+            equip: Some(String::from("胡桃")),
+            ..fixtures::artifact()
+        };
+        vec![with_sub_stats(parsed, sub_stats)]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let artifacts = artifacts();
+
+        let mona = serde_json::to_string(&MonaExtendedFormat::new(&artifacts)).unwrap();
+        let parsed = parse_artifacts(&mona).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].main_stat, artifacts[0].main_stat);
+        assert_eq!(parsed[0].sub_stat_2, artifacts[0].sub_stat_2);
+        assert_eq!(parsed[0].equip.as_deref(), Some("胡桃"));
+
+        let good = serde_json::to_string(&GOODFormat::new(&artifacts)).unwrap();
+        let parsed = parse_artifacts(&good).unwrap();
+        assert_eq!(parsed[0].set_name, ArtifactSetName::CrimsonWitch);
+        assert_eq!(parsed[0].sub_stat_1, artifacts[0].sub_stat_1);
+        assert_eq!(parsed[0].equip.as_deref(), Some("胡桃"));
+        assert!(parsed[0].lock);
+    }
+}
@@ -0,0 +1,69 @@
+use clap::ValueEnum;
+
+use crate::artifact::GenshinArtifact;
+
+/// Which artifacts to export by their lock state
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ExportLockFilter {
+    All,
+    /// Only the locked ones, e.g. those kept by an auto-lock run
+    #[value(name = "locked")]
+    LockedOnly,
+    #[value(name = "unlocked")]
+    UnlockedOnly,
+}
+
+impl Default for ExportLockFilter {
+    fn default() -> Self {
+        Self::All
+    }
+}
+
+impl ExportLockFilter {
+    pub fn keeps(&self, artifact: &GenshinArtifact) -> bool {
+        match *self {
+            ExportLockFilter::All => true,
+            ExportLockFilter::LockedOnly => artifact.lock,
+            ExportLockFilter::UnlockedOnly => !artifact.lock,
+        }
+    }
+
+    pub fn apply(&self, artifacts: &[GenshinArtifact]) -> Vec<GenshinArtifact> {
+        artifacts.iter()
+            .filter(|artifact| self.keeps(artifact))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::artifact::{ArtifactSetName, ArtifactSlot};
+    use crate::fixtures;
+
+    use super::*;
+
+    fn artifact(slot: ArtifactSlot, lock: bool) -> GenshinArtifact {
+        GenshinArtifact {
+            set_name: ArtifactSetName::CrimsonWitch,
+            slot,
+            lock,
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_lock_filter() {
+        let artifacts = vec![
+            artifact(ArtifactSlot::Flower, true),
+            artifact(ArtifactSlot::Feather, false),
+            artifact(ArtifactSlot::Sand, true),
+        ];
+
+        assert_eq!(ExportLockFilter::All.apply(&artifacts).len(), 3);
+        let locked = ExportLockFilter::LockedOnly.apply(&artifacts);
+        assert_eq!(locked.iter().map(|a| a.slot.clone()).collect::<Vec<_>>(), vec![ArtifactSlot::Flower, ArtifactSlot::Sand]);
+        let unlocked = ExportLockFilter::UnlockedOnly.apply(&artifacts);
+        assert_eq!(unlocked.iter().map(|a| a.slot.clone()).collect::<Vec<_>>(), vec![ArtifactSlot::Feather]);
+    }
+}
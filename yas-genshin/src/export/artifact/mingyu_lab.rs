@@ -1,3 +1,6 @@
+use std::collections::BTreeSet;
+
+use log::warn;
 use serde::ser::{Serialize, SerializeMap, Serializer};
 
 use crate::artifact::{
@@ -6,6 +9,7 @@ use crate::artifact::{
 
 struct MingyuLabArtifact<'a> {
     artifact: &'a GenshinArtifact,
+    set_key: &'static str,
 }
 
 impl<'a> Serialize for MingyuLabArtifact<'a> {
@@ -31,7 +35,7 @@ impl<'a> Serialize for MingyuLabArtifact<'a> {
 
         let artifact = &self.artifact;
         let mut root = serializer.serialize_map(Some(13))?;
-        root.serialize_entry("asKey", artifact.set_name.to_mingyu_lab())?;
+        root.serialize_entry("asKey", self.set_key)?;
         root.serialize_entry("rarity", &artifact.star)?;
         root.serialize_entry("slot", artifact.slot.to_mingyu_lab())?;
         root.serialize_entry("level", &artifact.level)?;
@@ -87,8 +91,9 @@ impl ArtifactSlot {
 }
 
 impl ArtifactSetName {
-    pub fn to_mingyu_lab(&self) -> &'static str {
-        match self {
+    /// `None` for the sets MingyuLab does not know
+    pub fn to_mingyu_lab(&self) -> Option<&'static str> {
+        let key = match self {
             ArtifactSetName::ArchaicPetra => "archaic_petra",
             ArtifactSetName::HeartOfDepth => "heart_of_depth",
             ArtifactSetName::BlizzardStrayer => "blizzard_walker",
@@ -146,30 +151,64 @@ impl ArtifactSetName {
             ArtifactSetName::ADayCarvedFromRisingWinds => "a_day_carved_from_rising_winds",
             ArtifactSetName::AubadeOfMorningstarAndMoon => "aubade_of_morningstar_and_moon",
 
-            // Not supported by Mingyulab
-            ArtifactSetName::Adventurer => unreachable!(),
-            ArtifactSetName::LuckyDog => unreachable!(),
-            ArtifactSetName::TravelingDoctor => unreachable!(),
-        }
+            ArtifactSetName::Adventurer
+            | ArtifactSetName::LuckyDog
+            | ArtifactSetName::TravelingDoctor => return None,
+        };
+        Some(key)
     }
 }
 
 pub struct MingyuLabFormat<'a> {
-    artifacts: Vec<MingyuLabArtifact<'a>>,
+    version: String,
+    flower: Vec<MingyuLabArtifact<'a>>,
+    feather: Vec<MingyuLabArtifact<'a>>,
+    sand: Vec<MingyuLabArtifact<'a>>,
+    cup: Vec<MingyuLabArtifact<'a>>,
+    head: Vec<MingyuLabArtifact<'a>>,
 }
 
 impl<'a> MingyuLabFormat<'a> {
-    pub fn new(results: &'a [GenshinArtifact]) -> MingyuLabFormat {
-        let artifacts: Vec<MingyuLabArtifact<'a>> = results
-            .iter()
-            .filter(|artifact| {
-                artifact.set_name != ArtifactSetName::Adventurer
-                    && artifact.set_name != ArtifactSetName::LuckyDog
-                    && artifact.set_name != ArtifactSetName::TravelingDoctor
-            })
-            .map(|artifact| MingyuLabArtifact { artifact })
-            .collect();
-        MingyuLabFormat { artifacts }
+    /// Artifacts of sets MingyuLab does not know are left out, with a warning naming the sets
+    pub fn new(results: &'a [GenshinArtifact]) -> MingyuLabFormat<'a> {
+        let mut flower: Vec<MingyuLabArtifact> = Vec::new();
+        let mut feather: Vec<MingyuLabArtifact> = Vec::new();
+        let mut sand: Vec<MingyuLabArtifact> = Vec::new();
+        let mut cup: Vec<MingyuLabArtifact> = Vec::new();
+        let mut head: Vec<MingyuLabArtifact> = Vec::new();
+        let mut dropped: BTreeSet<String> = BTreeSet::new();
+
+        for artifact in results.iter() {
+            let set_key = match artifact.set_name.to_mingyu_lab() {
+                Some(v) => v,
+                None => {
+                    dropped.insert(artifact.set_name.to_string());
+                    continue;
+                },
+            };
+            let wrapper = MingyuLabArtifact { artifact, set_key };
+            match artifact.slot {
+                ArtifactSlot::Flower => flower.push(wrapper),
+                ArtifactSlot::Feather => feather.push(wrapper),
+                ArtifactSlot::Sand => sand.push(wrapper),
+                ArtifactSlot::Goblet => cup.push(wrapper),
+                ArtifactSlot::Head => head.push(wrapper),
+            }
+        }
+
+        if !dropped.is_empty() {
+            let names: Vec<String> = dropped.into_iter().collect();
+            warn!("MingyuLab does not support the sets {}, their artifacts are not exported", names.join(", "));
+        }
+
+        MingyuLabFormat {
+            version: String::from("1"),
+            flower,
+            feather,
+            sand,
+            cup,
+            head,
+        }
     }
 }
 
@@ -178,6 +217,110 @@ impl Serialize for MingyuLabFormat<'_> {
     where
         S: Serializer,
     {
-        self.artifacts.serialize(serializer)
+        let mut root = serializer.serialize_map(Some(6))?;
+        root.serialize_entry("version", &self.version)?;
+        root.serialize_entry("flower", &self.flower)?;
+        root.serialize_entry("feather", &self.feather)?;
+        root.serialize_entry("sand", &self.sand)?;
+        root.serialize_entry("cup", &self.cup)?;
+        root.serialize_entry("head", &self.head)?;
+        root.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::fixtures::{self, stat};
+
+    fn stat_from_mingyu_lab(key: &str) -> ArtifactStatName {
+        ArtifactStatName::iter().find(|name| name.to_mingyu_lab() == key).unwrap()
+    }
+
+    /// Reads back one exported artifact, the way MingyuLab's importer sees it
+    fn reimport(json: &serde_json::Value) -> GenshinArtifact {
+        let sub_stat = |i: usize| {
+            let name = stat_from_mingyu_lab(json[format!("subStat{}Type", i)].as_str().unwrap());
+            let value = json[format!("subStat{}Value", i)].as_f64().unwrap();
+            if value == 0.0 {
+                return None;
+            }
+            let value = match name {
+                ArtifactStatName::Atk | ArtifactStatName::ElementalMastery | ArtifactStatName::Hp | ArtifactStatName::Def => value,
+                _ => value / 100.0,
+            };
+            Some(stat(name, value))
+        };
+
+        GenshinArtifact {
+            set_name: ArtifactSetName::iter()
+                .find(|s| s.to_mingyu_lab().is_some_and(|key| key == json["asKey"]))
+                .unwrap(),
+            slot: ArtifactSlot::iter().find(|s| s.to_mingyu_lab() == json["slot"]).unwrap(),
+            star: json["rarity"].as_i64().unwrap() as i32,
+            level: json["level"].as_i64().unwrap() as i32,
+            // MingyuLab keeps no main stat value
+            main_stat: stat(stat_from_mingyu_lab(json["mainStat"].as_str().unwrap()), 0.0),
+            sub_stat_1: sub_stat(1),
+            sub_stat_2: sub_stat(2),
+            sub_stat_3: sub_stat(3),
+            sub_stat_4: sub_stat(4),
+            ..fixtures::artifact()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let artifacts = vec![
+            GenshinArtifact {
+                set_name: ArtifactSetName::EmblemOfSeveredFate,
+                slot: ArtifactSlot::Sand,
+                main_stat: stat(ArtifactStatName::Recharge, 0.0),
+                sub_stat_1: Some(stat(ArtifactStatName::Critical, 0.07)),
+                sub_stat_2: Some(stat(ArtifactStatName::CriticalDamage, 0.218)),
+                sub_stat_3: Some(stat(ArtifactStatName::Atk, 19.0)),
+                ..fixtures::artifact()
+            },
+            GenshinArtifact {
+                set_name: ArtifactSetName::Adventurer,
+                star: 3,
+                level: 0,
+                main_stat: stat(ArtifactStatName::Hp, 0.0),
+                ..fixtures::artifact()
+            },
+        ];
+
+        let json = serde_json::to_value(MingyuLabFormat::new(&artifacts)).unwrap();
+        assert_eq!(json["version"], "1");
+        // sets MingyuLab does not know are left out
+        for key in ["flower", "feather", "cup", "head"] {
+            assert!(json[key].as_array().unwrap().is_empty());
+        }
+        let exported = json["sand"].as_array().unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0]["slot"], "eon");
+        assert_eq!(exported[0]["mainStat"], "energyRecharge");
+
+        let back = reimport(&exported[0]);
+        assert_eq!(back.set_name, artifacts[0].set_name);
+        assert_eq!(back.slot, artifacts[0].slot);
+        assert_eq!((back.star, back.level), (5, 20));
+        assert_eq!(back.main_stat.name, artifacts[0].main_stat.name);
+        for (a, b) in [
+            (&back.sub_stat_1, &artifacts[0].sub_stat_1),
+            (&back.sub_stat_2, &artifacts[0].sub_stat_2),
+            (&back.sub_stat_3, &artifacts[0].sub_stat_3),
+            (&back.sub_stat_4, &artifacts[0].sub_stat_4),
+        ] {
+            match (a, b) {
+                (Some(a), Some(b)) => {
+                    assert_eq!(a.name, b.name);
+                    assert!((a.value - b.value).abs() < 1e-9);
+                },
+                (a, b) => assert_eq!(a.is_none(), b.is_none()),
+            }
+        }
     }
 }
@@ -1,7 +1,14 @@
+pub use character_filter::{filter_by_character, is_character};
 pub use config::ExportArtifactConfig;
+pub use csv::GenshinArtifactCSVLayout;
+pub use diff::ArtifactDiff;
 pub use export_format::GenshinArtifactExportFormat;
 pub use exporter::GenshinArtifactExporter;
+pub use import::{load_artifacts, parse_artifacts};
+pub use lock_filter::ExportLockFilter;
 
+mod character_filter;
+mod lock_filter;
 mod good;
 mod mingyu_lab;
 mod mona_uranai;
@@ -10,3 +17,5 @@ mod exporter;
 mod export_format;
 mod config;
 mod csv;
+mod diff;
+mod import;
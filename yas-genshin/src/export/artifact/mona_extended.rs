@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::convert::From;
 
 use serde::ser::{Serialize, SerializeMap, Serializer};
@@ -5,6 +6,7 @@ use serde::ser::{Serialize, SerializeMap, Serializer};
 use crate::artifact::{
     ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, GenshinArtifact,
 };
+use crate::scanner::GenshinArtifactScanResult;
 
 use super::mona_uranai::MonaFormat; // Re-use utilities if possible, or just copy logic
 
@@ -26,8 +28,8 @@ impl<'a> Serialize for MonaExtendedStat<'a> {
     }
 }
 
-// Wrapper for GenshinArtifact
-pub struct MonaExtendedArtifact<'a>(&'a GenshinArtifact);
+// Wrapper for GenshinArtifact, with the scan result it was parsed from if it is to be written as `_raw`
+pub struct MonaExtendedArtifact<'a>(&'a GenshinArtifact, Option<&'a GenshinArtifactScanResult>);
 
 impl<'a> Serialize for MonaExtendedArtifact<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -37,6 +39,7 @@ impl<'a> Serialize for MonaExtendedArtifact<'a> {
         let artifact = self.0;
         let mut root = serializer.serialize_map(None)?;
 
+        root.serialize_entry("id", &artifact.deterministic_uuid().to_string())?;
         root.serialize_entry("setName", &artifact.set_name.to_mona()).unwrap();
         root.serialize_entry("position", &artifact.slot.to_mona()).unwrap();
         root.serialize_entry("mainTag", &artifact.main_stat).unwrap(); // ArtifactStat has default impl which is fine for main stat?
@@ -63,6 +66,9 @@ impl<'a> Serialize for MonaExtendedArtifact<'a> {
         root.serialize_entry("level", &artifact.level)?;
         root.serialize_entry("star", &artifact.star)?;
         root.serialize_entry("equip", &artifact.equip)?;
+        if let Some(raw) = self.1 {
+            root.serialize_entry("_raw", raw)?;
+        }
 
         root.end()
     }
@@ -95,6 +101,17 @@ impl<'a> Serialize for MonaExtendedFormat<'a> {
 
 impl<'a> MonaExtendedFormat<'a> {
     pub fn new(results: &'a [GenshinArtifact]) -> MonaExtendedFormat<'a> {
+        Self::with_raw(results, &[])
+    }
+
+    /// Like `new`, and every artifact parsed from one of `raw` also gets it as `_raw`,
+    /// the recognized text as is for looking into misreads
+    pub fn with_raw(results: &'a [GenshinArtifact], raw: &'a [GenshinArtifactScanResult]) -> MonaExtendedFormat<'a> {
+        // artifacts may have been filtered since, so they are matched by id rather than by position
+        let raw_by_id: HashMap<uuid::Uuid, &GenshinArtifactScanResult> = raw.iter()
+            .filter_map(|r| GenshinArtifact::try_from(r).ok().map(|a| (a.deterministic_uuid(), r)))
+            .collect();
+
         let mut flower: Vec<MonaExtendedArtifact> = Vec::new();
         let mut feather: Vec<MonaExtendedArtifact> = Vec::new();
         let mut cup: Vec<MonaExtendedArtifact> = Vec::new();
@@ -102,7 +119,7 @@ impl<'a> MonaExtendedFormat<'a> {
         let mut head: Vec<MonaExtendedArtifact> = Vec::new();
 
         for art in results.iter() {
-            let wrapper = MonaExtendedArtifact(art);
+            let wrapper = MonaExtendedArtifact(art, raw_by_id.get(&art.deterministic_uuid()).copied());
             match art.slot {
                 ArtifactSlot::Flower => flower.push(wrapper),
                 ArtifactSlot::Feather => feather.push(wrapper),
@@ -122,3 +139,30 @@ impl<'a> MonaExtendedFormat<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_raw_only_when_given() {
+        let raw = vec![GenshinArtifactScanResult {
+            sub_stat: [
+                String::from("暴击率+3.9%"),
+                String::from("暴击伤害+7.8%"),
+                String::from("攻击力+5.8%"),
+                String::from("元素充能效率+6.5%"),
+            ],
+            ..fixtures::scan_result()
+        }];
+        let artifacts = vec![GenshinArtifact::try_from(&raw[0]).unwrap()];
+
+        let value = serde_json::to_value(MonaExtendedFormat::new(&artifacts)).unwrap();
+        assert!(value["flower"][0].get("_raw").is_none());
+
+        let value = serde_json::to_value(MonaExtendedFormat::with_raw(&artifacts, &raw)).unwrap();
+        assert_eq!(value["flower"][0]["_raw"]["name"], "角斗士的留恋");
+        assert_eq!(value["flower"][0]["_raw"]["sub_stat"][3], "元素充能效率+6.5%");
+    }
+}
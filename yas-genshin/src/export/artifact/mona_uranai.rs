@@ -1,6 +1,7 @@
 use std::convert::From;
 
 use serde::ser::{Serialize, SerializeMap, Serializer};
+use strum::IntoEnumIterator;
 
 use crate::artifact::{
     ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, GenshinArtifact,
@@ -33,6 +34,11 @@ impl ArtifactStatName {
         };
         String::from(temp)
     }
+
+    /// Inverse of `to_mona`
+    pub fn from_mona(name: &str) -> Option<Self> {
+        ArtifactStatName::iter().find(|stat| stat.to_mona() == name)
+    }
 }
 
 impl ArtifactSetName {
@@ -206,3 +212,22 @@ impl<'a> MonaFormat<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_stat_name_to_mona_is_complete() {
+        let mut seen = HashSet::new();
+        for stat in ArtifactStatName::iter() {
+            let name = stat.to_mona();
+            assert!(!name.is_empty(), "{} has no mona name", stat);
+            assert!(seen.insert(name.clone()), "mona name {:?} is used twice", name);
+            assert_eq!(ArtifactStatName::from_mona(&name), Some(stat));
+        }
+        assert_eq!(ArtifactStatName::from_mona("notAStat"), None);
+    }
+}
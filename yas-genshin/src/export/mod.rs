@@ -1 +1,2 @@
 pub mod artifact;
+pub mod weapon;
@@ -0,0 +1,3 @@
+pub use mona::MonaWeaponFormat;
+
+mod mona;
@@ -0,0 +1,69 @@
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::weapon::GenshinWeapon;
+
+/// A weapon with the key names the Mona artifact export uses, written next to it as `mona_weapons.json`
+struct MonaWeapon<'a>(&'a GenshinWeapon);
+
+impl Serialize for MonaWeapon<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let weapon = self.0;
+        let mut root = serializer.serialize_map(Some(5))?;
+        root.serialize_entry("name", &weapon.name)?;
+        root.serialize_entry("level", &weapon.level)?;
+        root.serialize_entry("ascension", &weapon.ascension)?;
+        root.serialize_entry("refine", &weapon.refinement)?;
+        root.serialize_entry("equip", &weapon.equip)?;
+        root.end()
+    }
+}
+
+pub struct MonaWeaponFormat<'a> {
+    version: String,
+    weapons: Vec<MonaWeapon<'a>>,
+}
+
+impl<'a> MonaWeaponFormat<'a> {
+    pub fn new(results: &'a [GenshinWeapon]) -> Self {
+        MonaWeaponFormat {
+            version: String::from("1"),
+            weapons: results.iter().map(MonaWeapon).collect(),
+        }
+    }
+}
+
+impl Serialize for MonaWeaponFormat<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut root = serializer.serialize_map(Some(2))?;
+        root.serialize_entry("version", &self.version)?;
+        root.serialize_entry("weapons", &self.weapons)?;
+        root.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mona_weapon_format() {
+        let weapons = vec![GenshinWeapon {
+            name: String::from("护摩之杖"),
+            level: 80,
+            ascension: 6,
+            refinement: 1,
+            equip: Some(String::from("胡桃")),
+        }];
+        let json = serde_json::to_value(MonaWeaponFormat::new(&weapons)).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "version": "1",
+            "weapons": [{ "name": "护摩之杖", "level": 80, "ascension": 6, "refine": 1, "equip": "胡桃" }],
+        }));
+    }
+}
@@ -0,0 +1,43 @@
+//! Artifacts and scan results for the tests of this crate, each test overrides the fields it cares about
+//! with struct update syntax, e.g. `GenshinArtifact { lock: true, ..fixtures::artifact() }`
+
+use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, GenshinArtifact};
+use crate::scanner::artifact_scanner::GenshinArtifactScanResult;
+
+/// A stat that is not pending (待激活)
+pub fn stat(name: ArtifactStatName, value: f64) -> ArtifactStat {
+    ArtifactStat { name, value, pending: false }
+}
+
+/// A 5 star level 20 Gladiator's Finale flower with 4780 hp, no sub stats, unlocked and not equipped
+pub fn artifact() -> GenshinArtifact {
+    GenshinArtifact {
+        set_name: ArtifactSetName::GladiatorFinale,
+        slot: ArtifactSlot::Flower,
+        star: 5,
+        lock: false,
+        level: 20,
+        main_stat: stat(ArtifactStatName::Hp, 4780.0),
+        sub_stat_1: None,
+        sub_stat_2: None,
+        sub_stat_3: None,
+        sub_stat_4: None,
+        equip: None,
+    }
+}
+
+/// The scan of `artifact`: 角斗士的留恋 with 4,780 hp, empty sub stat lines, read with full confidence
+pub fn scan_result() -> GenshinArtifactScanResult {
+    GenshinArtifactScanResult {
+        name: String::from("角斗士的留恋"),
+        main_stat_name: String::from("生命值"),
+        main_stat_value: String::from("4,780"),
+        sub_stat: Default::default(),
+        equip: String::new(),
+        level: 20,
+        star: 5,
+        lock: false,
+        description: String::new(),
+        confidence: Default::default(),
+    }
+}
@@ -7,4 +7,8 @@ pub mod export;
 pub mod scanner;
 pub mod artifact;
 pub mod character;
+pub mod weapon;
 pub mod application;
+
+#[cfg(test)]
+mod fixtures;
@@ -4,48 +4,143 @@ use std::{
     pin::Pin,
     rc::Rc,
     sync::mpsc::{self, Receiver, Sender},
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::FromArgMatches;
 use image::RgbImage;
-use log::{error, info};
+use log::{error, info, warn};
 
-use yas::capture::{Capturer, GenericCapturer};
+use yas::capture::Capturer;
 use yas::game_info::GameInfo;
-use yas::ocr::{yas_ocr_model, ImageToText};
+use yas::ocr::ImageToText;
 use yas::positioning::{Pos, Rect};
 use yas::utils;
+use yas::utils::page_start_row;
 use yas::window_info::FromWindowInfoRepository;
 use yas::window_info::WindowInfoRepository;
 
-use crate::scanner::artifact_scanner::artifact_scanner_worker::ArtifactScannerWorker;
-use crate::scanner::artifact_scanner::lock_list::LockList;
+use crate::artifact::Language;
+use crate::scanner::artifact_scanner::artifact_scanner_worker::{check_panel_captured, crop_panel_rect, get_image_to_text, is_blank_image, ArtifactScannerWorker};
+use crate::scanner::artifact_scanner::lock_list::{lock_skip_reason, LockList};
+use crate::scanner::artifact_scanner::checkpoint::ScanCheckpoint;
+use crate::scanner::artifact_scanner::list_scanner::{read_list_cells, ListCellInfo};
 use crate::scanner::artifact_scanner::message_items::SendItem;
+use crate::scanner::artifact_scanner::scan_event::{ScanEvent, ScanEventSender};
 use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+use crate::scanner::artifact_scanner::scan_timings::ScanTimings;
 use crate::scanner_controller::repository_layout::{
     GenshinRepositoryScanController, GenshinRepositoryScannerLogicConfig,
-    ReturnResult as GenshinRepositoryControllerReturnResult,
+    ReturnResult as GenshinRepositoryControllerReturnResult, ScrollResult,
 };
 
 use super::artifact_scanner_config::GenshinArtifactScannerConfig;
 use super::ArtifactScannerWindowInfo;
 
-fn color_distance(c1: &image::Rgb<u8>, c2: &image::Rgb<u8>) -> usize {
-    let x = c1.0[0] as i32 - c2.0[0] as i32;
-    let y = c1.0[1] as i32 - c2.0[1] as i32;
-    let z = c1.0[2] as i32 - c2.0[2] as i32;
-    (x * x + y * y + z * z) as usize
+/// How long to wait for the worker to recognize an item before giving up on auto-lock
+const WORKER_RESULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What the worker answered for an item sent to it during auto-lock
+#[derive(Debug)]
+enum WorkerReply {
+    /// recognition result, None if it failed
+    Result(Option<GenshinArtifactScanResult>),
+    /// the worker stopped early (min level reached, too many duplicates) and will not send anything more
+    Finished,
+    TimedOut,
+}
+
+/// Wait for the worker's result of the item just sent, without blocking forever if the worker has stopped or is stuck
+fn recv_worker_result(rx: &Receiver<Option<GenshinArtifactScanResult>>, timeout: Duration) -> WorkerReply {
+    match rx.recv_timeout(timeout) {
+        Ok(result) => WorkerReply::Result(result),
+        Err(mpsc::RecvTimeoutError::Disconnected) => WorkerReply::Finished,
+        Err(mpsc::RecvTimeoutError::Timeout) => WorkerReply::TimedOut,
+    }
+}
+
+/// Wait for the worker's result of the current item. While the worker reports a failure, `resend` captures and sends
+/// the same item again, at most `retry_count` times. `resend` returns false if it could not send
+fn recv_with_retry(
+    rx: &Receiver<Option<GenshinArtifactScanResult>>,
+    timeout: Duration,
+    retry_count: u32,
+    mut resend: impl FnMut() -> bool,
+) -> WorkerReply {
+    let mut reply = recv_worker_result(rx, timeout);
+    for attempt in 1..=retry_count {
+        if !matches!(reply, WorkerReply::Result(None)) {
+            break;
+        }
+
+        warn!("识别失败，重新截图识别（第 {} 次）", attempt);
+        if !resend() {
+            break;
+        }
+        reply = recv_worker_result(rx, timeout);
+    }
+    reply
+}
+
+/// The visible (row, col) of item `index` (starting from 0) while its page is on screen, as laid out by the controller
+fn list_cell(index: i32, count: i32, row: i32, col: i32) -> (usize, usize) {
+    let page_size = row * col;
+    let page = index / page_size;
+    let in_page = index % page_size;
+    // the first page is never scrolled
+    let first_row = if page == 0 { 0 } else { page_start_row(count, page * page_size, row, col) };
+    ((first_row + in_page / col) as usize, (in_page % col) as usize)
+}
+
+/// Whether `panel_image` shows no item, i.e. its title is blank while the rest of the panel has content,
+/// as when the cursor ended up past the last item or on an empty slot.
+/// Fails for a flat panel, e.g. a minimized or covered window, see `check_panel_captured`
+fn is_empty_panel(window_info: &ArtifactScannerWindowInfo, panel_image: &RgbImage) -> Result<bool> {
+    let title = crop_panel_rect(window_info.panel_rect, window_info.title_rect, panel_image)?;
+    if !is_blank_image(&title) {
+        return Ok(false);
+    }
+    check_panel_captured(panel_image)?;
+    Ok(true)
+}
+
+/// The item count after the artifact tab title of `language`, see `yas::utils::parse_item_count`
+fn parse_item_count(s: &str, language: Language) -> Option<i32> {
+    utils::parse_item_count(s, language.item_count_keyword())
+}
+
+/// Capture and recognize the item count rect, None if the text is not an item count
+fn read_item_count(
+    capturer: &dyn Capturer<RgbImage>,
+    image_to_text: &dyn ImageToText<RgbImage>,
+    rect: Rect<i32>,
+    origin: Pos<i32>,
+    language: Language,
+) -> Result<Option<i32>> {
+    let im = capturer.capture_relative_to(rect, origin)?;
+    // im.save("item_count.png")?;
+    let s = image_to_text.image_to_text(&im, false)?;
+
+    info!("物品信息: {}", s);
+
+    let count = parse_item_count(&s, language);
+    if count.is_none() {
+        warn!("无法解析物品数量，OCR 结果: {:?}", s);
+    }
+    Ok(count)
 }
 
 pub struct GenshinArtifactScanner {
     scanner_config: GenshinArtifactScannerConfig,
     window_info: ArtifactScannerWindowInfo,
     game_info: GameInfo,
-    image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
+    /// None once the model was moved to the recognition thread by `scan`
+    image_to_text: Option<Box<dyn ImageToText<RgbImage> + Send>>,
     controller: Rc<RefCell<GenshinRepositoryScanController>>,
     capturer: Rc<dyn Capturer<RgbImage>>,
+    /// items sent to recognition in the last scan, duplicates included
+    scanned_count: usize,
 }
 
 impl GenshinArtifactScanner {
@@ -54,33 +149,42 @@ impl GenshinArtifactScanner {
 
 // constructor
 impl GenshinArtifactScanner {
-    fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-        use yas::ocr::yas_ocr_model;
-        let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(yas_ocr_model!(
-            "../../scanner/artifact_scanner/models/model_training.onnx",
-            "../../scanner/artifact_scanner/models/index_2_word.json"
-        )?);
-        Ok(model)
-    }
-
-    fn get_capturer() -> Result<Rc<dyn Capturer<RgbImage>>> {
-        Ok(Rc::new(GenericCapturer::new()?))
+    pub fn new(
+        window_info_repo: &WindowInfoRepository,
+        config: GenshinArtifactScannerConfig,
+        controller_config: GenshinRepositoryScannerLogicConfig,
+        game_info: GameInfo,
+    ) -> Result<Self> {
+        let capturer = controller_config.capturer("artifact")?;
+        let model = get_image_to_text(&config)?;
+        Self::with_components(window_info_repo, config, controller_config, game_info, capturer, model)
     }
 
-    pub fn new(
+    /// Like `new`, but panel captures and OCR go through the given capturer and model,
+    /// e.g. a scripted capturer in tests or another OCR backend.
+    /// The model reads the item count and is then handed to the recognition thread
+    pub fn with_components(
         window_info_repo: &WindowInfoRepository,
         config: GenshinArtifactScannerConfig,
         controller_config: GenshinRepositoryScannerLogicConfig,
         game_info: GameInfo,
+        capturer: Rc<dyn Capturer<RgbImage>>,
+        model: Box<dyn ImageToText<RgbImage> + Send>,
     ) -> Result<Self> {
+        let mut window_info = ArtifactScannerWindowInfo::from_window_info_repository(
+            game_info.window.to_rect_usize().size(),
+            game_info.ui,
+            game_info.platform,
+            window_info_repo,
+        )?;
+        window_info.override_grid(&controller_config);
+        if let Some(path) = config.window_info_override.as_ref() {
+            window_info.apply_override_file(path)?;
+            info!("已应用识别区域覆盖 {:?}", path);
+        }
         Ok(Self {
             scanner_config: config,
-            window_info: ArtifactScannerWindowInfo::from_window_info_repository(
-                game_info.window.to_rect_usize().size(),
-                game_info.ui,
-                game_info.platform,
-                window_info_repo,
-            )?,
+            window_info,
             controller: Rc::new(RefCell::new(GenshinRepositoryScanController::new(
                 window_info_repo,
                 controller_config,
@@ -88,9 +192,9 @@ impl GenshinArtifactScanner {
                 true,
             )?)),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
-            // item count will be set later, once the scan starts
-            capturer: Self::get_capturer()?,
+            image_to_text: Some(model),
+            capturer,
+            scanned_count: 0,
         })
     }
 
@@ -99,27 +203,14 @@ impl GenshinArtifactScanner {
         arg_matches: &clap::ArgMatches,
         game_info: GameInfo,
     ) -> Result<Self> {
-        let window_info = ArtifactScannerWindowInfo::from_window_info_repository(
-            game_info.window.to_rect_usize().size(),
-            game_info.ui,
-            game_info.platform,
+        let config = GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?;
+        config.validate()?;
+        Self::new(
             window_info_repo,
-        )?;
-        Ok(GenshinArtifactScanner {
-            scanner_config: GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?,
-            window_info,
-            controller: Rc::new(RefCell::new(
-                GenshinRepositoryScanController::from_arg_matches(
-                    window_info_repo,
-                    arg_matches,
-                    game_info.clone(),
-                    true,
-                )?,
-            )),
+            config,
+            GenshinRepositoryScannerLogicConfig::from_arg_matches(arg_matches)?,
             game_info,
-            image_to_text: Self::get_image_to_text()?,
-            capturer: Self::get_capturer()?,
-        })
+        )
     }
 }
 
@@ -131,74 +222,99 @@ impl GenshinArtifactScanner {
         )
     }
 
-    pub fn get_star(&self) -> Result<usize> {
+    /// Capture one panel and save it to `path` with the rects it would be read from drawn on it, see `ArtifactScannerWindowInfo::draw_regions`
+    pub fn preview_regions(&self, path: &std::path::Path) -> Result<()> {
+        let mut image = self.capture_panel()?;
+        self.window_info.draw_regions(&mut image);
+        image.save(path)?;
+        Ok(())
+    }
+
+    /// The star of the artifact in `panel_image`, which decides whether the star position is shifted by 祝圣之霜
+    pub fn get_star(&self, panel_image: &RgbImage) -> Result<usize> {
+        let star_pos = self.window_info.for_panel(panel_image).star_pos;
         let pos: Pos<i32> = Pos {
-            x: self.game_info.window.left + self.window_info.star_pos.x as i32,
-            y: self.game_info.window.top + self.window_info.star_pos.y as i32,
+            x: self.game_info.window.left + star_pos.x as i32,
+            y: self.game_info.window.top + star_pos.y as i32,
         };
         let color = self.capturer.capture_color(pos)?;
 
-        let match_colors = [
-            image::Rgb([113, 119, 139]),
-            image::Rgb([42, 143, 114]),
-            image::Rgb([81, 127, 203]),
-            image::Rgb([161, 86, 224]),
-            image::Rgb([188, 105, 50]),
-        ];
-
-        let mut min_dis: usize = 0xdeadbeef;
-        let mut ret: usize = 1;
-        for (i, match_color) in match_colors.iter().enumerate() {
-            let dis2 = color_distance(match_color, &color);
-            if dis2 < min_dis {
-                min_dis = dis2;
-                ret = i + 1;
-            }
+        let star = self.window_info.match_star_with_mode(&color, self.scanner_config.star_detect_mode);
+        if !star.confident {
+            warn!("星级颜色 {:?} 与所有参考颜色相差较大，按最接近的 {} 星处理", color, star.star);
         }
+        anyhow::Ok(star.star)
+    }
 
-        anyhow::Ok(ret)
+    /// The model for `scan`, loaded again if an earlier scan already took it
+    fn take_image_to_text(&mut self) -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
+        match self.image_to_text.take() {
+            Some(model) => Ok(model),
+            None => get_image_to_text(&self.scanner_config),
+        }
+    }
+
+    fn read_item_count(&self) -> Result<Option<i32>> {
+        let loaded;
+        let model = match self.image_to_text.as_ref() {
+            Some(model) => model,
+            None => {
+                loaded = get_image_to_text(&self.scanner_config)?;
+                &loaded
+            },
+        };
+        read_item_count(
+            self.capturer.as_ref(),
+            model.as_ref(),
+            self.window_info.item_count_rect.to_rect_i32(),
+            self.game_info.window.origin(),
+            self.scanner_config.language,
+        )
+    }
+
+    /// The item count shown in the inventory, read from the count rect without scrolling or scanning
+    pub fn count(&self) -> Result<i32> {
+        self.read_item_count()?.ok_or_else(|| anyhow!("无法识别物品数量"))
     }
 
     pub fn get_item_count(&self) -> Result<i32> {
         let count = self.scanner_config.number;
-        let item_name = "圣遗物";
 
         let max_count = Self::MAX_COUNT as i32;
         if count > 0 {
+            info!("指定扫描 {} 个物品，跳过物品数量识别", count);
             return Ok(max_count.min(count));
         }
 
-        let im = self.capturer.capture_relative_to(
-            self.window_info.item_count_rect.to_rect_i32(),
-            self.game_info.window.origin(),
-        )?;
-        // im.save("item_count.png")?;
-        let s = self.image_to_text.image_to_text(&im, false)?;
-
-        info!("物品信息: {}", s);
-
-        if s.starts_with(item_name) {
-            let chars = s.chars().collect::<Vec<char>>();
-            let count_str = chars[3..chars.len() - 5]
-                .iter()
-                .collect::<String>()
-                .trim()
-                .to_string();
-            Ok(match count_str.parse::<usize>() {
-                Ok(v) => (v as i32).min(max_count),
-                Err(_) => max_count,
-            })
-        } else {
-            Ok(max_count)
+        let count = self.read_item_count()?;
+        if count.is_none() {
+            warn!("使用最大物品数量 {}", max_count);
         }
+        Ok(count.map_or(max_count, |v| v.min(max_count)))
     }
 
     pub fn scan(&mut self) -> Result<Vec<GenshinArtifactScanResult>> {
+        self.scan_reporting(ScanEventSender::default())
+    }
+
+    /// Like `scan`, and reports its progress to `events`, see `ScanEvent`
+    pub fn scan_with_events(&mut self, events: Sender<ScanEvent>) -> Result<Vec<GenshinArtifactScanResult>> {
+        self.scan_reporting(ScanEventSender::new(Some(events)))
+    }
+
+    fn scan_reporting(&mut self, events: ScanEventSender) -> Result<Vec<GenshinArtifactScanResult>> {
         info!("开始扫描，使用鼠标右键中断扫描");
 
+        let initial_delay = self.scanner_config.initial_delay_ms;
+        if initial_delay > 0 {
+            info!("等待 {} ms 后开始扫描", initial_delay);
+            utils::sleep(initial_delay);
+        }
+
         let now = SystemTime::now();
         let (tx, rx) = mpsc::channel::<Option<SendItem>>();
         let count = self.get_item_count()?;
+        events.send(ScanEvent::Started { total: count.max(0) as usize });
 
         let lock_list = self
             .scanner_config
@@ -210,31 +326,56 @@ impl GenshinArtifactScanner {
             info!("已加载自动上锁列表");
         }
 
-        let (result_tx, result_rx) = if lock_list.is_some() {
+        // the main thread waits for each result to auto-lock, or to re-capture on failure
+        let (result_tx, result_rx) = if lock_list.is_some() || self.scanner_config.retry_count > 0 {
             let (t, r) = mpsc::channel::<Option<GenshinArtifactScanResult>>();
             (Some(t), Some(r))
         } else {
             (None, None)
         };
 
-        let worker =
-            ArtifactScannerWorker::new(self.window_info.clone(), self.scanner_config.clone())?;
+        let checkpoint = self
+            .scanner_config
+            .resume_from
+            .as_ref()
+            .map(|p| ScanCheckpoint::load(p))
+            .transpose()?;
+        // resume from a page start, the rest of that page is recognized again and deduplicated
+        let start_index = checkpoint
+            .as_ref()
+            .map_or(0, |c| c.resume_index(self.window_info.page_size().max(0) as usize).min(count.max(0) as usize));
+
+        let mut worker = ArtifactScannerWorker::with_model(
+            self.window_info.clone(),
+            self.scanner_config.clone(),
+            self.take_image_to_text()?,
+        ).with_events(events.clone());
+        if let Some(checkpoint) = checkpoint {
+            worker = worker.resume_from(checkpoint, start_index);
+        }
         let join_handle = worker.run(rx, result_tx);
         info!("Worker created");
 
-        self.send(&tx, count, result_rx.as_ref(), lock_list.as_ref());
+        let mut timings = ScanTimings::new(self.scanner_config.verbose);
+        let sent = self.send(&tx, count, start_index, result_rx.as_ref(), lock_list.as_ref(), &events, &mut timings);
+        timings.log("扫描耗时");
 
         match tx.send(None) {
             Ok(_) => info!("扫描结束，等待识别线程结束，请勿关闭程序"),
             Err(_) => info!("扫描结束，识别已完成"),
         }
 
-        match join_handle.join() {
+        // stop the worker before giving up on a failed capture, its partial result is not returned
+        let joined = join_handle.join();
+        self.scanned_count = sent?;
+
+        match joined {
             Ok(v) => {
                 info!("识别耗时: {:?}", now.elapsed()?);
 
                 let min_level = self.scanner_config.min_level;
-                let v = v.iter().filter(|a| a.level >= min_level).cloned().collect();
+                let max_level = self.scanner_config.max_level;
+                let v = v.iter().filter(|a| a.level >= min_level && a.level <= max_level).cloned().collect();
 
                 Ok(v)
             },
@@ -242,10 +383,15 @@ impl GenshinArtifactScanner {
         }
     }
 
+    /// Number of items recognized in the last `scan`, including duplicates and ones filtered out by level
+    pub fn scanned_count(&self) -> usize {
+        self.scanned_count
+    }
+
     /// Click the detail-panel lock button at artifact_lock_pos. Call only when list-view lock detection says not locked.
-    fn try_lock_artifact(&mut self) -> Result<()> {
+    /// `pos` is `artifact_lock_pos` of the panel's layout, see `ArtifactScannerWindowInfo::for_panel`
+    fn try_lock_artifact(&mut self, pos: Pos<f64>) -> Result<()> {
         let origin = self.game_info.window.origin();
-        let pos = &self.window_info.artifact_lock_pos;
         let cx = origin.x + pos.x as i32;
         let cy = origin.y + pos.y as i32;
 
@@ -253,41 +399,34 @@ impl GenshinArtifactScanner {
             .borrow_mut()
             .system_control_mut()
             .mouse_move_to(cx, cy)?;
-        utils::sleep(20);
+        utils::sleep(self.scanner_config.pre_click_delay_ms);
         self.controller.borrow_mut().system_control_mut().mouse_click()?;
-        utils::sleep(20);
+        utils::sleep(self.scanner_config.post_click_delay_ms);
         self.controller
             .borrow_mut()
             .move_to(0, 0);
-        utils::sleep(30);
+        utils::sleep(self.scanner_config.refocus_delay_ms);
         info!("已点击上锁");
         Ok(())
     }
 
     /// After locking, refocus by clicking the current list item so scroll/next-item works (focus was on lock button).
-    fn refocus_current_list_item(&mut self, artifact_index: i32) -> Result<()> {
-        let col = self.window_info.col;
-        if col <= 0 {
-            return Ok(());
-        }
-        let index0 = artifact_index - 1;
-        if index0 < 0 {
+    /// `artifact_index` starts from 1, `count` is the number of items being scanned
+    fn refocus_current_list_item(&mut self, artifact_index: i32, count: i32) -> Result<()> {
+        let (row, col) = (self.window_info.row, self.window_info.col);
+        if row <= 0 || col <= 0 || artifact_index < 1 {
             return Ok(());
         }
-        let row_idx = (index0 / col) as usize;
-        let col_idx = (index0 % col) as usize;
+        let (row_idx, col_idx) = list_cell(artifact_index - 1, count, row, col);
         self.controller
             .borrow_mut()
-            .move_to(0, 0);
-        utils::sleep(30);
+            .click_item(row_idx, col_idx)?;
+        utils::sleep(self.scanner_config.refocus_delay_ms);
         Ok(())
     }
 
     fn is_page_first_artifact(&self, cur_index: i32) -> bool {
-        let col = self.window_info.col;
-        let row = self.window_info.row;
-
-        let page_size = col * row;
+        let page_size = self.window_info.page_size();
         return cur_index % page_size == 0;
     }
 
@@ -295,71 +434,131 @@ impl GenshinArtifactScanner {
     /// max count: total count
     /// cur_index: current item index (starting from 0)
     fn get_start_row(&self, max_count: i32, cur_index: i32) -> i32 {
-        let col = self.window_info.col;
-        let row = self.window_info.row;
+        page_start_row(max_count, cur_index, self.window_info.row, self.window_info.col)
+    }
 
-        let page_size = col * row;
-        if max_count - cur_index >= page_size {
-            return 0;
-        } else {
-            let remain = max_count - cur_index;
-            let remain_row = (remain + col - 1) / col;
-            let scroll_row = remain_row.min(row);
-            return row - scroll_row;
+    /// The list grid from row `start_row` of the visible page down to the bottom of the window
+    fn capture_list_image(&self, start_row: i32) -> Result<RgbImage> {
+        let origin = self.game_info.window;
+        let margin = self.window_info.scan_margin_pos;
+        let gap = self.window_info.item_gap_size;
+        let size = self.window_info.item_size;
+        let skipped = (gap.height + size.height) * start_row as f64;
+
+        let left = (origin.left as f64 + margin.x) as i32;
+        let top = (origin.top as f64 + margin.y + skipped) as i32;
+        let width = (origin.width as f64 - margin.x) as i32;
+        let height = (origin.height as f64 - margin.y - skipped) as i32;
+
+        self.capturer.capture_rect(Rect { left, top, width, height })
+    }
+
+    /// Page through the list without opening any detail panel and read rarity, level and lock state
+    /// of every item from the list view, see `ListCellInfo`
+    pub fn scan_list(&mut self) -> Result<Vec<ListCellInfo>> {
+        info!("开始扫描列表，使用鼠标右键中断扫描");
+        let count = self.get_item_count()?.max(0);
+        let model = self.take_image_to_text()?;
+        let (row, col) = (self.window_info.row, self.window_info.col);
+        if row <= 0 || col <= 0 {
+            return Err(anyhow!("列表布局无效: {} 行 {} 列", row, col));
+        }
+
+        {
+            let mut controller = self.controller.borrow_mut();
+            controller.move_to(0, 0);
+            controller.system_control_mut().mouse_click()?;
         }
+        utils::sleep(1000);
+        self.controller.borrow_mut().sample_initial_color()?;
+
+        let mut cells = Vec::new();
+        let mut index = 0;
+        while index < count {
+            if utils::is_rmb_down() {
+                info!("用户中断");
+                break;
+            }
+
+            let start_row = self.get_start_row(count, index);
+            let list_image = self.capture_list_image(start_row)?;
+            let page_cells = ((row - start_row) * col).min(count - index) as usize;
+            let read = read_list_cells(
+                &list_image,
+                &self.window_info,
+                model.as_ref(),
+                index as usize,
+                page_cells,
+                self.scanner_config.partial_row_ratio,
+            );
+            if read.len() < page_cells {
+                warn!("第 {} 个物品起的列表行不完整，只读取到 {} 个", index + 1, read.len());
+            }
+            cells.extend(read);
+            index += page_cells as i32;
+            if index >= count {
+                break;
+            }
+
+            let remain_row = (count - index + col - 1) / col;
+            match self.controller.borrow_mut().scroll_rows(remain_row.min(row)) {
+                ScrollResult::TimeLimitExceeded => return Err(anyhow!("翻页超时，扫描终止……")),
+                ScrollResult::Interrupt => {
+                    info!("用户中断");
+                    break;
+                },
+                _ => (),
+            }
+            utils::sleep(100);
+        }
+
+        self.image_to_text = Some(model);
+        info!("列表扫描结束，共 {} 个物品", cells.len());
+        Ok(cells)
     }
 
     fn send(
         &mut self,
         tx: &Sender<Option<SendItem>>,
         count: i32,
+        start_index: usize,
         result_rx: Option<&Receiver<Option<GenshinArtifactScanResult>>>,
         lock_list: Option<&LockList>,
-    ) {
+        events: &ScanEventSender,
+        timings: &mut ScanTimings,
+    ) -> Result<usize> {
         let mut generator =
-            GenshinRepositoryScanController::get_generator(self.controller.clone(), count as usize);
-        let mut artifact_index: i32 = 0;
+            GenshinRepositoryScanController::get_generator_from(self.controller.clone(), count as usize, start_index);
+        let mut artifact_index = start_index as i32;
+        let mut sent_count: usize = 0;
 
         loop {
             let pinned_generator = Pin::new(&mut generator);
-            match pinned_generator.resume(()) {
+            match timings.time("switch item", || pinned_generator.resume(())) {
                 CoroutineState::Yielded(_) => {
-                    let image = self.capture_panel().unwrap();
-                    let star = self.get_star().unwrap();
+                    let mut image = timings.time("capture panel", || self.capture_panel())?;
+                    if is_empty_panel(&self.window_info, &image).unwrap_or(true) {
+                        // the panel may still be opening, look once more before giving up
+                        utils::sleep(100);
+                        image = timings.time("capture panel", || self.capture_panel())?;
+                        // a flat panel is a covered or minimized window, not the end of the items
+                        if is_empty_panel(&self.window_info, &image)? {
+                            info!("第 {} 个位置未显示物品，可能已到达背包末尾（物品数量识别有误），停止扫描", artifact_index + 1);
+                            break;
+                        }
+                    }
+                    let star = timings.time("star", || self.get_star(&image))?;
+                    let lock_pos = self.window_info.for_panel(&image).artifact_lock_pos;
 
                     let list_image = if self.is_page_first_artifact(artifact_index) {
-                        let origin = self.game_info.window;
-                        let margin = self.window_info.scan_margin_pos;
-                        let gap = self.window_info.item_gap_size;
-                        let size = self.window_info.item_size;
-
-                        let left = (origin.left as f64 + margin.x) as i32;
-                        let top = (origin.top as f64
-                            + margin.y
-                            + (gap.height + size.height)
-                                * self.get_start_row(count, artifact_index) as f64)
-                            as i32;
-                        let width = (origin.width as f64 - margin.x) as i32;
-                        let height = (origin.height as f64
-                            - margin.y
-                            - (gap.height + size.height)
-                                * self.get_start_row(count, artifact_index) as f64)
-                            as i32;
-
-                        let game_image = self
-                            .capturer
-                            .capture_rect(yas::positioning::Rect {
-                                left,
-                                top,
-                                width,
-                                height,
-                            })
-                            .unwrap();
-                        Some(game_image)
+                        let start_row = self.get_start_row(count, artifact_index);
+                        Some(timings.time("capture list", || self.capture_list_image(start_row))?)
                     } else {
                         None
                     };
 
+                    // the position in the whole bag, so that a resumed scan keeps the numbers of the first one
+                    let seq = artifact_index as usize;
                     artifact_index = artifact_index + 1;
 
                     if (star as i32) < self.scanner_config.min_star {
@@ -375,20 +574,70 @@ impl GenshinArtifactScanner {
                             panel_image: image,
                             star,
                             list_image,
+                            seq,
+                            retry: false,
                         }))
                         .is_err()
                     {
                         break;
                     }
-
-                    if let (Some(rx), Some(list)) = (result_rx, lock_list) {
-                        if let Ok(Some(res)) = rx.recv() {
-                            let matched = list.contains(&res);
-                            if matched && !res.lock {
-                                match self.try_lock_artifact() {
+                    sent_count += 1;
+
+                    if let Some(rx) = result_rx {
+                        let resend = || {
+                            // give the panel a moment, e.g. in case it was captured mid-animation
+                            utils::sleep(50);
+                            let image = match self.capture_panel() {
+                                Ok(image) => image,
+                                Err(_) => return false,
+                            };
+                            let star = match self.get_star(&image) {
+                                Ok(star) => star,
+                                Err(_) => return false,
+                            };
+                            tx.send(Some(SendItem {
+                                panel_image: image,
+                                star,
+                                list_image: None,
+                                seq,
+                                retry: true,
+                            })).is_ok()
+                        };
+                        let reply = timings.time("wait for recognition", || {
+                            recv_with_retry(rx, WORKER_RESULT_TIMEOUT, self.scanner_config.retry_count, resend)
+                        });
+                        let res = match reply {
+                            WorkerReply::Result(v) => v,
+                            WorkerReply::Finished => {
+                                info!("识别线程已结束，停止扫描");
+                                break;
+                            },
+                            WorkerReply::TimedOut => {
+                                // later results would no longer line up with the item on screen
+                                error!("等待识别结果超时，停止扫描");
+                                break;
+                            },
+                        };
+
+                        if let (Some(res), Some(list)) = (res, lock_list) {
+                            let matched = list.contains_normalized(&res);
+                            let skip_reason = if matched && !res.lock {
+                                lock_skip_reason(&res, self.scanner_config.min_confidence)
+                            } else {
+                                None
+                            };
+                            if let Some(reason) = skip_reason {
+                                warn!("自动上锁跳过（识别结果不可靠，{}）: 第 {} 个 {} {}", reason, artifact_index, res.name, res.main_stat_value);
+                            } else if matched && !res.lock {
+                                match self.try_lock_artifact(lock_pos) {
                                     Ok(()) => {
-                                        
-                                    }
+                                        events.send(ScanEvent::Locked { index: (artifact_index - 1) as usize });
+                                        if !self.scanner_config.no_refocus {
+                                            if let Err(e) = self.refocus_current_list_item(artifact_index, count) {
+                                                error!("上锁后重新选中物品失败: {}", e);
+                                            }
+                                        }
+                                    },
                                     Err(e) => error!("自动上锁点击失败: {}", e),
                                 }
                             } else if matched && res.lock {
@@ -414,5 +663,180 @@ impl GenshinArtifactScanner {
                 },
             }
         }
+
+        Ok(sent_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::fixtures;
+
+    /// Records the rects it is asked to capture
+    struct FakeCapturer {
+        rects: RefCell<Vec<Rect<i32>>>,
+    }
+
+    impl Capturer<RgbImage> for FakeCapturer {
+        fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+            self.rects.borrow_mut().push(rect);
+            Ok(RgbImage::new(rect.width as u32, rect.height as u32))
+        }
+    }
+
+    struct FakeOCR(&'static str);
+
+    impl ImageToText<RgbImage> for FakeOCR {
+        fn image_to_text(&self, _image: &RgbImage, _is_preprocessed: bool) -> Result<String> {
+            Ok(String::from(self.0))
+        }
+
+        fn get_average_inference_time(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_read_item_count_only_captures_count_rect() {
+        let capturer = FakeCapturer { rects: RefCell::new(Vec::new()) };
+        let rect = Rect::new(10, 20, 100, 30);
+        let origin = Pos { x: 5, y: 5 };
+
+        let count = read_item_count(&capturer, &FakeOCR("圣遗物 1500/2400"), rect, origin, Language::ZhCn).unwrap();
+        assert_eq!(count, Some(1500));
+        // a single capture of the count rect, nothing else happens on screen
+        assert_eq!(*capturer.rects.borrow(), vec![Rect::new(15, 25, 100, 30)]);
+
+        let count = read_item_count(&capturer, &FakeOCR("武器 20/2000"), rect, origin, Language::ZhCn).unwrap();
+        assert_eq!(count, None);
+    }
+
+    #[test]
+    fn test_list_cell() {
+        // 8 columns, 5 rows, 100 items: pages start at 0, 40 and 80
+        assert_eq!(list_cell(0, 100, 5, 8), (0, 0));
+        assert_eq!(list_cell(19, 100, 5, 8), (2, 3));
+        assert_eq!(list_cell(45, 100, 5, 8), (0, 5));
+        // the last 20 items fill the bottom 3 rows, the first of them is at row 2
+        assert_eq!(list_cell(80, 100, 5, 8), (2, 0));
+        assert_eq!(list_cell(99, 100, 5, 8), (4, 3));
+        // a single page that is not full is not scrolled
+        assert_eq!(list_cell(9, 10, 5, 8), (1, 1));
+    }
+
+    #[test]
+    fn test_parse_item_count() {
+        assert_eq!(parse_item_count("圣遗物1800/2400", Language::ZhCn), Some(1800));
+        assert_eq!(parse_item_count("Artifacts 1800/2400", Language::En), Some(1800));
+        assert_eq!(parse_item_count("Artifacts 1800/2400", Language::ZhCn), None);
+    }
+
+    #[test]
+    fn test_early_worker_break_does_not_block_auto_lock() {
+        let (tx, rx) = mpsc::channel::<Option<i32>>();
+        let (result_tx, result_rx) = mpsc::channel::<Option<GenshinArtifactScanResult>>();
+
+        // a worker that answers the first item, then stops like on reaching min level
+        let worker = std::thread::spawn(move || {
+            for item in rx.into_iter() {
+                let _ = result_tx.send(None);
+                if item == Some(1) {
+                    break;
+                }
+            }
+            drop(result_tx);
+        });
+
+        let mut replies = Vec::new();
+        for i in 1..=5 {
+            if tx.send(Some(i)).is_err() {
+                break;
+            }
+            match recv_worker_result(&result_rx, Duration::from_secs(5)) {
+                WorkerReply::Finished => break,
+                reply => replies.push(reply),
+            }
+        }
+
+        assert_eq!(replies.len(), 1);
+        assert!(matches!(replies[0], WorkerReply::Result(None)));
+        let _ = tx.send(None);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn test_failed_item_recaptured() {
+        let (tx, rx) = mpsc::channel::<bool>();
+        let (result_tx, result_rx) = mpsc::channel::<Option<GenshinArtifactScanResult>>();
+
+        // recognition fails until the item is captured again
+        let worker = std::thread::spawn(move || {
+            for retry in rx.into_iter() {
+                let result = if retry {
+                    Some(fixtures::scan_result())
+                } else {
+                    None
+                };
+                let _ = result_tx.send(result);
+            }
+        });
+
+        tx.send(false).unwrap();
+        let mut resent = 0;
+        let reply = recv_with_retry(&result_rx, Duration::from_secs(5), 2, || {
+            resent += 1;
+            tx.send(true).is_ok()
+        });
+        assert!(matches!(reply, WorkerReply::Result(Some(_))));
+        assert_eq!(resent, 1);
+
+        // without retries the failure is reported as is
+        tx.send(false).unwrap();
+        let reply = recv_with_retry(&result_rx, Duration::from_secs(5), 0, || unreachable!());
+        assert!(matches!(reply, WorkerReply::Result(None)));
+
+        drop(tx);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn test_empty_panel() {
+        let window_info = ArtifactScannerWindowInfo {
+            panel_rect: Rect::new(100.0, 50.0, 100.0, 200.0),
+            title_rect: Rect::new(110.0, 60.0, 80.0, 20.0),
+            ..Default::default()
+        };
+        // a minimized or covered window, not an empty slot
+        let mut panel = RgbImage::from_pixel(100, 200, image::Rgb([236, 229, 216]));
+        assert!(is_empty_panel(&window_info, &panel).is_err());
+
+        // nothing selected: no title, the rest of the panel is drawn
+        for y in 100..180 {
+            for x in 10..90 {
+                if x % 7 < 3 {
+                    panel.put_pixel(x, y, image::Rgb([74, 83, 102]));
+                }
+            }
+        }
+        assert!(is_empty_panel(&window_info, &panel).unwrap());
+
+        // a title on its banner
+        for y in 10..30 {
+            for x in 10..90 {
+                let color = if (14..26).contains(&y) && x % 5 < 2 { [255, 255, 255] } else { [188, 105, 50] };
+                panel.put_pixel(x, y, image::Rgb(color));
+            }
+        }
+        assert!(!is_empty_panel(&window_info, &panel).unwrap());
+    }
+
+    #[test]
+    fn test_stuck_worker_times_out() {
+        let (_result_tx, result_rx) = mpsc::channel::<Option<GenshinArtifactScanResult>>();
+        let reply = recv_worker_result(&result_rx, Duration::from_millis(10));
+        assert!(matches!(reply, WorkerReply::TimedOut));
     }
 }
@@ -1,3 +1,8 @@
+use strum::IntoEnumIterator;
+
+use crate::artifact::{ArtifactSetName, Language};
+use crate::scanner::artifact_scanner::{DedupMode, EmptyTitleBehavior, StarDetectMode};
+
 #[derive(Clone, clap::Args)]
 pub struct GenshinArtifactScannerConfig {
     /// Items with stars less than this will be ignored
@@ -8,19 +13,534 @@ pub struct GenshinArtifactScannerConfig {
     #[arg(id = "min-level", long = "min-level", help = "最小等级", value_name = "MIN_LEVEL", default_value_t = 0)]
     pub min_level: i32,
 
+    /// Items with level greater than this will be left out of the results, scanning continues past them
+    #[arg(id = "max-level", long = "max-level", help = "最大等级（高于该等级的物品不计入结果，但不会终止扫描）", value_name = "MAX_LEVEL", default_value_t = 20)]
+    pub max_level: i32,
+
     /// Ignore duplicated items
     #[arg(id = "ignore-dup", long = "ignore-dup", help = "忽略重复物品")]
     pub ignore_dup: bool,
 
+    /// With `ignore_dup`, still stop after this many duplicates in a row, in case page turning is broken. 0 to never stop
+    #[arg(id = "ignore-dup-max-run", long = "ignore-dup-max-run", help = "开启忽略重复物品时，连续重复物品达到该数量仍停止扫描（防止翻页错误导致扫描无法结束，0 为不限制）", value_name = "IGNORE_DUP_MAX_RUN", default_value_t = 100)]
+    pub ignore_dup_max_run: i32,
+
     /// it will output very verbose messages
     #[arg(id = "verbose", long, help = "显示详细信息")]
     pub verbose: bool,
 
-    /// the exact amount to scan
-    #[arg(id = "number", long, help = "指定圣遗物数量", value_name = "NUMBER", default_value_t = -1)]
+    /// the exact amount to scan. The item count is not read then, and duplicates do not stop the scan
+    #[arg(id = "number", long, help = "指定圣遗物数量，扫描恰好该数量后停止（不识别物品总数，重复物品不会中断扫描）", value_name = "NUMBER", default_value_t = -1)]
     pub number: i32,
 
+    /// Time to wait once before the first capture, so the inventory UI can finish opening
+    #[arg(id = "initial-delay", long = "initial-delay", help = "开始扫描前的等待时间（ms）（背包界面未完全打开可以考虑加大该选项）", value_name = "INITIAL_DELAY", default_value_t = 0)]
+    pub initial_delay_ms: u32,
+
+    /// A partially visible list row is read for locks only if at least this fraction of it is on screen
+    #[arg(id = "partial-row-ratio", long = "partial-row-ratio", help = "列表最后一行部分可见时，可见比例不低于该值（0~1）才识别其锁定状态", value_name = "PARTIAL_ROW_RATIO", default_value_t = 0.75)]
+    pub partial_row_ratio: f64,
+
+    /// How duplicated items are detected
+    #[arg(id = "dedup", long = "dedup", value_enum, default_value_t = DedupMode::Exact, help = "重复物品判断方式：exact 识别文字完全一致，fuzzy 解析后的圣遗物一致（词条数值允许 --dedup-epsilon 的误差），可避免识别误差产生的重复")]
+    pub dedup_mode: DedupMode,
+
+    /// Tolerance on stat values when deciding two scanned artifacts are the same one with `--dedup fuzzy`, see `ArtifactIdentity`
+    #[arg(id = "dedup-epsilon", long = "dedup-epsilon", help = "--dedup fuzzy 判断重复物品时词条数值的容差（过大会把不同的圣遗物当作重复）", value_name = "DEDUP_EPSILON", default_value_t = 0.0005)]
+    pub dedup_epsilon: f64,
+
+    /// What to do when the title of an artifact is recognized as empty
+    #[arg(id = "empty-title", long = "empty-title", value_enum, default_value_t = EmptyTitleBehavior::Retry, help = "圣遗物名称识别为空时的处理方式：skip 跳过，retry 重新识别一次后仍为空则跳过，record 照常记录")]
+    pub empty_title: EmptyTitleBehavior,
+
+    /// Save the results so far after this many new artifacts, 0 to disable
+    #[arg(id = "auto-save-every", long = "auto-save-every", help = "每识别多少个圣遗物自动保存一次中间结果（0 为关闭）", value_name = "AUTO_SAVE_EVERY", default_value_t = 100)]
+    pub auto_save_every: usize,
+
+    /// Save the results so far when this many seconds passed since the last save, 0 to disable
+    #[arg(id = "auto-save-interval", long = "auto-save-interval", help = "每隔多少秒自动保存一次中间结果（0 为关闭）", value_name = "AUTO_SAVE_INTERVAL", default_value_t = 60)]
+    pub auto_save_interval_secs: u64,
+
+    /// Where the intermediate results are saved, removed after the scan finishes. Defaults to the temp dir
+    #[arg(id = "auto-save-path", long = "auto-save-path", help = "中间结果的保存路径（扫描正常结束后删除），默认为系统临时目录", value_name = "AUTO_SAVE_PATH")]
+    pub auto_save_path: Option<std::path::PathBuf>,
+
+    /// Directory with `model_training.onnx` and `index_2_word.json` to use instead of the embedded OCR model
+    #[arg(id = "model-dir", long = "model-dir", help = "OCR 模型目录（包含 model_training.onnx 与 index_2_word.json），默认使用内置模型", value_name = "MODEL_DIR")]
+    pub model_dir: Option<std::path::PathBuf>,
+
+    /// Warn about artifacts with any field recognized below this confidence (0~1), so they can be checked by hand
+    #[arg(id = "min-confidence", long = "min-confidence", help = "任一词条识别置信度低于该值（0~1）时输出警告，便于人工核对", value_name = "MIN_CONFIDENCE", default_value_t = 0.9)]
+    pub min_confidence: f32,
+
+    /// Binarize the pending (待激活) substat line with a local threshold, helps when the panel background is unevenly lit
+    #[arg(id = "adaptive-binarize", long = "adaptive-binarize", help = "待激活词条使用局部自适应二值化（背景明暗不均导致识别错误时可尝试）")]
+    pub adaptive_binarize: bool,
+
+    /// Fail an artifact whose level is recognized as empty, instead of recording it as level 0
+    #[arg(id = "strict-level", long = "strict-level", help = "等级识别为空时视为识别错误（默认记为 0 级并输出警告）")]
+    pub strict_level: bool,
+
+    /// Capture the panel again up to this many times when its recognition fails, 0 to disable.
+    /// Waits for each result before moving on, so the scan is slower when enabled
+    #[arg(id = "retry-count", long = "retry-count", help = "识别失败时重新截图识别的次数（0 为关闭，开启后扫描会变慢）", value_name = "RETRY_COUNT", default_value_t = 0)]
+    pub retry_count: u32,
+
+    /// Write recognized stat lines that cannot be parsed to this file, labeled with their rect
+    #[arg(id = "unparsed-output", long = "unparsed-output", help = "将识别出但无法解析的词条文本（附区域名）保存至该文件，如 unparsed.txt", value_name = "UNPARSED_OUTPUT")]
+    pub unparsed_output: Option<std::path::PathBuf>,
+
+    /// Save the panel image, recognized text and error of every item that cannot be recognized or parsed into this directory
+    #[arg(id = "rejects-dir", long = "rejects-dir", help = "将识别或解析失败的物品（面板截图、识别文本与错误原因）保存到该目录，便于人工检查", value_name = "DIR")]
+    pub rejects_dir: Option<std::path::PathBuf>,
+
+    /// Scan as usual but only report counts, nothing is exported
+    #[arg(id = "dry-run", long = "dry-run", help = "仅扫描并输出统计信息（识别数量、解析失败数、星级分布），不导出任何文件")]
+    pub dry_run: bool,
+
     /// JSON path of artifacts to auto-lock (array of { name, main_stat_name, main_stat_value, sub_stat: [4] }, mona_extended compatible)
     #[arg(long = "lock", help = "自动上锁：读入 JSON 列表，识别到与列表中完全一致的圣遗物时点击上锁", value_name = "LOCK_JSON")]
     pub lock_list_path: Option<std::path::PathBuf>,
+
+    /// Language of the game client
+    #[arg(id = "language", long = "language", value_enum, default_value_t = Language::ZhCn, help = "游戏语言（暂只支持 zh-cn：en 的圣遗物名称、词条尚不能转换为导出结果）")]
+    pub language: Language,
+
+    /// Append each recognized artifact as a JSON line to this file as soon as it is recognized
+    #[arg(id = "stream-output", long = "stream-output", help = "识别到每个圣遗物后立即以 JSON Lines 格式追加写入该文件（扫描中断也不会丢失已识别结果）", value_name = "STREAM_OUTPUT")]
+    pub stream_output_path: Option<std::path::PathBuf>,
+
+    /// Per-item log lines (verbose results, duplicates) are written at most once per this many milliseconds,
+    /// errors are never held back. 0 to log every line
+    #[arg(id = "log-interval", long = "log-interval", help = "逐个物品的日志（详细信息、重复物品）的最小间隔（ms），错误日志不受限制，0 为不限制", value_name = "LOG_INTERVAL", default_value_t = 500)]
+    pub log_interval_ms: u64,
+
+    /// Only keep artifacts of these sets, e.g. `GladiatorFinale,WandererTroupe`. Empty keeps all
+    #[arg(id = "only-sets", long = "only-sets", help = "只保留这些套装的圣遗物，以逗号分隔的套装英文名（如 GladiatorFinale,WandererTroupe）", value_name = "SETS", value_delimiter = ',', value_parser = parse_set_key)]
+    pub set_filter: Vec<ArtifactSetName>,
+
+    /// After auto-locking, do not click the current item again to give the list its focus back
+    #[arg(id = "no-refocus", long = "no-refocus", help = "自动上锁后不重新点击当前物品（默认会点击以恢复列表焦点，保证之后翻页正常）")]
+    pub no_refocus: bool,
+
+    /// Wait between moving the mouse onto the lock button and clicking it when auto-locking
+    #[arg(id = "pre-click-delay", long = "pre-click-delay", help = "自动上锁时移动鼠标到锁定按钮后、点击前的等待时间（ms），机器较慢时可加大", value_name = "MS", default_value_t = 20)]
+    pub pre_click_delay_ms: u32,
+
+    /// Wait after clicking the lock button
+    #[arg(id = "post-click-delay", long = "post-click-delay", help = "自动上锁点击后的等待时间（ms），机器较慢时可加大", value_name = "MS", default_value_t = 20)]
+    pub post_click_delay_ms: u32,
+
+    /// Wait after moving the mouse away from the lock button, and after clicking the current item to give the list its focus back
+    #[arg(id = "refocus-delay", long = "refocus-delay", help = "自动上锁后移开鼠标及重新点击当前物品后的等待时间（ms），机器较慢时可加大", value_name = "MS", default_value_t = 30)]
+    pub refocus_delay_ms: u32,
+
+    /// Write a checkpoint (progress and results so far) to this file, see `ScanCheckpoint` for the schema
+    #[arg(id = "checkpoint", long = "checkpoint", help = "定期将扫描进度与已识别结果保存到该文件，中断后可用 --resume 继续", value_name = "CHECKPOINT")]
+    pub checkpoint_path: Option<std::path::PathBuf>,
+
+    /// Write the checkpoint after every this many items
+    #[arg(id = "checkpoint-every", long = "checkpoint-every", help = "每扫描多少个物品保存一次进度", value_name = "CHECKPOINT_EVERY", default_value_t = 50)]
+    pub checkpoint_every: usize,
+
+    /// Continue the scan saved in this checkpoint file
+    #[arg(id = "resume", long = "resume", help = "从该进度文件继续扫描（跳过已扫描的页）", value_name = "RESUME")]
+    pub resume_from: Option<std::path::PathBuf>,
+
+    /// Give up on an item whose OCR inference takes longer than this many milliseconds, 0 to wait forever
+    #[arg(id = "ocr-timeout", long = "ocr-timeout", help = "单次 OCR 推理超时（ms），超时的物品记为识别失败，0 为不限制", value_name = "OCR_TIMEOUT", default_value_t = 10000)]
+    pub ocr_timeout_ms: u64,
+
+    /// JSON replacing some rects of the window info, e.g. a misaligned sub stat,
+    /// see `ArtifactScannerWindowInfo::apply_override` for the format
+    #[arg(id = "window-info-override", long = "window-info-override", help = "用 JSON 文件覆盖部分识别区域（如 {\"sub_stat_4\": {\"left\": 1120, \"top\": 395, \"width\": 300, \"height\": 30}}，单位为当前窗口像素）", value_name = "WINDOW_INFO_OVERRIDE")]
+    pub window_info_override: Option<std::path::PathBuf>,
+
+    /// Capture the current panel with the rects to be recognized drawn on it, save it to `region_preview.png` and exit
+    #[arg(id = "preview-regions", long = "preview-regions", help = "截取当前圣遗物面板并标出各识别区域，保存为 region_preview.png 后退出，用于扫描前检查区域是否对齐")]
+    pub preview_regions: bool,
+
+    /// Only read rarity, level and lock state from the list view without opening any detail panel,
+    /// and save them to `artifact_list.json`, to pick out the items worth a full scan
+    #[arg(id = "list-only", long = "list-only", help = "只从背包列表读取星级、等级与锁定状态，不打开详情面板，结果保存为 artifact_list.json，用于快速筛选需要完整扫描的圣遗物")]
+    pub list_only: bool,
+
+    /// How the star is read from the sampled star color
+    #[arg(id = "star-detect", long = "star-detect", value_enum, default_value_t = StarDetectMode::Nearest, help = "星级判断方式：nearest 按最接近的参考颜色，hue 按色相（画面整体偏亮或偏暗，如 HDR、伽马调整时使用）")]
+    pub star_detect_mode: StarDetectMode,
+}
+
+/// A set by its canonical key, the variant name (e.g. `GladiatorFinale`) or the GOOD key
+fn parse_set_key(key: &str) -> Result<ArtifactSetName, String> {
+    let key = key.trim();
+    ArtifactSetName::iter()
+        .find(|set| set.to_string() == key || set.to_good() == key)
+        .ok_or_else(|| format!("未知的套装: {}", key))
+}
+
+impl GenshinArtifactScannerConfig {
+    pub fn builder() -> GenshinArtifactScannerConfigBuilder {
+        GenshinArtifactScannerConfigBuilder::new()
+    }
+
+    /// Checks the ranges clap does not, call it on every config read from the command line, `build` does already
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(1..=5).contains(&self.min_star) {
+            anyhow::bail!("min_star must be within 1..=5, got {}", self.min_star);
+        }
+        if !(0..=20).contains(&self.min_level) {
+            anyhow::bail!("min_level must be within 0..=20, got {}", self.min_level);
+        }
+        if !(0..=20).contains(&self.max_level) {
+            anyhow::bail!("max_level must be within 0..=20, got {}", self.max_level);
+        }
+        if self.max_level < self.min_level {
+            anyhow::bail!("max_level ({}) must not be less than min_level ({})", self.max_level, self.min_level);
+        }
+        if self.number < -1 || self.number == 0 {
+            anyhow::bail!("number must be positive, or -1 to scan all items, got {}", self.number);
+        }
+        if self.ignore_dup_max_run < 0 {
+            anyhow::bail!("ignore_dup_max_run must not be negative, got {}", self.ignore_dup_max_run);
+        }
+        if !(0.0..=1.0).contains(&self.partial_row_ratio) {
+            anyhow::bail!("partial_row_ratio must be within 0..=1, got {}", self.partial_row_ratio);
+        }
+        if !(0.0..0.01).contains(&self.dedup_epsilon) {
+            anyhow::bail!("dedup_epsilon must be within 0..0.01, got {}", self.dedup_epsilon);
+        }
+        if !(0.0..=1.0).contains(&self.min_confidence) {
+            anyhow::bail!("min_confidence must be within 0..=1, got {}", self.min_confidence);
+        }
+        // the scan result is converted, exported and matched against lock lists as Simplified Chinese
+        // only, an English scan would read fine and then fail for every artifact
+        if self.language != Language::ZhCn {
+            anyhow::bail!("language {:?} is not supported yet, only zh-cn scan results can be converted to artifacts", self.language);
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a `GenshinArtifactScannerConfig` without going through clap, with the same defaults as the command line
+pub struct GenshinArtifactScannerConfigBuilder {
+    config: GenshinArtifactScannerConfig,
+}
+
+impl Default for GenshinArtifactScannerConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GenshinArtifactScannerConfigBuilder {
+    pub fn new() -> Self {
+        GenshinArtifactScannerConfigBuilder {
+            config: GenshinArtifactScannerConfig {
+                min_star: 4,
+                min_level: 0,
+                max_level: 20,
+                ignore_dup: false,
+                ignore_dup_max_run: 100,
+                verbose: false,
+                number: -1,
+                initial_delay_ms: 0,
+                partial_row_ratio: 0.75,
+                dedup_mode: DedupMode::Exact,
+                dedup_epsilon: 0.0005,
+                empty_title: EmptyTitleBehavior::Retry,
+                auto_save_every: 100,
+                auto_save_interval_secs: 60,
+                auto_save_path: None,
+                model_dir: None,
+                min_confidence: 0.9,
+                adaptive_binarize: false,
+                strict_level: false,
+                retry_count: 0,
+                unparsed_output: None,
+                rejects_dir: None,
+                dry_run: false,
+                lock_list_path: None,
+                language: Language::ZhCn,
+                stream_output_path: None,
+                log_interval_ms: 500,
+                set_filter: Vec::new(),
+                no_refocus: false,
+                pre_click_delay_ms: 20,
+                post_click_delay_ms: 20,
+                refocus_delay_ms: 30,
+                checkpoint_path: None,
+                checkpoint_every: 50,
+                resume_from: None,
+                ocr_timeout_ms: 10000,
+                window_info_override: None,
+                preview_regions: false,
+                list_only: false,
+                star_detect_mode: StarDetectMode::Nearest,
+            },
+        }
+    }
+
+    pub fn min_star(&mut self, min_star: i32) -> &mut Self {
+        self.config.min_star = min_star;
+        self
+    }
+
+    pub fn min_level(&mut self, min_level: i32) -> &mut Self {
+        self.config.min_level = min_level;
+        self
+    }
+
+    pub fn max_level(&mut self, max_level: i32) -> &mut Self {
+        self.config.max_level = max_level;
+        self
+    }
+
+    pub fn ignore_dup(&mut self, ignore_dup: bool) -> &mut Self {
+        self.config.ignore_dup = ignore_dup;
+        self
+    }
+
+    pub fn ignore_dup_max_run(&mut self, ignore_dup_max_run: i32) -> &mut Self {
+        self.config.ignore_dup_max_run = ignore_dup_max_run;
+        self
+    }
+
+    pub fn verbose(&mut self, verbose: bool) -> &mut Self {
+        self.config.verbose = verbose;
+        self
+    }
+
+    /// -1 scans every item in the inventory
+    pub fn number(&mut self, number: i32) -> &mut Self {
+        self.config.number = number;
+        self
+    }
+
+    pub fn initial_delay_ms(&mut self, initial_delay_ms: u32) -> &mut Self {
+        self.config.initial_delay_ms = initial_delay_ms;
+        self
+    }
+
+    pub fn partial_row_ratio(&mut self, partial_row_ratio: f64) -> &mut Self {
+        self.config.partial_row_ratio = partial_row_ratio;
+        self
+    }
+
+    pub fn dedup_mode(&mut self, dedup_mode: DedupMode) -> &mut Self {
+        self.config.dedup_mode = dedup_mode;
+        self
+    }
+
+    pub fn dedup_epsilon(&mut self, dedup_epsilon: f64) -> &mut Self {
+        self.config.dedup_epsilon = dedup_epsilon;
+        self
+    }
+
+    pub fn empty_title(&mut self, empty_title: EmptyTitleBehavior) -> &mut Self {
+        self.config.empty_title = empty_title;
+        self
+    }
+
+    /// `every` results or `interval_secs` seconds, whichever comes first, 0 disables either trigger
+    pub fn auto_save(&mut self, every: usize, interval_secs: u64, path: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.auto_save_every = every;
+        self.config.auto_save_interval_secs = interval_secs;
+        self.config.auto_save_path = path;
+        self
+    }
+
+    pub fn model_dir(&mut self, model_dir: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.model_dir = model_dir;
+        self
+    }
+
+    pub fn min_confidence(&mut self, min_confidence: f32) -> &mut Self {
+        self.config.min_confidence = min_confidence;
+        self
+    }
+
+    pub fn adaptive_binarize(&mut self, adaptive_binarize: bool) -> &mut Self {
+        self.config.adaptive_binarize = adaptive_binarize;
+        self
+    }
+
+    pub fn strict_level(&mut self, strict_level: bool) -> &mut Self {
+        self.config.strict_level = strict_level;
+        self
+    }
+
+    pub fn retry_count(&mut self, retry_count: u32) -> &mut Self {
+        self.config.retry_count = retry_count;
+        self
+    }
+
+    pub fn unparsed_output(&mut self, path: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.unparsed_output = path;
+        self
+    }
+
+    pub fn rejects_dir(&mut self, dir: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.rejects_dir = dir;
+        self
+    }
+
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.config.dry_run = dry_run;
+        self
+    }
+
+    pub fn lock_list_path(&mut self, path: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.lock_list_path = path;
+        self
+    }
+
+    pub fn language(&mut self, language: Language) -> &mut Self {
+        self.config.language = language;
+        self
+    }
+
+    pub fn stream_output_path(&mut self, path: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.stream_output_path = path;
+        self
+    }
+
+    pub fn log_interval_ms(&mut self, log_interval_ms: u64) -> &mut Self {
+        self.config.log_interval_ms = log_interval_ms;
+        self
+    }
+
+    /// Empty keeps artifacts of every set
+    pub fn set_filter(&mut self, set_filter: Vec<ArtifactSetName>) -> &mut Self {
+        self.config.set_filter = set_filter;
+        self
+    }
+
+    pub fn no_refocus(&mut self, no_refocus: bool) -> &mut Self {
+        self.config.no_refocus = no_refocus;
+        self
+    }
+
+    pub fn pre_click_delay_ms(&mut self, pre_click_delay_ms: u32) -> &mut Self {
+        self.config.pre_click_delay_ms = pre_click_delay_ms;
+        self
+    }
+
+    pub fn post_click_delay_ms(&mut self, post_click_delay_ms: u32) -> &mut Self {
+        self.config.post_click_delay_ms = post_click_delay_ms;
+        self
+    }
+
+    pub fn refocus_delay_ms(&mut self, refocus_delay_ms: u32) -> &mut Self {
+        self.config.refocus_delay_ms = refocus_delay_ms;
+        self
+    }
+
+    pub fn checkpoint_path(&mut self, path: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.checkpoint_path = path;
+        self
+    }
+
+    pub fn checkpoint_every(&mut self, checkpoint_every: usize) -> &mut Self {
+        self.config.checkpoint_every = checkpoint_every;
+        self
+    }
+
+    pub fn resume_from(&mut self, path: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.resume_from = path;
+        self
+    }
+
+    pub fn ocr_timeout_ms(&mut self, ocr_timeout_ms: u64) -> &mut Self {
+        self.config.ocr_timeout_ms = ocr_timeout_ms;
+        self
+    }
+
+    pub fn window_info_override(&mut self, path: Option<std::path::PathBuf>) -> &mut Self {
+        self.config.window_info_override = path;
+        self
+    }
+
+    pub fn preview_regions(&mut self, preview_regions: bool) -> &mut Self {
+        self.config.preview_regions = preview_regions;
+        self
+    }
+
+    pub fn list_only(&mut self, list_only: bool) -> &mut Self {
+        self.config.list_only = list_only;
+        self
+    }
+
+    pub fn star_detect_mode(&mut self, star_detect_mode: StarDetectMode) -> &mut Self {
+        self.config.star_detect_mode = star_detect_mode;
+        self
+    }
+
+    pub fn build(&self) -> anyhow::Result<GenshinArtifactScannerConfig> {
+        self.config.validate()?;
+        Ok(self.config.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_valid_config() {
+        let config = GenshinArtifactScannerConfig::builder()
+            .min_star(5)
+            .min_level(16)
+            .number(100)
+            .partial_row_ratio(0.5)
+            .build()
+            .unwrap();
+
+        assert_eq!(config.min_star, 5);
+        assert_eq!(config.min_level, 16);
+        assert_eq!(config.number, 100);
+        assert!(!config.ignore_dup);
+        assert!(config.lock_list_path.is_none());
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_config() {
+        assert!(GenshinArtifactScannerConfig::builder().min_level(21).build().is_err());
+        assert!(GenshinArtifactScannerConfig::builder().min_level(8).max_level(4).build().is_err());
+        assert!(GenshinArtifactScannerConfig::builder().number(-5).build().is_err());
+        assert!(GenshinArtifactScannerConfig::builder().partial_row_ratio(1.5).build().is_err());
+    }
+
+    #[test]
+    fn test_command_line_config_validated() {
+        use clap::{Args, FromArgMatches};
+
+        let cmd = GenshinArtifactScannerConfig::augment_args(clap::Command::new("yas"));
+        let config = |args: &[&str]| {
+            let matches = cmd.clone().try_get_matches_from([&["yas"][..], args].concat()).unwrap();
+            GenshinArtifactScannerConfig::from_arg_matches(&matches).unwrap()
+        };
+        assert!(config(&[]).validate().is_ok());
+        assert!(config(&["--min-level", "21"]).validate().is_err());
+        assert!(config(&["--min-level", "8", "--max-level", "4"]).validate().is_err());
+        assert!(config(&["--number", "0"]).validate().is_err());
+        assert!(config(&["--language", "en"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_only_sets_from_command_line() {
+        use clap::{Args, FromArgMatches};
+
+        let cmd = GenshinArtifactScannerConfig::augment_args(clap::Command::new("yas"));
+        let matches = cmd.clone()
+            .try_get_matches_from(["yas", "--only-sets", "GladiatorFinale, WanderersTroupe"])
+            .unwrap();
+        let config = GenshinArtifactScannerConfig::from_arg_matches(&matches).unwrap();
+        assert_eq!(config.set_filter, vec![ArtifactSetName::GladiatorFinale, ArtifactSetName::WandererTroupe]);
+
+        let matches = cmd.clone().try_get_matches_from(["yas"]).unwrap();
+        assert!(GenshinArtifactScannerConfig::from_arg_matches(&matches).unwrap().set_filter.is_empty());
+
+        assert!(cmd.try_get_matches_from(["yas", "--only-sets", "Gladiator"]).is_err());
+    }
 }
@@ -1,6 +1,14 @@
+use anyhow::{anyhow, Context, Result};
+use image::{Rgb, RgbImage};
+use yas::draw_capture_region::draw_capture_region::draw_rect;
 use yas::positioning::{Pos, Rect, Size};
+use yas::utils::color::color_distance_sqrt;
+use yas::utils::ListLockGrid;
 
-#[derive(Clone, yas_derive::YasWindowInfo, Debug)]
+use crate::scanner::artifact_scanner::star_detect::{hue_distance, hue_saturation, StarDetectMode};
+use crate::scanner_controller::repository_layout::GenshinRepositoryScannerLogicConfig;
+
+#[derive(Clone, yas_derive::YasWindowInfo, Debug, Default)]
 pub struct ArtifactScannerWindowInfo {
     /// the position of artifact title relative to window
     #[window_info(rename = "genshin_artifact_title_rect")]
@@ -28,15 +36,21 @@ pub struct ArtifactScannerWindowInfo {
     #[window_info(rename = "genshin_artifact_level_rect")]
     pub level_rect: Rect<f64>,
 
+    /// Optional in window info, missing disables the detection.
     /// When present (height > 0), detect purple 祝圣之霜 block in this rect (window-relative).
     /// If detected, level and sub-stat rects are shifted down by `blessed_frost_offset_y`.
-    #[window_info(rename = "genshin_artifact_blessed_frost_detect_rect")]
+    #[window_info(rename = "genshin_artifact_blessed_frost_detect_rect", default = "yas::positioning::Rect::default()")]
     pub blessed_frost_detect_rect: Rect<f64>,
 
     /// Y offset (pixels) to apply to level and sub-stat crop regions when 祝圣之霜 is detected.
-    #[window_info(rename = "genshin_artifact_blessed_frost_offset_y")]
+    #[window_info(rename = "genshin_artifact_blessed_frost_offset_y", default = "0.0")]
     pub blessed_frost_offset_y: f64,
 
+    /// the set name line above the set bonus description relative to window, used to confirm an uncertain title.
+    /// Optional in window info, missing disables the check
+    #[window_info(rename = "genshin_artifact_set_description_rect", default = "yas::positioning::Rect::default()")]
+    pub set_description_rect: Rect<f64>,
+
     /// equip status of the artifact relative to window
     #[window_info(rename = "genshin_artifact_item_equip_rect")]
     pub item_equip_rect: Rect<f64>,
@@ -49,6 +63,31 @@ pub struct ArtifactScannerWindowInfo {
     #[window_info(rename = "genshin_artifact_star_pos")]
     pub star_pos: Pos<f64>,
 
+    /// colors sampled at star_pos for 1 to 5 star items, optional in window info
+    #[window_info(rename = "genshin_artifact_star_color_1", default = "image::Rgb([113, 119, 139])")]
+    pub star_color_1: Rgb<u8>,
+    #[window_info(rename = "genshin_artifact_star_color_2", default = "image::Rgb([42, 143, 114])")]
+    pub star_color_2: Rgb<u8>,
+    #[window_info(rename = "genshin_artifact_star_color_3", default = "image::Rgb([81, 127, 203])")]
+    pub star_color_3: Rgb<u8>,
+    #[window_info(rename = "genshin_artifact_star_color_4", default = "image::Rgb([161, 86, 224])")]
+    pub star_color_4: Rgb<u8>,
+    #[window_info(rename = "genshin_artifact_star_color_5", default = "image::Rgb([188, 105, 50])")]
+    pub star_color_5: Rgb<u8>,
+
+    /// how far (euclidean RGB distance) a sampled color may be from each star's reference color
+    /// and still be trusted, optional in window info. Tighten one tier if it is often confused with its neighbour
+    #[window_info(rename = "genshin_artifact_star_threshold_1", default = "60.0")]
+    pub star_threshold_1: f64,
+    #[window_info(rename = "genshin_artifact_star_threshold_2", default = "60.0")]
+    pub star_threshold_2: f64,
+    #[window_info(rename = "genshin_artifact_star_threshold_3", default = "60.0")]
+    pub star_threshold_3: f64,
+    #[window_info(rename = "genshin_artifact_star_threshold_4", default = "60.0")]
+    pub star_threshold_4: f64,
+    #[window_info(rename = "genshin_artifact_star_threshold_5", default = "60.0")]
+    pub star_threshold_5: f64,
+
     /// the whole panel of the artifact, relative to window
     #[window_info(rename = "genshin_repository_panel_rect")]
     pub panel_rect: Rect<f64>,
@@ -73,7 +112,511 @@ pub struct ArtifactScannerWindowInfo {
     #[window_info(rename = "genshin_repository_lock_pos")]
     pub lock_pos: Pos<f64>,
 
+    /// color of the lock icon in the list view, optional in window info.
+    /// UI brightness and the 祝圣之霜 tint shift it, `yas genshin calibrate-lock` samples the current one
+    #[window_info(rename = "genshin_repository_lock_color", default = "image::Rgb([255, 138, 117])")]
+    pub list_lock_color: Rgb<u8>,
+
+    /// a list cell is locked if a pixel is within this squared RGB distance (see `yas::utils::color_distance`)
+    /// of `list_lock_color`, optional in window info
+    #[window_info(rename = "genshin_repository_lock_threshold", default = "30.0")]
+    pub list_lock_threshold: f64,
+
     /// Lock button position in the artifact detail panel (window-relative). Click center to toggle lock.
     #[window_info(rename = "genshin_artifact_lock_pos")]
     pub artifact_lock_pos: Pos<f64>,
 }
+
+/// How far (degrees) a color's hue may be from the nearest star reference with `StarDetectMode::Hue`
+const STAR_HUE_TOLERANCE: f64 = 30.0;
+
+/// The star read from the color at star_pos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StarMatch {
+    pub star: usize,
+    /// false if the color is not within the threshold of any star's reference color
+    pub confident: bool,
+}
+
+impl ArtifactScannerWindowInfo {
+    /// The rects a `--window-info-override` file may replace, by field name
+    pub const OVERRIDABLE_RECTS: [&'static str; 13] = [
+        "title_rect",
+        "main_stat_name_rect",
+        "main_stat_value_rect",
+        "sub_stat_1",
+        "sub_stat_2",
+        "sub_stat_3",
+        "sub_stat_4",
+        "level_rect",
+        "blessed_frost_detect_rect",
+        "set_description_rect",
+        "item_equip_rect",
+        "item_count_rect",
+        "panel_rect",
+    ];
+
+    fn rect_mut(&mut self, name: &str) -> Option<&mut Rect<f64>> {
+        match name {
+            "title_rect" => Some(&mut self.title_rect),
+            "main_stat_name_rect" => Some(&mut self.main_stat_name_rect),
+            "main_stat_value_rect" => Some(&mut self.main_stat_value_rect),
+            "sub_stat_1" => Some(&mut self.sub_stat_1),
+            "sub_stat_2" => Some(&mut self.sub_stat_2),
+            "sub_stat_3" => Some(&mut self.sub_stat_3),
+            "sub_stat_4" => Some(&mut self.sub_stat_4),
+            "level_rect" => Some(&mut self.level_rect),
+            "blessed_frost_detect_rect" => Some(&mut self.blessed_frost_detect_rect),
+            "set_description_rect" => Some(&mut self.set_description_rect),
+            "item_equip_rect" => Some(&mut self.item_equip_rect),
+            "item_count_rect" => Some(&mut self.item_count_rect),
+            "panel_rect" => Some(&mut self.panel_rect),
+            _ => None,
+        }
+    }
+
+    /// Replace rects by a JSON object of field name to `{ "left", "top", "width", "height" }`, e.g.
+    /// `{ "sub_stat_4": { "left": 1120, "top": 395, "width": 300, "height": 30 } }`.
+    /// The rects are in pixels of the current window, they are not scaled. An unknown field name is an error
+    pub fn apply_override(&mut self, overrides: &serde_json::Value) -> Result<()> {
+        let overrides = overrides.as_object()
+            .ok_or_else(|| anyhow!("window info override must be a JSON object"))?;
+
+        // check every key before changing anything
+        let mut rects = Vec::with_capacity(overrides.len());
+        for (name, value) in overrides.iter() {
+            if self.rect_mut(name).is_none() {
+                return Err(anyhow!(
+                    "unknown window info override {:?}, expected one of: {}",
+                    name, Self::OVERRIDABLE_RECTS.join(", ")
+                ));
+            }
+            let rect: Rect<f64> = serde_json::from_value(value.clone())
+                .with_context(|| format!("window info override {:?}", name))?;
+            rects.push((name, rect));
+        }
+
+        for (name, rect) in rects {
+            *self.rect_mut(name).unwrap() = rect;
+        }
+        Ok(())
+    }
+
+    /// `apply_override` with the JSON in `path`
+    pub fn apply_override_file(&mut self, path: &std::path::Path) -> Result<()> {
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("read window info override {:?}", path))?;
+        let overrides: serde_json::Value = serde_json::from_str(&s)
+            .with_context(|| format!("parse window info override {:?}", path))?;
+        self.apply_override(&overrides)
+    }
+
+    /// Apply the `--grid-rows`/`--grid-cols` overrides
+    pub fn override_grid(&mut self, config: &GenshinRepositoryScannerLogicConfig) {
+        let (row, col) = config.grid_size(self.row, self.col);
+        self.row = row;
+        self.col = col;
+    }
+
+    /// Number of items in one page
+    pub fn page_size(&self) -> i32 {
+        self.row * self.col
+    }
+
+    /// The list grid for list-view lock detection, see `yas::utils::get_page_locks`
+    pub fn list_lock_grid(&self) -> ListLockGrid {
+        ListLockGrid {
+            row: self.row,
+            col: self.col,
+            item_size: self.item_size,
+            item_gap_size: self.item_gap_size,
+            lock_pos: Pos { x: 19.0, y: 93.0 },
+            lock_color: self.list_lock_color,
+            lock_threshold: self.list_lock_threshold,
+        }
+    }
+
+    pub fn star_colors(&self) -> [Rgb<u8>; 5] {
+        [self.star_color_1, self.star_color_2, self.star_color_3, self.star_color_4, self.star_color_5]
+    }
+
+    pub fn star_thresholds(&self) -> [f64; 5] {
+        [self.star_threshold_1, self.star_threshold_2, self.star_threshold_3, self.star_threshold_4, self.star_threshold_5]
+    }
+
+    /// The star (1-5) whose reference color is closest to `color`
+    pub fn match_star(&self, color: &Rgb<u8>) -> usize {
+        self.match_star_with_confidence(color).star
+    }
+
+    /// The closest star among those whose reference is within its threshold of `color`.
+    /// If there is none, the closest star overall, flagged as not confident
+    pub fn match_star_with_confidence(&self, color: &Rgb<u8>) -> StarMatch {
+        let distances = self.star_colors().map(|reference| color_distance_sqrt(&reference, color));
+        let thresholds = self.star_thresholds();
+        let closest = |confident: bool| {
+            (0..5)
+                .filter(|&i| !confident || distances[i] <= thresholds[i])
+                .min_by(|&a, &b| distances[a].total_cmp(&distances[b]))
+                .map(|i| StarMatch { star: i + 1, confident })
+        };
+
+        closest(true).or_else(|| closest(false)).unwrap()
+    }
+
+    /// The star of `color` as `mode` reads it
+    pub fn match_star_with_mode(&self, color: &Rgb<u8>, mode: StarDetectMode) -> StarMatch {
+        match mode {
+            StarDetectMode::Nearest => self.match_star_with_confidence(color),
+            StarDetectMode::Hue => self.match_star_by_hue(color),
+        }
+    }
+
+    /// The star whose reference color has the nearest hue. A color less saturated than halfway between the
+    /// 1 star reference and the least saturated other one is 1 star, which is gray and has no meaningful hue.
+    /// Not confident if the hue is more than `STAR_HUE_TOLERANCE` degrees from the nearest reference
+    pub fn match_star_by_hue(&self, color: &Rgb<u8>) -> StarMatch {
+        let references = self.star_colors().map(|reference| hue_saturation(&reference));
+        let (hue, saturation) = hue_saturation(color);
+
+        let gray_saturation = references[0].1;
+        let min_saturation = references[1..].iter().map(|r| r.1).fold(f64::INFINITY, f64::min);
+        if saturation <= (gray_saturation + min_saturation) / 2.0 {
+            return StarMatch { star: 1, confident: true };
+        }
+
+        let (index, distance) = (1..5)
+            .map(|i| (i, hue_distance(hue, references[i].0)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        StarMatch { star: index + 1, confident: distance <= STAR_HUE_TOLERANCE }
+    }
+
+    /// Check if the panel image has the purple 祝圣之霜 (Blessed Frost) block in the configured detect rect.
+    /// Uses color distance to a reference purple; when enough pixels match, returns true.
+    pub fn has_blessed_frost_mark(&self, panel_image: &RgbImage) -> bool {
+        if self.blessed_frost_detect_rect.height <= 0.0 {
+            return false;
+        }
+        let relative_rect = self.blessed_frost_detect_rect.translate(Pos {
+            x: -self.panel_rect.left,
+            y: -self.panel_rect.top,
+        });
+        let left = relative_rect.left as u32;
+        let top = relative_rect.top as u32;
+        let w = relative_rect.width as u32;
+        let h = relative_rect.height as u32;
+        if left + w > panel_image.width() || top + h > panel_image.height() {
+            return false;
+        }
+        // Reference purple for 祝圣之霜 block (RGB)
+        const PURPLE_REF: Rgb<u8> = Rgb([220, 192, 255]);
+        const DIST_THRESHOLD: usize = 10;
+        const RATIO_THRESHOLD: f32 = 0.9;
+        let mut match_count = 0u32;
+        let total = (w * h).max(1);
+        for py in top..(top + h) {
+            for px in left..(left + w) {
+                let color = panel_image.get_pixel(px, py);
+                if yas::utils::color_distance(color, &PURPLE_REF) < DIST_THRESHOLD {
+                    match_count += 1;
+                }
+            }
+        }
+        (match_count as f32 / total as f32) >= RATIO_THRESHOLD
+    }
+
+    /// The layout with the 祝圣之霜 block present: level, sub stats, star and lock button are pushed down
+    /// by `blessed_frost_offset_y`, the equip rect stays
+    pub fn shifted_by_blessed_frost(&self) -> Self {
+        let offset = Pos { x: 0.0, y: self.blessed_frost_offset_y };
+        let shift = |pos: Pos<f64>| Pos { x: pos.x + offset.x, y: pos.y + offset.y };
+        ArtifactScannerWindowInfo {
+            level_rect: self.level_rect.translate(offset),
+            sub_stat_1: self.sub_stat_1.translate(offset),
+            sub_stat_2: self.sub_stat_2.translate(offset),
+            sub_stat_3: self.sub_stat_3.translate(offset),
+            sub_stat_4: self.sub_stat_4.translate(offset),
+            set_description_rect: self.set_description_rect.translate(offset),
+            star_pos: shift(self.star_pos),
+            artifact_lock_pos: shift(self.artifact_lock_pos),
+            ..self.clone()
+        }
+    }
+
+    /// The layout of this panel, shifted if it has the 祝圣之霜 block
+    pub fn for_panel(&self, panel_image: &RgbImage) -> Self {
+        if self.blessed_frost_offset_y != 0.0 && self.has_blessed_frost_mark(panel_image) {
+            self.shifted_by_blessed_frost()
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Draw the rects read from a panel onto `panel_image`, a capture of `panel_rect`, each kind in its own color:
+    /// title red, main stat yellow, sub stats green, level cyan, equip blue, the detect rects magenta.
+    /// The layout is the one `for_panel` picks for this image
+    pub fn draw_regions(&self, panel_image: &mut RgbImage) {
+        let layout = self.for_panel(panel_image);
+        let origin = Pos { x: -self.panel_rect.left, y: -self.panel_rect.top };
+
+        let red = Rgb([255, 0, 0]);
+        let yellow = Rgb([255, 255, 0]);
+        let green = Rgb([0, 255, 0]);
+        let cyan = Rgb([0, 255, 255]);
+        let blue = Rgb([0, 0, 255]);
+        let magenta = Rgb([255, 0, 255]);
+
+        let mut regions = vec![
+            (layout.title_rect, red),
+            (layout.main_stat_name_rect, yellow),
+            (layout.main_stat_value_rect, yellow),
+            (layout.sub_stat_1, green),
+            (layout.sub_stat_2, green),
+            (layout.sub_stat_3, green),
+            (layout.sub_stat_4, green),
+            (layout.level_rect, cyan),
+            (layout.item_equip_rect, blue),
+        ];
+        // optional ones, zero height when missing
+        for rect in [layout.blessed_frost_detect_rect, layout.set_description_rect] {
+            if rect.height > 0.0 {
+                regions.push((rect, magenta));
+            }
+        }
+
+        for (rect, color) in regions {
+            draw_rect(panel_image, &rect.translate(origin), color);
+        }
+    }
+
+    /// The color at star_pos of a full-window screenshot, to calibrate the star colors with a panel of known star
+    pub fn sample_star_color(&self, window_image: &RgbImage) -> Option<Rgb<u8>> {
+        let (x, y) = (self.star_pos.x as u32, self.star_pos.y as u32);
+        if x < window_image.width() && y < window_image.height() {
+            Some(*window_image.get_pixel(x, y))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yas::game_info::{Platform, UI};
+    use yas::window_info::{content_box, load_window_info_repo, FromWindowInfoRepository};
+
+    use super::*;
+
+    #[test]
+    fn test_star_colors_default_when_missing() {
+        let repo = load_window_info_repo!("../../../window_info/windows1600x900.json");
+        let info = ArtifactScannerWindowInfo::from_window_info_repository(
+            Size::new(1600, 900),
+            UI::Desktop,
+            Platform::Windows,
+            &repo,
+        ).unwrap();
+
+        assert_eq!(info.star_color_5, Rgb([188, 105, 50]));
+        assert_eq!(info.match_star(&Rgb([190, 100, 55])), 5);
+        assert_eq!(info.match_star(&Rgb([115, 120, 140])), 1);
+    }
+
+    #[test]
+    fn test_custom_star_color() {
+        let info = ArtifactScannerWindowInfo {
+            star_pos: Pos { x: 2.0, y: 1.0 },
+            star_color_4: Rgb([200, 200, 200]),
+            star_color_5: Rgb([20, 20, 20]),
+            ..Default::default()
+        };
+        let mut image = RgbImage::new(4, 4);
+        image.put_pixel(2, 1, Rgb([25, 22, 18]));
+
+        let sampled = info.sample_star_color(&image).unwrap();
+        assert_eq!(info.match_star(&sampled), 5);
+        assert!(info.sample_star_color(&RgbImage::new(1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_star_threshold() {
+        let info = ArtifactScannerWindowInfo {
+            star_color_4: Rgb([161, 86, 224]),
+            star_color_5: Rgb([188, 105, 50]),
+            star_threshold_4: 10.0,
+            star_threshold_5: 60.0,
+            ..Default::default()
+        };
+
+        let star = info.match_star_with_confidence(&Rgb([161, 86, 214]));
+        assert_eq!(star, StarMatch { star: 4, confident: true });
+
+        // 11 away from the 4 star reference, just outside its tightened threshold
+        let star = info.match_star_with_confidence(&Rgb([161, 86, 213]));
+        assert_eq!(star, StarMatch { star: 4, confident: false });
+        assert_eq!(info.match_star(&Rgb([161, 86, 213])), 4);
+    }
+
+    #[test]
+    fn test_star_by_hue_survives_brightness_shift() {
+        let repo = load_window_info_repo!("../../../window_info/windows1600x900.json");
+        let info = ArtifactScannerWindowInfo::from_window_info_repository(
+            Size::new(1600, 900),
+            UI::Desktop,
+            Platform::Windows,
+            &repo,
+        ).unwrap();
+
+        let shift = |color: Rgb<u8>, factor: f64| Rgb(color.0.map(|c| (c as f64 * factor).round().min(255.0) as u8));
+        for factor in [0.5, 0.7, 1.0, 1.15, 1.3] {
+            for (i, reference) in info.star_colors().into_iter().enumerate() {
+                let star = info.match_star_with_mode(&shift(reference, factor), StarDetectMode::Hue);
+                assert_eq!(star, StarMatch { star: i + 1, confident: true }, "{} star at brightness {}", i + 1, factor);
+            }
+        }
+
+        // the nearest RGB color is wrong once the screen is dark enough
+        let dark_purple = shift(info.star_color_4, 0.5);
+        assert_ne!(info.match_star_with_mode(&dark_purple, StarDetectMode::Nearest).star, 4);
+        assert_eq!(info.match_star_with_mode(&dark_purple, StarDetectMode::Hue).star, 4);
+    }
+
+    #[test]
+    fn test_blessed_frost_shifts_layout() {
+        let repo = load_window_info_repo!("../../../window_info/windows1600x900.json");
+        let info = ArtifactScannerWindowInfo::from_window_info_repository(
+            Size::new(1600, 900),
+            UI::Desktop,
+            Platform::Windows,
+            &repo,
+        ).unwrap();
+
+        let (width, height) = (info.panel_rect.width as u32, info.panel_rect.height as u32);
+        let plain = RgbImage::new(width, height);
+        let detect = info.blessed_frost_detect_rect.translate(Pos { x: -info.panel_rect.left, y: -info.panel_rect.top });
+        let marked = RgbImage::from_fn(width, height, |x, y| {
+            let (x, y) = (x as f64, y as f64);
+            if x >= detect.left - 1.0 && x <= detect.left + detect.width + 1.0
+                && y >= detect.top - 1.0 && y <= detect.top + detect.height + 1.0 {
+                Rgb([220, 192, 255])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        });
+
+        let without = info.for_panel(&plain);
+        let with = info.for_panel(&marked);
+        let offset = info.blessed_frost_offset_y;
+        assert!(offset > 0.0);
+
+        assert_eq!(without.level_rect, info.level_rect);
+        assert_eq!(without.artifact_lock_pos, info.artifact_lock_pos);
+        assert_eq!(with.level_rect.top, info.level_rect.top + offset);
+        assert_eq!(with.sub_stat_4.top, info.sub_stat_4.top + offset);
+        assert_eq!(with.star_pos.y, info.star_pos.y + offset);
+        assert_eq!(with.artifact_lock_pos.y, info.artifact_lock_pos.y + offset);
+        assert_eq!(with.artifact_lock_pos.x, info.artifact_lock_pos.x);
+        assert_eq!(with.item_equip_rect, info.item_equip_rect);
+    }
+
+    #[test]
+    fn test_ultrawide_panel_in_content_box() {
+        let repo = load_window_info_repo!("../../../window_info/windows1600x900.json");
+        let size = Size::new(2560, 1080);
+        let info = ArtifactScannerWindowInfo::from_window_info_repository(
+            size,
+            UI::Desktop,
+            Platform::Windows,
+            &repo,
+        ).unwrap();
+
+        let content = content_box(size);
+        let panel = info.panel_rect;
+        assert!(panel.left >= content.left);
+        assert!(panel.top >= content.top);
+        assert!(panel.left + panel.width <= content.left + content.width);
+        assert!(panel.top + panel.height <= content.top + content.height);
+    }
+
+    #[test]
+    fn test_missing_optional_key_falls_back() {
+        let mut repo = load_window_info_repo!("../../../window_info/windows1600x900.json");
+        repo.data.remove("genshin_artifact_blessed_frost_detect_rect");
+        repo.data.remove("genshin_artifact_blessed_frost_offset_y");
+
+        let info = ArtifactScannerWindowInfo::from_window_info_repository(
+            Size::new(1600, 900),
+            UI::Desktop,
+            Platform::Windows,
+            &repo,
+        ).unwrap();
+        assert_eq!(info.blessed_frost_detect_rect.height, 0.0);
+        assert_eq!(info.blessed_frost_offset_y, 0.0);
+    }
+
+    #[test]
+    fn test_draw_regions() {
+        let info = ArtifactScannerWindowInfo {
+            panel_rect: Rect::new(100.0, 50.0, 40.0, 40.0),
+            title_rect: Rect::new(105.0, 55.0, 10.0, 5.0),
+            sub_stat_1: Rect::new(105.0, 70.0, 10.0, 5.0),
+            // partly outside the panel, clipped instead of panicking
+            item_equip_rect: Rect::new(130.0, 85.0, 20.0, 20.0),
+            ..Default::default()
+        };
+        let mut image = RgbImage::new(40, 40);
+        info.draw_regions(&mut image);
+
+        assert_eq!(*image.get_pixel(5, 5), Rgb([255, 0, 0]));
+        assert_eq!(*image.get_pixel(15, 10), Rgb([255, 0, 0]));
+        assert_eq!(*image.get_pixel(5, 20), Rgb([0, 255, 0]));
+        assert_eq!(*image.get_pixel(30, 39), Rgb([0, 0, 255]));
+        assert_eq!(*image.get_pixel(10, 7), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_override_rect() {
+        let mut info = ArtifactScannerWindowInfo {
+            title_rect: Rect::new(1.0, 2.0, 3.0, 4.0),
+            ..Default::default()
+        };
+        let overrides = serde_json::json!({
+            "sub_stat_4": { "left": 1120.0, "top": 395.0, "width": 300.0, "height": 30.0 }
+        });
+
+        info.apply_override(&overrides).unwrap();
+        assert_eq!(info.sub_stat_4, Rect::new(1120.0, 395.0, 300.0, 30.0));
+        assert_eq!(info.title_rect, Rect::new(1.0, 2.0, 3.0, 4.0));
+        for name in ArtifactScannerWindowInfo::OVERRIDABLE_RECTS {
+            assert!(info.rect_mut(name).is_some(), "{}", name);
+        }
+
+        // a typo is reported and nothing is applied
+        let overrides = serde_json::json!({
+            "title_rect": { "left": 0.0, "top": 0.0, "width": 10.0, "height": 10.0 },
+            "sub_stat4": { "left": 0.0, "top": 0.0, "width": 10.0, "height": 10.0 }
+        });
+        let error = info.apply_override(&overrides).unwrap_err();
+        assert!(error.to_string().contains("\"sub_stat4\""), "{}", error);
+        assert_eq!(info.title_rect, Rect::new(1.0, 2.0, 3.0, 4.0));
+
+        assert!(info.apply_override(&serde_json::json!({ "title_rect": { "left": 0.0 } })).is_err());
+    }
+
+    #[test]
+    fn test_missing_required_key_named() {
+        let mut repo = load_window_info_repo!("../../../window_info/windows1600x900.json");
+        repo.data.remove("genshin_repository_panel_rect");
+
+        let error = ArtifactScannerWindowInfo::from_window_info_repository(
+            Size::new(1600, 900),
+            UI::Desktop,
+            Platform::Windows,
+            &repo,
+        ).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("\"genshin_repository_panel_rect\""), "{}", message);
+        assert!(message.contains("1600x900"), "{}", message);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::cell::RefCell;
 use std::path::Path;
 use std::sync::mpsc::{Receiver, Sender};
 use std::thread::JoinHandle;
@@ -9,110 +9,195 @@ use image::{GenericImageView, RgbImage};
 use log::{error, info, warn};
 
 use yas::ocr::yas_ocr_model;
-use yas::ocr::ImageToText;
+use yas::ocr::{preprocess_result, ImageToText, PreprocessMode, SharedYasOCRModel};
 use yas::positioning::{Pos, Rect};
-use yas::utils::color_distance;
+use yas::utils::{get_page_locks, lock_at, LockCell};
 
-use crate::artifact::ArtifactStat;
+use crate::artifact::{ArtifactSetName, ArtifactStat, GenshinArtifact};
+use crate::scanner::artifact_scanner::auto_save::AutoSaver;
+use crate::scanner::artifact_scanner::dedup::Deduplicator;
+use crate::scanner::artifact_scanner::checkpoint::{CheckpointWriter, ScanCheckpoint};
+use crate::scanner::artifact_scanner::log_throttle::LogThrottle;
 use crate::scanner::artifact_scanner::artifact_scanner_window_info::ArtifactScannerWindowInfo;
 use crate::scanner::artifact_scanner::message_items::SendItem;
-use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+use crate::scanner::artifact_scanner::pending_detect::is_gray_text;
+use crate::scanner::artifact_scanner::rejects::RejectsWriter;
+use crate::scanner::artifact_scanner::result_stream::ResultStream;
+use crate::scanner::artifact_scanner::scan_event::{ScanEvent, ScanEventSender};
+use crate::scanner::artifact_scanner::scan_timings::ScanTimings;
+use crate::scanner::artifact_scanner::timed_model::TimedImageToText;
+use crate::scanner::artifact_scanner::unparsed_lines::UnparsedLines;
+use crate::scanner::artifact_scanner::scan_result::{GenshinArtifactScanResult, ScanConfidence};
 use crate::scanner::artifact_scanner::GenshinArtifactScannerConfig;
 
-fn parse_level(s: &str) -> Result<i32> {
-    let pos = s.find('+');
+const MAX_LEVEL: i32 = 20;
 
-    if pos.is_none() {
-        let level = s
-            .parse::<i32>()
-            .with_context(|| format!("parse level (OCR raw: {:?})", s))?;
-        return Ok(level);
+/// Brightness standard deviation under which a field is taken as empty background, a line of text,
+/// gray (待激活) ones included, is far above it
+const BLANK_LUMA_STD_DEV: f64 = 6.0;
+
+/// Standard deviation of the brightness of `image`
+fn luma_std_dev(image: &RgbImage) -> f64 {
+    let n = (image.width() * image.height()) as f64;
+    if n == 0.0 {
+        return 0.0;
+    }
+    let luma = |p: &Rgb<u8>| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64;
+    let mean = image.pixels().map(luma).sum::<f64>() / n;
+    let variance = image.pixels().map(|p| (luma(p) - mean).powi(2)).sum::<f64>() / n;
+    variance.sqrt()
+}
+
+/// Whether a field has nothing in it, gray (待激活) text included: near uniform background,
+/// checked first as the panel background is not flat enough for the preprocess alone
+pub(crate) fn is_blank_image(image: &RgbImage) -> bool {
+    luma_std_dev(image) < BLANK_LUMA_STD_DEV || preprocess_result(image, true).is_none()
+}
+
+/// Fails for a panel capture that is empty or a single flat color, e.g. black while the game window
+/// is minimized or covered, instead of reading made up text from it
+pub(crate) fn check_panel_captured(panel_image: &RgbImage) -> Result<()> {
+    if panel_image.width() == 0 || panel_image.height() == 0 {
+        anyhow::bail!("面板截图为空，游戏窗口可能被遮挡或最小化");
+    }
+    let std_dev = luma_std_dev(panel_image);
+    if std_dev < BLANK_LUMA_STD_DEV {
+        anyhow::bail!("面板截图几乎为纯色（亮度标准差 {:.1}），游戏窗口可能被遮挡或最小化", std_dev);
+    }
+    Ok(())
+}
+
+/// Characters of a main stat value, e.g. `4,780` or `46.6%`
+const VALUE_CHARS: &str = "0123456789.,%";
+/// Characters of the level line, e.g. `+20`
+pub(crate) const LEVEL_CHARS: &str = "+0123456789";
+
+/// Parses the level line, e.g. `+20`. Only the text after the last `+` counts, the letters OCR commonly
+/// mistakes for digits (`O` for 0, `l` for 1) are mapped back and anything else that is not a digit is dropped.
+/// The result is clamped to 0..=20
+pub(crate) fn parse_level(s: &str) -> Result<i32> {
+    let text = match s.rfind('+') {
+        Some(pos) => &s[pos + 1..],
+        None => s,
+    };
+
+    let digits = text.chars()
+        .map(|c| match c {
+            'O' | 'o' => '0',
+            'l' | 'I' | '|' => '1',
+            c => c,
+        })
+        .filter(|c| c.is_ascii_digit())
+        .collect::<String>();
+    if digits.is_empty() {
+        anyhow::bail!("no digits in level (OCR raw: {:?})", s);
     }
 
-    let level = s[pos.unwrap()..]
+    let level = digits
         .parse::<i32>()
-        .with_context(|| format!("parse level after '+' (OCR raw: {:?})", s))?;
-    Ok(level)
+        .with_context(|| format!("parse level (OCR raw: {:?})", s))?;
+    if level > MAX_LEVEL {
+        warn!("等级 {} 超出范围，记为 {} 级（识别原文: {:?}）", level, MAX_LEVEL, s);
+    }
+    Ok(level.clamp(0, MAX_LEVEL))
 }
 
-/// List-view lock detection: from a cropped list grid image, return lock state per cell (row-major).
-/// Uses lock icon color [255,138,117] at lock_pos within each cell. Caller crops the list region
-/// (e.g. scan_margin_pos + first-page rect) from the window image.
-/// If `debug_dir` is Some, dumps the sampled pixel region image for each (row, col) as `r{row}_c{col}.png`.
+/// Like `parse_level`, but an empty OCR result is level 0 unless `strict`
+fn parse_level_or_default(s: &str, strict: bool) -> Result<i32> {
+    if s.trim().is_empty() && !strict {
+        warn!("等级识别为空，记为 0 级");
+        return Ok(0);
+    }
+    parse_level(s)
+}
+
+/// The pixel of cell (`row`, `col`)'s lock search box closest to the configured lock icon color, with its distance.
+/// Sampled from a locked artifact, this is the lock color of the current UI brightness. None if the box is off the image
+pub fn sample_lock_color(
+    list_image: &RgbImage,
+    window_info: &ArtifactScannerWindowInfo,
+    row: i32,
+    col: i32,
+) -> Option<(Rgb<u8>, usize)> {
+    yas::utils::sample_lock_color(list_image, &window_info.list_lock_grid(), row, col)
+}
+
+/// `yas::utils::get_page_locks` with the artifact list grid of `window_info`
 pub fn get_page_locks_from_list_image(
     list_image: &RgbImage,
     window_info: &ArtifactScannerWindowInfo,
+    min_visible_ratio: f64,
     debug_dir: Option<&Path>,
-) -> Vec<bool> {
-    let mut result = Vec::new();
-    let row = window_info.row;
-    let col = window_info.col;
-    let gap = window_info.item_gap_size;
-    let size = window_info.item_size;
-    let lock_pos = Pos { x: 19.0, y: 93.0 };
-    
-    if let Some(dir) = debug_dir {
-        let _ = std::fs::create_dir_all(dir);
-        let path = dir.join(format!("qwq.png"));
-        let _ = list_image.save(&path);
-    }
-
-    for r in 0..row {
-        if ((gap.height + size.height) * (r as f64)) as u32 > list_image.height() {
-            break;
-        }
-        for c in 0..col {
-            let pos_x = (gap.width + size.width) * (c as f64) + lock_pos.x;
-            let pos_y = (gap.height + size.height) * (r as f64) + lock_pos.y;
-            println!("pos_x: {}, pos_y: {}", pos_x, pos_y);
-
-            if let Some(dir) = debug_dir {
-                let px = pos_x as i32;
-                let py = pos_y as i32;
-                let left = px.saturating_sub(2).max(0) as u32;
-                let top = py.saturating_sub(10).max(0) as u32;
-                let w = (list_image.width()).saturating_sub(left).min(6);
-                let h = (list_image.height()).saturating_sub(top).min(22);
-                if w > 0 && h > 0 {
-                    let crop = list_image.view(left, top, w, h).to_image();
-                    let path = dir.join(format!("r{}_c{}.png", r, c));
-                    let _ = crop.save(&path);
-                }
-            }
+) -> Vec<LockCell> {
+    get_page_locks(list_image, &window_info.list_lock_grid(), min_visible_ratio, debug_dir)
+}
 
-            let mut locked = false;
-            'sq: for dx in -1..1 {
-                for dy in -10..10 {
-                    if pos_y as i32 + dy < 0
-                        || (pos_y as i32 + dy) as u32 >= list_image.height()
-                    {
-                        continue;
-                    }
+/// Crop `rect` (in window coordinates) out of `panel_image`, captured at `panel_rect`
+pub(crate) fn crop_panel_rect(panel_rect: Rect<f64>, rect: Rect<f64>, panel_image: &RgbImage) -> Result<RgbImage> {
+    let relative_rect = rect.translate(Pos {
+        x: -panel_rect.left,
+        y: -panel_rect.top,
+    });
 
-                    let color = list_image.get_pixel(
-                        (pos_x as i32 + dx) as u32,
-                        (pos_y as i32 + dy) as u32,
-                    );
+    let w = panel_image.width();
+    let h = panel_image.height();
+    let x = relative_rect.left as u32;
+    let y = relative_rect.top as u32;
+    let rw = relative_rect.width as u32;
+    let rh = relative_rect.height as u32;
+    if x.saturating_add(rw) > w || y.saturating_add(rh) > h {
+        anyhow::bail!(
+            "crop region out of bounds: rect ({}..{}, {}..{}) vs image {}x{}",
+            x,
+            x + rw,
+            y,
+            y + rh,
+            w,
+            h
+        );
+    }
 
-                    if color_distance(color, &Rgb([255, 138, 117])) < 30 {
-                        locked = true;
-                        break 'sq;
-                    }
-                }
-            }
-            result.push(locked);
-        }
+    Ok(panel_image.view(x, y, rw, rh).to_image())
+}
+
+/// Whether a run of `consecutive_dup_count` duplicates should stop the scan.
+/// Normally a full row of duplicates means a paging error; with `ignore_dup` only the much longer `ignore_dup_max_run` does,
+/// so that a broken page turn cannot keep the scan going forever.
+/// A given `number` of items is always scanned to the end, the scan cannot run past it
+fn should_break_on_dup(consecutive_dup_count: i32, col: i32, config: &GenshinArtifactScannerConfig) -> bool {
+    if config.number > 0 {
+        false
+    } else if config.ignore_dup {
+        config.ignore_dup_max_run > 0 && consecutive_dup_count >= config.ignore_dup_max_run
+    } else {
+        consecutive_dup_count >= col
     }
-    result
 }
 
-fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-    use yas::ocr::yas_ocr_model;
-    let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(yas_ocr_model!(
-        "./models/model_training.onnx",
-        "./models/index_2_word.json"
-    )?);
-    Ok(model)
+/// Load the OCR model from `config.model_dir` if given, otherwise use the model embedded in the binary
+pub(crate) fn get_image_to_text(config: &GenshinArtifactScannerConfig) -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
+    let model = match config.model_dir.as_deref() {
+        Some(dir) => SharedYasOCRModel::from_files(
+            &dir.join("model_training.onnx"),
+            &dir.join("index_2_word.json"),
+        )?,
+        None => yas_ocr_model!(
+            "./models/model_training.onnx",
+            "./models/index_2_word.json"
+        )?,
+    };
+
+    let pending_line_mode = if config.adaptive_binarize {
+        PreprocessMode::Adaptive
+    } else {
+        PreprocessMode::Global
+    };
+    Ok(Box::new(model.with_pending_line_mode(pending_line_mode)))
+}
+
+/// Whether an artifact passes `--only-sets`. An empty filter keeps everything, including results that cannot be parsed
+fn in_set_filter(artifact: Option<&GenshinArtifact>, set_filter: &[ArtifactSetName]) -> bool {
+    set_filter.is_empty() || artifact.is_some_and(|artifact| set_filter.contains(&artifact.set_name))
 }
 
 /// run in a separate thread, accept captured image and get an artifact
@@ -120,6 +205,12 @@ pub struct ArtifactScannerWorker {
     model: Box<dyn ImageToText<RgbImage> + Send>,
     window_info: ArtifactScannerWindowInfo,
     config: GenshinArtifactScannerConfig,
+    /// the interrupted scan being continued, see `resume_from`
+    resume: Option<ScanCheckpoint>,
+    /// index (starting from 0) of the first item that will be received
+    start_index: usize,
+    events: ScanEventSender,
+    timings: RefCell<ScanTimings>,
 }
 
 impl ArtifactScannerWorker {
@@ -127,101 +218,84 @@ impl ArtifactScannerWorker {
         window_info: ArtifactScannerWindowInfo,
         config: GenshinArtifactScannerConfig,
     ) -> Result<Self> {
-        Ok(ArtifactScannerWorker {
-            model: get_image_to_text()?,
+        let model = get_image_to_text(&config)?;
+        Ok(Self::with_model(window_info, config, model))
+    }
+
+    pub fn with_model(
+        window_info: ArtifactScannerWindowInfo,
+        config: GenshinArtifactScannerConfig,
+        model: Box<dyn ImageToText<RgbImage> + Send>,
+    ) -> Self {
+        let model: Box<dyn ImageToText<RgbImage> + Send> = if config.ocr_timeout_ms > 0 {
+            Box::new(TimedImageToText::new(model, std::time::Duration::from_millis(config.ocr_timeout_ms)))
+        } else {
+            model
+        };
+        let timings = RefCell::new(ScanTimings::new(config.verbose));
+        ArtifactScannerWorker {
+            model,
             window_info,
             config,
-        })
+            resume: None,
+            start_index: 0,
+            events: ScanEventSender::default(),
+            timings,
+        }
+    }
+
+    /// Continue the scan of `checkpoint`: its results are kept and count as already seen,
+    /// and the first item received is the `start_index`-th (starting from 0), see `ScanCheckpoint::resume_index`
+    pub fn resume_from(mut self, checkpoint: ScanCheckpoint, start_index: usize) -> Self {
+        self.resume = Some(checkpoint);
+        self.start_index = start_index;
+        self
+    }
+
+    /// Report `Scanned`, `ParseFailed` and `Finished` events to `events`
+    pub fn with_events(mut self, events: ScanEventSender) -> Self {
+        self.events = events;
+        self
     }
 
     /// the captured_img is a panel of the artifact, the rect is a region of the panel
     fn model_inference(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<String> {
-        let relative_rect = rect.translate(Pos {
-            x: -self.window_info.panel_rect.left,
-            y: -self.window_info.panel_rect.top,
-        });
+        Ok(self.model_inference_with_confidence(rect, captured_img)?.0)
+    }
 
-        let w = captured_img.width();
-        let h = captured_img.height();
-        let x = relative_rect.left as u32;
-        let y = relative_rect.top as u32;
-        let rw = relative_rect.width as u32;
-        let rh = relative_rect.height as u32;
-        if x.saturating_add(rw) > w || y.saturating_add(rh) > h {
-            anyhow::bail!(
-                "crop region out of bounds: rect ({}..{}, {}..{}) vs image {}x{}",
-                x,
-                x + rw,
-                y,
-                y + rh,
-                w,
-                h
-            );
-        }
+    /// Crop `rect` (in window coordinates) out of the panel image
+    fn crop_panel(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<RgbImage> {
+        crop_panel_rect(self.window_info.panel_rect, rect, captured_img)
+    }
 
-        let raw_img = captured_img.view(x, y, rw, rh).to_image();
-        self.model.image_to_text(&raw_img, false)
+    /// Whether `rect` has nothing in it, see `is_blank_image`
+    fn is_blank(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<bool> {
+        Ok(is_blank_image(&self.crop_panel(rect, captured_img)?))
     }
 
-    /// Same as model_inference but with preprocessing tuned for gray (待激活) substat; use for 4th substat.
-    fn model_inference_pending_line(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<String> {
-        let relative_rect = rect.translate(Pos {
-            x: -self.window_info.panel_rect.left,
-            y: -self.window_info.panel_rect.top,
-        });
-        let w = captured_img.width();
-        let h = captured_img.height();
-        let x = relative_rect.left as u32;
-        let y = relative_rect.top as u32;
-        let rw = relative_rect.width as u32;
-        let rh = relative_rect.height as u32;
-        if x.saturating_add(rw) > w || y.saturating_add(rh) > h {
-            anyhow::bail!(
-                "crop region out of bounds (pending-line): rect ({}..{}, {}..{}) vs image {}x{}",
-                x,
-                x + rw,
-                y,
-                y + rh,
-                w,
-                h
-            );
+    /// model_inference, also returning the model's confidence in the text
+    fn model_inference_with_confidence(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<(String, f32)> {
+        let raw_img = self.crop_panel(rect, captured_img)?;
+        // a blank field, e.g. a missing substat line
+        if preprocess_result(&raw_img, false).is_none() {
+            return Ok((String::new(), 1.0));
         }
-        let raw_img = captured_img.view(x, y, rw, rh).to_image();
-        self.model.image_to_text_pending_line(&raw_img)
+        self.model.image_to_text_with_confidence(&raw_img, false)
     }
 
-    /// Check if the panel image has the purple 祝圣之霜 (Blessed Frost) block in the configured detect rect.
-    /// Uses color distance to a reference purple; when enough pixels match, returns true.
-    fn has_blessed_frost_mark(&self, panel_image: &RgbImage) -> bool {
-        if self.window_info.blessed_frost_detect_rect.height <= 0.0 {
-            return false;
+    /// model_inference_with_confidence, only recognizing the characters in `allowed_chars`
+    fn model_inference_constrained(&self, rect: Rect<f64>, captured_img: &RgbImage, allowed_chars: &str) -> Result<(String, f32)> {
+        let raw_img = self.crop_panel(rect, captured_img)?;
+        if preprocess_result(&raw_img, false).is_none() {
+            return Ok((String::new(), 1.0));
         }
-        let relative_rect = self.window_info.blessed_frost_detect_rect.translate(Pos {
-            x: -self.window_info.panel_rect.left,
-            y: -self.window_info.panel_rect.top,
-        });
-        let left = relative_rect.left as u32;
-        let top = relative_rect.top as u32;
-        let w = relative_rect.width as u32;
-        let h = relative_rect.height as u32;
-        if left + w > panel_image.width() || top + h > panel_image.height() {
-            return false;
-        }
-        // Reference purple for 祝圣之霜 block (RGB)
-        const PURPLE_REF: Rgb<u8> = Rgb([220, 192, 255]);
-        const DIST_THRESHOLD: usize = 10;
-        const RATIO_THRESHOLD: f32 = 0.9;
-        let mut match_count = 0u32;
-        let total = (w * h).max(1);
-        for py in top..(top + h) {
-            for px in left..(left + w) {
-                let color = panel_image.get_pixel(px, py);
-                if color_distance(color, &PURPLE_REF) < DIST_THRESHOLD {
-                    match_count += 1;
-                }
-            }
-        }
-        (match_count as f32 / total as f32) >= RATIO_THRESHOLD
+        self.model.image_to_text_constrained_with_confidence(&raw_img, allowed_chars)
+    }
+
+    /// Same as model_inference_with_confidence but with preprocessing tuned for gray (待激活) substat; use for 4th substat.
+    fn model_inference_pending_line(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<(String, f32)> {
+        let raw_img = self.crop_panel(rect, captured_img)?;
+        self.model.image_to_text_pending_line_with_confidence(&raw_img)
     }
 
     /// Scan a single panel image (e.g. cropped from a full window screenshot).
@@ -236,73 +310,110 @@ impl ArtifactScannerWorker {
             panel_image: panel_image.clone(),
             list_image: None,
             star: 0,
+            seq: 0,
+            retry: false,
         };
-        self.scan_item_image(item, lock)
+        self.scan_item_image(item, lock)?
+            .ok_or_else(|| anyhow::anyhow!("empty title"))
     }
 
-    /// Parse the captured result (of type SendItem) to a scanned artifact
-    fn scan_item_image(&self, item: SendItem, lock: bool) -> Result<GenshinArtifactScanResult> {
+    /// Parse the captured result (of type SendItem) to a scanned artifact.
+    /// Returns None if the title is empty and the config says to skip such artifacts
+    fn scan_item_image(&self, item: SendItem, lock: bool) -> Result<Option<GenshinArtifactScanResult>> {
         let image = &item.panel_image;
+        check_panel_captured(image)?;
 
-        let str_title = self
-            .model_inference(self.window_info.title_rect, image)
+        let (str_title, mut title_confidence) = self
+            .timed("OCR title", || self.model_inference_with_confidence(self.window_info.title_rect, image))
             .context("OCR title_rect")?;
-        let str_main_stat_name = self
-            .model_inference(self.window_info.main_stat_name_rect, image)
+        let str_title = match self.config.empty_title.resolve(str_title, || {
+            let (title, confidence) = self.model_inference_pending_line(self.window_info.title_rect, image)
+                .context("OCR title_rect (retry)")?;
+            title_confidence = confidence;
+            Ok(title)
+        })? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let (str_main_stat_name, main_stat_name_confidence) = self
+            .timed("OCR main stat name", || self.model_inference_with_confidence(self.window_info.main_stat_name_rect, image))
             .context("OCR main_stat_name_rect")?;
-        let str_main_stat_value = self
-            .model_inference(self.window_info.main_stat_value_rect, image)
+        let (str_main_stat_value, main_stat_value_confidence) = self
+            .timed("OCR main stat value", || self.model_inference_constrained(self.window_info.main_stat_value_rect, image, VALUE_CHARS))
             .context("OCR main_stat_value_rect")?;
 
-        let offset_y = if self.window_info.blessed_frost_offset_y != 0.0
-            && self.has_blessed_frost_mark(image)
-        {
-            println!("Blessed Frost detected");
-            self.window_info.blessed_frost_offset_y
-        } else {
-            0.0
-        };
-        let offset = Pos { x: 0.0, y: offset_y };
-
-        // When 祝圣之霜 block is present, shift level and sub_stats rects down (item_equip_rect stays)
-        let level_rect = self.window_info.level_rect.translate(offset);
-        let sub_stat_1 = self.window_info.sub_stat_1.translate(offset);
-        let sub_stat_2 = self.window_info.sub_stat_2.translate(offset);
-        let sub_stat_3 = self.window_info.sub_stat_3.translate(offset);
-        let sub_stat_4 = self.window_info.sub_stat_4.translate(offset);
+        // When 祝圣之霜 block is present, level and sub_stats rects are shifted down (item_equip_rect stays)
+        let layout = self.window_info.for_panel(image);
+        let level_rect = layout.level_rect;
+        let sub_stat_1 = layout.sub_stat_1;
+        let sub_stat_2 = layout.sub_stat_2;
+        let sub_stat_3 = layout.sub_stat_3;
+        let sub_stat_4 = layout.sub_stat_4;
 
-        let str_sub_stat0 = self
-            .model_inference(sub_stat_1, image)
+        let (str_sub_stat0, sub_stat0_confidence) = self
+            .timed("OCR sub stat", || self.model_inference_with_confidence(sub_stat_1, image))
             .context("OCR sub_stat_1")?;
-        let str_sub_stat1 = self
-            .model_inference(sub_stat_2, image)
+        let (str_sub_stat1, sub_stat1_confidence) = self
+            .timed("OCR sub stat", || self.model_inference_with_confidence(sub_stat_2, image))
             .context("OCR sub_stat_2")?;
-        let str_sub_stat2 = self
-            .model_inference(sub_stat_3, image)
+        let (str_sub_stat2, sub_stat2_confidence) = self
+            .timed("OCR sub stat", || self.model_inference_with_confidence(sub_stat_3, image))
             .context("OCR sub_stat_3")?;
-        // Fourth substat may be gray (待激活): try normal OCR first; if it doesn't parse, retry with pending-line preprocess
-        let str_sub_stat3 = {
-            let normal = self
-                .model_inference(sub_stat_4, image)
-                .context("OCR sub_stat_4")?;
-            if ArtifactStat::from_zh_cn_raw(&normal).is_some() {
-                normal
-            } else {
-                self.model_inference_pending_line(sub_stat_4, image)
-                    .context("OCR sub_stat_4 (pending-line)")?
+        let (str_level, mut level_confidence) = self
+            .timed("OCR level", || self.model_inference_constrained(level_rect, image, LEVEL_CHARS))
+            .context("OCR level_rect")?;
+        let level = parse_level_or_default(&str_level, self.config.strict_level).context("parse level from OCR")?;
+        if str_level.trim().is_empty() {
+            // the level was not read at all
+            level_confidence = 0.0;
+        }
+
+        // Fourth substat may be gray (待激活): decide from the text color which preprocess reads it,
+        // and mark it pending when the OCR loses the suffix of a gray line.
+        // A three substat artifact has nothing there, skip OCR so nothing is made up
+        let (str_sub_stat3, sub_stat3_confidence) = if self.is_blank(sub_stat_4, image).context("OCR sub_stat_4")? {
+            (String::new(), 1.0)
+        } else if is_gray_text(&self.crop_panel(sub_stat_4, image)?) {
+            let (mut text, confidence) = self
+                .timed("OCR sub stat (pending line)", || self.model_inference_pending_line(sub_stat_4, image))
+                .context("OCR sub_stat_4 (pending-line)")?;
+            if ArtifactStat::from_sub_raw(&text, self.config.language).is_some_and(|stat| !stat.pending) {
+                text.push_str(self.config.language.pending_suffix());
             }
+            (text, confidence)
+        } else {
+            self.timed("OCR sub stat", || self.model_inference_with_confidence(sub_stat_4, image))
+                .context("OCR sub_stat_4")?
         };
 
-        let str_level = self
-            .model_inference(level_rect, image)
-            .context("OCR level_rect")?;
-        let str_equip = self
-            .model_inference(self.window_info.item_equip_rect, image)
+        let (str_equip, equip_confidence) = self
+            .timed("OCR equip", || self.model_inference_with_confidence(self.window_info.item_equip_rect, image))
             .context("OCR item_equip_rect")?;
 
-        let level = parse_level(&str_level).context("parse level from OCR")?;
+        // only needed to confirm the set when the title is not read exactly
+        let str_description = if self.window_info.set_description_rect.height > 0.0
+            && ArtifactSetName::from_zh_cn_exact(&str_title).is_none()
+        {
+            self.timed("OCR set description", || self.model_inference(layout.set_description_rect, image))
+                .context("OCR set_description_rect")?
+        } else {
+            String::new()
+        };
+
+        let confidence = ScanConfidence {
+            name: title_confidence,
+            main_stat_name: main_stat_name_confidence,
+            main_stat_value: main_stat_value_confidence,
+            sub_stat: [sub_stat0_confidence, sub_stat1_confidence, sub_stat2_confidence, sub_stat3_confidence],
+            level: level_confidence,
+            equip: equip_confidence,
+        };
+        let low_confidence_fields = confidence.low_confidence_fields(self.config.min_confidence);
+        if !low_confidence_fields.is_empty() {
+            warn!("识别置信度较低，建议人工核对: {} {:?}", str_title, low_confidence_fields);
+        }
 
-        Ok(GenshinArtifactScanResult {
+        let result = GenshinArtifactScanResult {
             name: str_title,
             main_stat_name: str_main_stat_name,
             main_stat_value: str_main_stat_value,
@@ -311,29 +422,105 @@ impl ArtifactScannerWorker {
             equip: str_equip,
             star: item.star as i32,
             lock,
-        })
+            description: str_description,
+            confidence,
+        };
+        if let Some((count, expected)) = result.sub_stat_count_mismatch() {
+            warn!(
+                "副词条数量与星级/等级不符，可能识别有误: {} {}星 +{} 识别到 {} 条，应为 {}~{} 条",
+                result.name, result.star, result.level, count, expected.start(), expected.end()
+            );
+        }
+
+        Ok(Some(result))
     }
 
     /// Get all lock state from a list image (list-view grid). Used for auto-lock: only click lock when list says not locked.
-    fn get_page_locks(&self, list_image: &RgbImage) -> Vec<bool> {
-        get_page_locks_from_list_image(list_image, &self.window_info, None)
+    fn get_page_locks(&self, list_image: &RgbImage) -> Vec<LockCell> {
+        self.timed("lock detection", || {
+            get_page_locks_from_list_image(list_image, &self.window_info, self.config.partial_row_ratio, None)
+        })
+    }
+
+    /// Run `f` as `stage` of the timing breakdown logged with `--verbose`, see `ScanTimings`
+    fn timed<T>(&self, stage: &'static str, f: impl FnOnce() -> T) -> T {
+        if !self.config.verbose {
+            return f();
+        }
+        let start = std::time::Instant::now();
+        let result = f();
+        self.timings.borrow_mut().record(stage, start.elapsed());
+        result
     }
 
     /// Run the worker. If `result_tx` is Some, send each scan result (or None on error) so the main thread can e.g. auto-lock.
     pub fn run(
-        self,
+        mut self,
         rx: Receiver<Option<SendItem>>,
         result_tx: Option<Sender<Option<GenshinArtifactScanResult>>>,
     ) -> JoinHandle<Vec<GenshinArtifactScanResult>> {
         std::thread::spawn(move || {
             let mut results = Vec::new();
-            let mut hash: HashSet<GenshinArtifactScanResult> = HashSet::new();
+            let mut dedup = Deduplicator::new(self.config.dedup_mode, self.config.dedup_epsilon);
+            // items before this were handled by the resumed scan, seeing them again is expected
+            let mut resumed_index = 0;
+            if let Some(checkpoint) = self.resume.take() {
+                info!("从进度文件继续，已有 {} 个结果，从第 {} 个物品开始", checkpoint.results.len(), self.start_index + 1);
+                resumed_index = checkpoint.next_index;
+                for result in checkpoint.results {
+                    dedup.insert(&result, GenshinArtifact::try_from(&result).ok().as_ref());
+                    results.push(result);
+                }
+            }
             let mut consecutive_dup_count = 0;
             let is_verbose = self.config.verbose;
             let min_level = self.config.min_level;
+            let max_level = self.config.max_level;
             let info = self.window_info.clone();
-            let mut locks = Vec::new();
-            let mut artifact_index: i32 = 0;
+            // lock states of the current page, whose first item is the `page_start`-th (starting from 0)
+            let mut page_locks = Vec::new();
+            let mut page_start = self.start_index as i32;
+            let mut artifact_index = self.start_index as i32;
+            let mut unparsed_lines = UnparsedLines::new();
+            let mut log_throttle = LogThrottle::new(std::time::Duration::from_millis(self.config.log_interval_ms));
+
+            let mut auto_saver = if self.config.auto_save_every > 0 || self.config.auto_save_interval_secs > 0 {
+                let path = self.config.auto_save_path.clone()
+                    .unwrap_or_else(|| std::env::temp_dir().join("yas_artifact_autosave.json"));
+                Some(AutoSaver::new(
+                    path,
+                    self.config.auto_save_every,
+                    std::time::Duration::from_secs(self.config.auto_save_interval_secs),
+                ))
+            } else {
+                None
+            };
+
+            let mut checkpoint_writer = self.config.checkpoint_path.as_ref()
+                .map(|path| CheckpointWriter::new(path.clone(), self.config.checkpoint_every));
+
+            let mut rejects = match self.config.rejects_dir.as_ref().map(|dir| RejectsWriter::new(dir.clone())) {
+                Some(Ok(writer)) => Some(writer),
+                Some(Err(e)) => {
+                    warn!("无法创建失败物品目录: {}", e);
+                    None
+                },
+                None => None,
+            };
+
+            let mut result_stream = match self.config.stream_output_path.as_ref() {
+                Some(path) => match ResultStream::open(path) {
+                    Ok(stream) => {
+                        info!("识别结果将实时写入 {:?}", path);
+                        Some(stream)
+                    },
+                    Err(e) => {
+                        warn!("无法打开实时输出文件: {}", e);
+                        None
+                    },
+                },
+                None => None,
+            };
 
             let send_result = |tx: &Option<Sender<Option<GenshinArtifactScanResult>>>, r: Option<GenshinArtifactScanResult>| {
                 if let Some(t) = tx {
@@ -347,28 +534,60 @@ impl ArtifactScannerWorker {
                     None => break,
                 };
 
-                match item.list_image.as_ref() {
-                    Some(v) => locks = vec![locks, self.get_page_locks(v)].concat(),
-                    None => {},
-                };
+                if let Some(v) = item.list_image.as_ref() {
+                    page_locks = self.get_page_locks(v);
+                    page_start = artifact_index;
+                }
 
-                artifact_index += 1;
-                let result = match self.scan_item_image(item, locks[artifact_index as usize - 1]) {
-                    Ok(v) => v,
+                // a retry is the same item as before
+                if !item.retry {
+                    // every item before this one is done
+                    if let Some(writer) = checkpoint_writer.as_mut() {
+                        if let Err(e) = writer.maybe_save(artifact_index as usize, &results) {
+                            warn!("保存扫描进度失败: {}", e);
+                        }
+                    }
+                    artifact_index += 1;
+                }
+                let seq = item.seq;
+                // kept for the rejects report, the item goes to recognition
+                let panel_image = rejects.as_ref().map(|_| item.panel_image.clone());
+                let offset = (artifact_index - 1 - page_start).max(0) as usize;
+                let lock = lock_at(&page_locks, offset, info.col as usize);
+                let result = match self.timed("recognize item", || self.scan_item_image(item, lock)) {
+                    Ok(Some(v)) => v,
+                    Ok(None) => {
+                        warn!("第 {} 个物品名称识别为空，已跳过", seq + 1);
+                        self.events.send(ScanEvent::ParseFailed { index: (artifact_index - 1) as usize });
+                        send_result(&result_tx, None);
+                        continue;
+                    },
                     Err(e) => {
-                        error!("识别错误: {}", e);
+                        error!("第 {} 个物品识别错误: {}", seq + 1, e);
+                        if let (Some(writer), Some(image)) = (rejects.as_mut(), panel_image.as_ref()) {
+                            if let Err(e) = writer.save(seq, image, None, &format!("{:#}", e)) {
+                                warn!("保存失败物品失败: {}", e);
+                            }
+                        }
+                        self.events.send(ScanEvent::ParseFailed { index: (artifact_index - 1) as usize });
                         send_result(&result_tx, None);
                         continue;
                     },
                 };
 
+                self.events.send(ScanEvent::Scanned { index: (artifact_index - 1) as usize, result: result.clone() });
                 send_result(&result_tx, Some(result.clone()));
 
-                if is_verbose {
+                if is_verbose && log_throttle.should_log(log::Level::Info) {
+                    let suppressed = log_throttle.take_suppressed();
+                    if suppressed > 0 {
+                        info!("（省略 {} 条日志）", suppressed);
+                    }
                     info!("{:?}", result);
                 }
 
-                if result.level < min_level {
+                // a level that could not be read does not end the scan
+                if result.level < min_level && result.confidence.level > 0.0 {
                     info!(
                         "找到满足最低等级要求 {} 的物品({})，准备退出……",
                         min_level, result.level
@@ -376,23 +595,498 @@ impl ArtifactScannerWorker {
                     break;
                 }
 
-                if hash.contains(&result) {
+                let artifact = match GenshinArtifact::from_scan_result(&result) {
+                    Ok(artifact) => Some(artifact),
+                    Err(e) => {
+                        if let (Some(writer), Some(image)) = (rejects.as_mut(), panel_image.as_ref()) {
+                            if let Err(e) = writer.save(seq, image, Some(&result), &e.to_string()) {
+                                warn!("保存失败物品失败: {}", e);
+                            }
+                        }
+                        None
+                    },
+                };
+                if !in_set_filter(artifact.as_ref(), &self.config.set_filter) {
+                    // left out before dedup, so it neither counts as a duplicate nor breaks a run of them
+                    continue;
+                }
+
+                let is_dup = dedup.contains(&result, artifact.as_ref());
+
+                if is_dup && (artifact_index as usize) <= resumed_index {
+                    // the rest of the resumed page, already in the checkpoint
+                    continue;
+                }
+
+                if is_dup {
                     consecutive_dup_count += 1;
-                    warn!("识别到重复物品: {:#?}", result);
+                    if log_throttle.should_log(log::Level::Warn) {
+                        warn!("识别到重复物品: {:#?}", result);
+                    }
                 } else {
                     consecutive_dup_count = 0;
-                    hash.insert(result.clone());
-                    results.push(result);
+                    unparsed_lines.collect(&result);
+                    dedup.insert(&result, artifact.as_ref());
+                    // still remembered above, so that duplicates of it are detected when turning pages
+                    if result.level <= max_level {
+                        if let Some(stream) = result_stream.as_mut() {
+                            if let Err(e) = stream.append(&result) {
+                                warn!("实时输出写入失败: {}", e);
+                            }
+                        }
+                        results.push(result);
+                    }
+
+                    if let Some(saver) = auto_saver.as_mut() {
+                        if let Err(e) = saver.maybe_save(&results) {
+                            warn!("自动保存失败: {}", e);
+                        }
+                    }
                 }
 
-                if consecutive_dup_count >= info.col && !self.config.ignore_dup {
-                    error!("识别到连续多个重复物品，可能为翻页错误，或者为非背包顶部开始扫描");
+                if should_break_on_dup(consecutive_dup_count, info.col, &self.config) {
+                    if self.config.ignore_dup {
+                        error!("已忽略重复物品，但连续识别到 {} 个重复物品，可能为翻页错误，停止扫描", consecutive_dup_count);
+                    } else {
+                        error!("识别到连续多个重复物品，可能为翻页错误，或者为非背包顶部开始扫描");
+                    }
                     break;
                 }
             }
 
-            info!("识别结束，非重复物品数量: {}", hash.len());
+            // the main thread may be waiting for a result to auto-lock, let it know nothing more is coming
+            drop(result_tx);
+
+            if let Some(writer) = checkpoint_writer.as_mut() {
+                if let Err(e) = writer.save(artifact_index as usize, &results) {
+                    warn!("保存扫描进度失败: {}", e);
+                }
+            }
+
+            if let Some(saver) = auto_saver {
+                if let Err(e) = saver.finish() {
+                    warn!("删除自动保存文件失败: {}", e);
+                }
+            }
+
+            if let Some(path) = self.config.unparsed_output.as_ref() {
+                match unparsed_lines.write_to(path) {
+                    Ok(()) => info!("{} 条无法解析的词条已保存至 {:?}", unparsed_lines.len(), path),
+                    Err(e) => warn!("保存无法解析的词条失败: {}", e),
+                }
+            }
+
+            self.timings.borrow().log("识别耗时");
+            if let Some(writer) = rejects.as_ref() {
+                if let Some(dir) = self.config.rejects_dir.as_ref() {
+                    info!("{} 个识别或解析失败的物品已保存至 {:?}", writer.count(), dir);
+                }
+            }
+
+            info!("识别结束，非重复物品数量: {}", dedup.len());
+            self.events.send(ScanEvent::Finished { unique: dedup.len() });
             results
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yas::positioning::Size;
+    use yas::utils::locks_flat;
+
+    use crate::fixtures;
+    use crate::scanner_controller::repository_layout::GenshinRepositoryScannerLogicConfig;
+
+    fn list_window_info(row: i32, col: i32) -> ArtifactScannerWindowInfo {
+        ArtifactScannerWindowInfo {
+            row,
+            col,
+            item_gap_size: Size::new(20.0, 20.0),
+            item_size: Size::new(102.0, 126.0),
+            list_lock_color: Rgb([255, 138, 117]),
+            list_lock_threshold: 30.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ignore_dup_safety_break() {
+        let config = GenshinArtifactScannerConfig::builder()
+            .ignore_dup(true)
+            .ignore_dup_max_run(50)
+            .build()
+            .unwrap();
+
+        // a full row of duplicates no longer stops the scan
+        assert!(!should_break_on_dup(8, 8, &config));
+        assert!(!should_break_on_dup(49, 8, &config));
+        // but a very long run still does
+        assert!(should_break_on_dup(50, 8, &config));
+
+        let config = GenshinArtifactScannerConfig::builder().build().unwrap();
+        assert!(!should_break_on_dup(7, 8, &config));
+        assert!(should_break_on_dup(8, 8, &config));
+
+        let config = GenshinArtifactScannerConfig::builder().number(20).build().unwrap();
+        assert!(!should_break_on_dup(8, 8, &config));
+    }
+
+    /// Reads every field as the same stat line and counts how often it runs
+    struct CountingOCR(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl ImageToText<RgbImage> for CountingOCR {
+        fn image_to_text(&self, _image: &RgbImage, _is_preprocessed: bool) -> Result<String> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(String::from("暴击率+3.9%"))
+        }
+
+        fn get_average_inference_time(&self) -> Option<std::time::Duration> {
+            None
+        }
+    }
+
+    /// A panel where every field has content except the level and, if `blank_fourth`, the fourth substat
+    fn scripted_panel(blank_fourth: bool) -> (ArtifactScannerWindowInfo, RgbImage) {
+        let line = |i: usize| Rect::new(0.0, i as f64 * 20.0, 100.0, 10.0);
+        let window_info = ArtifactScannerWindowInfo {
+            panel_rect: Rect::new(0.0, 0.0, 100.0, 200.0),
+            title_rect: line(0),
+            main_stat_name_rect: line(1),
+            main_stat_value_rect: line(2),
+            level_rect: line(3),
+            sub_stat_1: line(4),
+            sub_stat_2: line(5),
+            sub_stat_3: line(6),
+            sub_stat_4: line(7),
+            item_equip_rect: line(8),
+            ..Default::default()
+        };
+        let blank_rows = if blank_fourth { vec![3, 7] } else { vec![3] };
+        let panel = RgbImage::from_fn(100, 200, |x, y| {
+            let row = y as usize / 20;
+            if blank_rows.contains(&row) || (x + y) % 2 == 0 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+        (window_info, panel)
+    }
+
+    /// OCR runs on `scripted_panel`
+    fn count_ocr_runs(blank_fourth: bool) -> usize {
+        let (window_info, panel) = scripted_panel(blank_fourth);
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let worker = ArtifactScannerWorker::with_model(
+            window_info,
+            GenshinArtifactScannerConfig::builder().build().unwrap(),
+            Box::new(CountingOCR(runs.clone())),
+        );
+        let result = worker.scan_panel_image(&panel, false).unwrap();
+        if blank_fourth {
+            assert_eq!(result.sub_stat[3], "");
+        }
+        runs.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_blank_fourth_sub_stat_skips_ocr() {
+        // title, main stat name and value, three substats and equip
+        assert_eq!(count_ocr_runs(true), 7);
+        assert!(count_ocr_runs(false) > 7);
+    }
+
+    #[test]
+    fn test_blank_panel_rejected() {
+        let (window_info, _) = scripted_panel(false);
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let worker = ArtifactScannerWorker::with_model(
+            window_info,
+            GenshinArtifactScannerConfig::builder().build().unwrap(),
+            Box::new(CountingOCR(runs.clone())),
+        );
+
+        let black = RgbImage::new(100, 200);
+        let err = worker.scan_panel_image(&black, false).unwrap_err();
+        assert!(err.to_string().contains("遮挡"));
+        assert!(worker.scan_panel_image(&RgbImage::new(0, 0), false).is_err());
+        // nothing was read from them
+        assert_eq!(runs.load(std::sync::atomic::Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_three_sub_stat_panel() {
+        // the fourth line is panel background with a little texture, not flat black
+        let (window_info, mut panel) = scripted_panel(false);
+        for y in 140..150 {
+            for x in 0..100 {
+                panel.put_pixel(x, y, Rgb([60 + ((x * 7 + y * 3) % 5) as u8, 58, 52]));
+            }
+        }
+        assert!(luma_std_dev(&panel.view(0, 140, 100, 10).to_image()) < BLANK_LUMA_STD_DEV);
+
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let worker = ArtifactScannerWorker::with_model(
+            window_info,
+            GenshinArtifactScannerConfig::builder().build().unwrap(),
+            Box::new(CountingOCR(runs.clone())),
+        );
+        let result = worker.scan_panel_image(&panel, false).unwrap();
+        assert_eq!(result.sub_stat[3], "");
+        // no OCR and no pending-line retry of the fourth line
+        assert_eq!(runs.load(std::sync::atomic::Ordering::Relaxed), 7);
+    }
+
+    #[test]
+    fn test_gray_fourth_sub_stat_is_pending() {
+        // a 待激活 line: light gray strokes on the light panel background
+        let (window_info, mut panel) = scripted_panel(false);
+        for y in 140..150 {
+            for x in 0..100 {
+                let color = if (2..8).contains(&(y - 140)) && x % 6 < 2 { [158, 158, 158] } else { [236, 229, 216] };
+                panel.put_pixel(x, y, Rgb(color));
+            }
+        }
+        assert!(is_gray_text(&panel.view(0, 140, 100, 10).to_image()));
+
+        // the model does not read the suffix
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let worker = ArtifactScannerWorker::with_model(
+            window_info,
+            GenshinArtifactScannerConfig::builder().build().unwrap(),
+            Box::new(CountingOCR(runs.clone())),
+        );
+        let result = worker.scan_panel_image(&panel, false).unwrap();
+        let stat = ArtifactStat::from_zh_cn_sub_raw(&result.sub_stat[3]).unwrap();
+        assert!(stat.pending);
+        // read once, with the pending-line preprocess only
+        assert_eq!(runs.load(std::sync::atomic::Ordering::Relaxed), 8);
+
+        let stat = ArtifactStat::from_zh_cn_sub_raw(&result.sub_stat[2]).unwrap();
+        assert!(!stat.pending);
+    }
+
+    #[test]
+    fn test_run_with_injected_model() {
+        let (window_info, panel) = scripted_panel(true);
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let worker = ArtifactScannerWorker::with_model(
+            window_info,
+            GenshinArtifactScannerConfig::builder().build().unwrap(),
+            Box::new(CountingOCR(runs.clone())),
+        );
+        let (events_tx, events_rx) = std::sync::mpsc::channel();
+        let worker = worker.with_events(ScanEventSender::new(Some(events_tx)));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = worker.run(rx, None);
+        tx.send(Some(SendItem { panel_image: panel, star: 5, list_image: None, seq: 0, retry: false })).unwrap();
+        tx.send(None).unwrap();
+
+        let results = handle.join().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "暴击率+3.9%");
+        assert_eq!(results[0].star, 5);
+        assert!(runs.load(std::sync::atomic::Ordering::Relaxed) > 0);
+
+        let events = events_rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(events, vec![
+            ScanEvent::Scanned { index: 0, result: results[0].clone() },
+            ScanEvent::Finished { unique: 1 },
+        ]);
+    }
+
+    /// OCR runs of the worker for `items` copies of one panel, in a list of 2 columns
+    fn count_ocr_runs_of_duplicates(config: GenshinArtifactScannerConfig, items: usize) -> usize {
+        let (window_info, panel) = scripted_panel(true);
+        let window_info = ArtifactScannerWindowInfo { col: 2, ..window_info };
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let worker = ArtifactScannerWorker::with_model(window_info, config, Box::new(CountingOCR(runs.clone())));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = worker.run(rx, None);
+        for seq in 0..items {
+            let _ = tx.send(Some(SendItem { panel_image: panel.clone(), star: 5, list_image: None, seq, retry: false }));
+        }
+        let _ = tx.send(None);
+
+        let results = handle.join().unwrap();
+        assert_eq!(results.len(), 1);
+        runs.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_number_scans_through_duplicates() {
+        // a row of duplicates stops the scan, as for a paging error
+        let config = GenshinArtifactScannerConfig::builder().build().unwrap();
+        assert_eq!(count_ocr_runs_of_duplicates(config, 6), 3 * 7);
+
+        // every one of the given number of items is recognized
+        let config = GenshinArtifactScannerConfig::builder().number(6).build().unwrap();
+        assert_eq!(count_ocr_runs_of_duplicates(config, 6), 6 * 7);
+    }
+
+    #[test]
+    fn test_run_skips_item_on_ocr_timeout() {
+        struct SlowOCR;
+
+        impl ImageToText<RgbImage> for SlowOCR {
+            fn image_to_text(&self, _image: &RgbImage, _is_preprocessed: bool) -> Result<String> {
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                Ok(String::from("暴击率+3.9%"))
+            }
+
+            fn get_average_inference_time(&self) -> Option<std::time::Duration> {
+                None
+            }
+        }
+
+        let (window_info, panel) = scripted_panel(true);
+        let config = GenshinArtifactScannerConfig::builder().ocr_timeout_ms(50).build().unwrap();
+        let (events_tx, events_rx) = std::sync::mpsc::channel();
+        let worker = ArtifactScannerWorker::with_model(window_info, config, Box::new(SlowOCR))
+            .with_events(ScanEventSender::new(Some(events_tx)));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (result_tx, result_rx) = std::sync::mpsc::channel();
+        let handle = worker.run(rx, Some(result_tx));
+        tx.send(Some(SendItem { panel_image: panel, star: 5, list_image: None, seq: 0, retry: false })).unwrap();
+        tx.send(None).unwrap();
+
+        assert!(handle.join().unwrap().is_empty());
+        assert_eq!(result_rx.recv().unwrap(), None);
+        assert_eq!(events_rx.try_iter().next(), Some(ScanEvent::ParseFailed { index: 0 }));
+    }
+
+    #[test]
+    fn test_set_filter() {
+        let result = fixtures::scan_result();
+        let artifact = GenshinArtifact::try_from(&result).ok();
+        assert!(artifact.is_some());
+
+        assert!(in_set_filter(artifact.as_ref(), &[]));
+        assert!(in_set_filter(None, &[]));
+        assert!(in_set_filter(artifact.as_ref(), &[ArtifactSetName::WandererTroupe, ArtifactSetName::GladiatorFinale]));
+        assert!(!in_set_filter(artifact.as_ref(), &[ArtifactSetName::WandererTroupe]));
+        assert!(!in_set_filter(None, &[ArtifactSetName::GladiatorFinale]));
+    }
+
+    #[test]
+    fn test_empty_level_defaults_to_zero() {
+        assert_eq!(parse_level_or_default("", false).unwrap(), 0);
+        assert_eq!(parse_level_or_default(" ", false).unwrap(), 0);
+        assert_eq!(parse_level_or_default("+20", false).unwrap(), 20);
+        assert!(parse_level_or_default("", true).is_err());
+        assert!(parse_level_or_default("+", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("+20").unwrap(), 20);
+        assert_eq!(parse_level("+0").unwrap(), 0);
+        // no '+' prefix
+        assert_eq!(parse_level("16").unwrap(), 16);
+        // stray characters before the '+'
+        assert_eq!(parse_level("l+20").unwrap(), 20);
+        assert_eq!(parse_level("+ 4").unwrap(), 4);
+        // OCR confusions
+        assert_eq!(parse_level("+2O").unwrap(), 20);
+        assert_eq!(parse_level("+l2").unwrap(), 12);
+        // out of range
+        assert_eq!(parse_level("+80").unwrap(), 20);
+        // garbage
+        let err = parse_level("+级").unwrap_err();
+        assert!(err.to_string().contains("no digits in level"));
+        assert!(parse_level("abc").is_err());
+    }
+
+    #[test]
+    fn test_grid_override() {
+        let mut info = list_window_info(2, 1);
+        let image = RgbImage::new(400, 600);
+        assert_eq!(info.page_size(), 2);
+        assert_eq!(get_page_locks_from_list_image(&image, &info, 0.75, None).len(), 2);
+
+        let config = GenshinRepositoryScannerLogicConfig {
+            grid_rows: Some(3),
+            grid_cols: Some(2),
+            ..Default::default()
+        };
+        info.override_grid(&config);
+
+        assert_eq!((info.row, info.col), (3, 2));
+        assert_eq!(info.page_size(), 6);
+        assert_eq!(get_page_locks_from_list_image(&image, &info, 0.75, None).len(), 6);
+    }
+
+    #[test]
+    fn test_lock_detection_silent_at_info() {
+        use std::sync::Mutex;
+        use std::thread::ThreadId;
+
+        /// Records of the test threads, other tests may log at the same time
+        struct CaptureLogger(Mutex<Vec<(ThreadId, log::Level)>>);
+
+        impl log::Log for CaptureLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                self.0.lock().unwrap().push((std::thread::current().id(), record.level()));
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let info = list_window_info(2, 1);
+        let image = RgbImage::new(400, 600);
+        get_page_locks_from_list_image(&image, &info, 0.75, None);
+
+        let levels: Vec<log::Level> = LOGGER.0.lock().unwrap().iter()
+            .filter(|(thread, _)| *thread == std::thread::current().id())
+            .map(|(_, level)| *level)
+            .collect();
+        assert!(!levels.is_empty());
+        assert!(levels.iter().all(|level| *level > log::Level::Info));
+    }
+
+    #[test]
+    fn test_lock_color_from_window_info() {
+        let mut info = list_window_info(1, 2);
+        // a dimmer lock icon in the second cell
+        let mut image = RgbImage::new(244, 126);
+        image.put_pixel(122 + 19, 93 - 4, Rgb([230, 120, 105]));
+
+        assert_eq!(locks_flat(&get_page_locks_from_list_image(&image, &info, 0.75, None)), vec![false, false]);
+        assert_eq!(sample_lock_color(&image, &info, 0, 1), Some((Rgb([230, 120, 105]), 1093)));
+
+        info.list_lock_color = Rgb([230, 120, 105]);
+        assert_eq!(locks_flat(&get_page_locks_from_list_image(&image, &info, 0.75, None)), vec![false, true]);
+
+        info.list_lock_color = Rgb([255, 138, 117]);
+        info.list_lock_threshold = 1300.0;
+        assert_eq!(locks_flat(&get_page_locks_from_list_image(&image, &info, 0.75, None)), vec![false, true]);
+    }
+
+    #[test]
+    fn test_partial_bottom_row_included() {
+        let info = list_window_info(2, 1);
+        // the second row starts at 146 and 101 of its 126 pixels (~80%) are visible
+        let mut image = RgbImage::new(122, 247);
+        image.put_pixel(19, 146 + 93, Rgb([255, 138, 117]));
+
+        let locks = get_page_locks_from_list_image(&image, &info, 0.75, None);
+        assert_eq!(locks_flat(&locks), vec![false, true]);
+        assert_eq!(locks[1], LockCell { row: 1, col: 0, locked: true });
+
+        let locks = get_page_locks_from_list_image(&image, &info, 0.9, None);
+        assert_eq!(locks_flat(&locks), vec![false]);
+    }
+}
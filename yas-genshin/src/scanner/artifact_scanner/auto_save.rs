@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use log::info;
+use serde::Serialize;
+
+/// Periodically writes the results of a running scan to a file, so that a crash near the end does not lose everything.
+/// The file is removed once the scan finishes normally
+pub struct AutoSaver {
+    path: PathBuf,
+    /// save after this many new results, 0 to disable
+    every: usize,
+    /// save when this much time passed since the last save, zero to disable
+    interval: Duration,
+    last_saved_count: usize,
+    last_saved_at: Instant,
+}
+
+impl AutoSaver {
+    pub fn new(path: PathBuf, every: usize, interval: Duration) -> Self {
+        AutoSaver {
+            path,
+            every,
+            interval,
+            last_saved_count: 0,
+            last_saved_at: Instant::now(),
+        }
+    }
+
+    fn is_due(&self, count: usize) -> bool {
+        if count == self.last_saved_count {
+            return false;
+        }
+
+        let by_count = self.every > 0 && count - self.last_saved_count >= self.every;
+        let by_time = !self.interval.is_zero() && self.last_saved_at.elapsed() >= self.interval;
+        by_count || by_time
+    }
+
+    /// Write all `results` if enough results or time accumulated since the last save. Returns whether it saved
+    pub fn maybe_save<T: Serialize>(&mut self, results: &[T]) -> Result<bool> {
+        if !self.is_due(results.len()) {
+            return Ok(false);
+        }
+
+        let contents = serde_json::to_string(results)?;
+        std::fs::write(&self.path, contents)
+            .with_context(|| format!("auto save to {:?}", self.path))?;
+        if self.last_saved_count == 0 {
+            info!("扫描结果将定期自动保存至 {:?}", self.path);
+        }
+
+        self.last_saved_count = results.len();
+        self.last_saved_at = Instant::now();
+        Ok(true)
+    }
+
+    /// Remove the auto-save file, call when the scan completed
+    pub fn finish(self) -> Result<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)
+                .with_context(|| format!("remove auto save {:?}", self.path))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_save_after_n_results_and_cleanup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("auto_save.json");
+        let mut saver = AutoSaver::new(path.clone(), 2, Duration::ZERO);
+
+        assert!(!saver.maybe_save(&[1]).unwrap());
+        assert!(!path.exists());
+
+        assert!(saver.maybe_save(&[1, 2]).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1,2]");
+
+        assert!(!saver.maybe_save(&[1, 2, 3]).unwrap());
+        assert!(saver.maybe_save(&[1, 2, 3, 4]).unwrap());
+
+        saver.finish().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+
+/// The state of an interrupted scan, written by `--checkpoint` and read back by `--resume`.
+///
+/// Schema (JSON):
+/// ```json
+/// {
+///   "version": 1,
+///   "next_index": 120,
+///   "results": [ { "name": "...", "main_stat_name": "...", ... } ]
+/// }
+/// ```
+/// - `version`: schema version, currently 1
+/// - `next_index`: number of list items already handled, the scan resumes from the page this index is on
+/// - `results`: the scan results collected so far, in the same form as `--stream-output` lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    pub version: u32,
+    pub next_index: usize,
+    pub results: Vec<GenshinArtifactScanResult>,
+}
+
+impl ScanCheckpoint {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(next_index: usize, results: Vec<GenshinArtifactScanResult>) -> Self {
+        ScanCheckpoint {
+            version: Self::VERSION,
+            next_index,
+            results,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("read checkpoint {:?}", path))?;
+        let checkpoint: ScanCheckpoint = serde_json::from_str(&contents)
+            .with_context(|| format!("parse checkpoint {:?}", path))?;
+        if checkpoint.version != Self::VERSION {
+            return Err(anyhow!("unsupported checkpoint version {} in {:?}", checkpoint.version, path));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Written to a temporary file first, so a crash while saving keeps the previous checkpoint
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)
+            .with_context(|| format!("write checkpoint {:?}", tmp))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("write checkpoint {:?}", path))
+    }
+
+    /// The first item of the page `next_index` is on, with `page_size` items per page.
+    /// Resuming from a page start keeps the list images of every later page aligned
+    pub fn resume_index(&self, page_size: usize) -> usize {
+        if page_size == 0 {
+            return 0;
+        }
+        self.next_index / page_size * page_size
+    }
+}
+
+/// Writes a checkpoint after every `every` handled items
+pub struct CheckpointWriter {
+    path: PathBuf,
+    every: usize,
+    last_saved_index: usize,
+}
+
+impl CheckpointWriter {
+    pub fn new(path: PathBuf, every: usize) -> Self {
+        CheckpointWriter {
+            path,
+            every: every.max(1),
+            last_saved_index: 0,
+        }
+    }
+
+    /// Save if at least `every` items were handled since the last save. Returns whether it saved
+    pub fn maybe_save(&mut self, next_index: usize, results: &[GenshinArtifactScanResult]) -> Result<bool> {
+        if next_index < self.last_saved_index + self.every {
+            return Ok(false);
+        }
+        self.save(next_index, results)?;
+        Ok(true)
+    }
+
+    pub fn save(&mut self, next_index: usize, results: &[GenshinArtifactScanResult]) -> Result<()> {
+        ScanCheckpoint::new(next_index, results.to_vec()).save(&self.path)?;
+        if self.last_saved_index == 0 {
+            info!("扫描进度将定期保存至 {:?}，可使用 --resume 继续", self.path);
+        }
+        self.last_saved_index = next_index;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn scan_result(level: i32) -> GenshinArtifactScanResult {
+        GenshinArtifactScanResult {
+            level,
+            ..fixtures::scan_result()
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        let mut writer = CheckpointWriter::new(path.clone(), 2);
+
+        assert!(!writer.maybe_save(1, &[scan_result(0)]).unwrap());
+        assert!(!path.exists());
+        assert!(writer.maybe_save(2, &[scan_result(0), scan_result(4)]).unwrap());
+        assert!(!writer.maybe_save(3, &[scan_result(0), scan_result(4)]).unwrap());
+
+        let checkpoint = ScanCheckpoint::load(&path).unwrap();
+        assert_eq!(checkpoint, ScanCheckpoint::new(2, vec![scan_result(0), scan_result(4)]));
+    }
+
+    #[test]
+    fn test_resume_from_page_start() {
+        let checkpoint = ScanCheckpoint::new(45, Vec::new());
+        assert_eq!(checkpoint.resume_index(40), 40);
+        assert_eq!(ScanCheckpoint::new(80, Vec::new()).resume_index(40), 80);
+        assert_eq!(checkpoint.resume_index(0), 0);
+    }
+}
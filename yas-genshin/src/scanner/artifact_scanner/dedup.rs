@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+
+use crate::artifact::{ArtifactIdentity, GenshinArtifact};
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+
+/// How a scanned item is decided to be one that was seen before
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum DedupMode {
+    /// Every recognized text is the same
+    Exact,
+    /// The parsed artifacts are the same, stat values within `--dedup-epsilon`, see `ArtifactIdentity`.
+    /// Items that do not parse are still compared by their text
+    Fuzzy,
+}
+
+impl Default for DedupMode {
+    fn default() -> Self {
+        Self::Exact
+    }
+}
+
+/// The items seen so far, compared according to a `DedupMode`
+pub struct Deduplicator {
+    mode: DedupMode,
+    epsilon: f64,
+    seen: HashSet<GenshinArtifactScanResult>,
+    // the identities are not hashable, see `ArtifactIdentity`
+    identities: Vec<ArtifactIdentity>,
+}
+
+impl Deduplicator {
+    pub fn new(mode: DedupMode, epsilon: f64) -> Self {
+        Deduplicator {
+            mode,
+            epsilon,
+            seen: HashSet::new(),
+            identities: Vec::new(),
+        }
+    }
+
+    fn identity(&self, artifact: Option<&GenshinArtifact>) -> Option<ArtifactIdentity> {
+        match self.mode {
+            DedupMode::Exact => None,
+            DedupMode::Fuzzy => artifact.map(|artifact| ArtifactIdentity::with_epsilon(artifact, self.epsilon)),
+        }
+    }
+
+    /// `artifact` is `result` parsed, if it parses
+    pub fn contains(&self, result: &GenshinArtifactScanResult, artifact: Option<&GenshinArtifact>) -> bool {
+        self.seen.contains(result)
+            || self.identity(artifact).is_some_and(|id| self.identities.contains(&id))
+    }
+
+    pub fn insert(&mut self, result: &GenshinArtifactScanResult, artifact: Option<&GenshinArtifact>) {
+        self.identities.extend(self.identity(artifact));
+        self.seen.insert(result.clone());
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn scan_result(name: &str, main_stat_value: &str, crit_damage: &str) -> GenshinArtifactScanResult {
+        GenshinArtifactScanResult {
+            name: String::from(name),
+            main_stat_value: String::from(main_stat_value),
+            sub_stat: [
+                String::from("暴击率+3.5%"),
+                String::from(crit_damage),
+                String::from("攻击力+9.9%"),
+                String::from("元素精通+23"),
+            ],
+            ..fixtures::scan_result()
+        }
+    }
+
+    fn dedup_pair(mode: DedupMode, a: &GenshinArtifactScanResult, b: &GenshinArtifactScanResult) -> bool {
+        let mut dedup = Deduplicator::new(mode, 0.0005);
+        dedup.insert(a, GenshinArtifact::try_from(a).ok().as_ref());
+        dedup.contains(b, GenshinArtifact::try_from(b).ok().as_ref())
+    }
+
+    #[test]
+    fn test_fuzzy_collapses_ocr_noise() {
+        let base = scan_result("角斗士的留恋", "4,780", "暴击伤害+7.8%");
+        let noisy = [
+            // one character of the title misread
+            scan_result("角斗土的留恋", "4,780", "暴击伤害+7.8%"),
+            // the same values written differently
+            scan_result("角斗士的留恋", "4780", "暴击伤害+7.8%"),
+            scan_result("角斗士的留恋", "4,780", "暴击伤害+7.80%"),
+        ];
+
+        for other in noisy.iter() {
+            assert!(dedup_pair(DedupMode::Fuzzy, &base, other), "{:?}", other);
+            assert!(!dedup_pair(DedupMode::Exact, &base, other), "{:?}", other);
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_keeps_different_artifacts() {
+        let base = scan_result("角斗士的留恋", "4,780", "暴击伤害+7.8%");
+        let other = scan_result("角斗士的留恋", "4,780", "暴击伤害+8.6%");
+        assert!(!dedup_pair(DedupMode::Fuzzy, &base, &other));
+
+        // identical text is a duplicate in both modes, also when it does not parse
+        let unparsed = scan_result("", "", "");
+        assert!(dedup_pair(DedupMode::Exact, &unparsed, &unparsed));
+        assert!(dedup_pair(DedupMode::Fuzzy, &unparsed, &unparsed));
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use clap::ValueEnum;
+
+/// What to do with an artifact whose title is recognized as empty, so that its set cannot be determined
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum EmptyTitleBehavior {
+    /// Do not record the artifact
+    Skip,
+    /// Recognize the title once more, skip the artifact if it is still empty
+    Retry,
+    /// Record the artifact with an empty title
+    #[value(name = "record")]
+    RecordAsUnknown,
+}
+
+impl Default for EmptyTitleBehavior {
+    fn default() -> Self {
+        Self::Retry
+    }
+}
+
+impl EmptyTitleBehavior {
+    /// Decide which title to record. `retry` recognizes the title again and is called at most once.
+    /// Returns None if the artifact should not be recorded
+    pub fn resolve(&self, title: String, retry: impl FnOnce() -> Result<String>) -> Result<Option<String>> {
+        if !title.trim().is_empty() {
+            return Ok(Some(title));
+        }
+
+        match *self {
+            EmptyTitleBehavior::Skip => Ok(None),
+            EmptyTitleBehavior::RecordAsUnknown => Ok(Some(title)),
+            EmptyTitleBehavior::Retry => {
+                let title = retry()?;
+                if title.trim().is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(title))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_skip_empty_title() {
+        let retried = Cell::new(false);
+        let title = EmptyTitleBehavior::Skip.resolve(String::from(" "), || {
+            retried.set(true);
+            Ok(String::from("角斗士的留恋"))
+        }).unwrap();
+
+        assert_eq!(title, None);
+        assert!(!retried.get());
+    }
+
+    #[test]
+    fn test_retry_empty_title() {
+        let title = EmptyTitleBehavior::Retry
+            .resolve(String::new(), || Ok(String::from("角斗士的留恋")))
+            .unwrap();
+        assert_eq!(title.as_deref(), Some("角斗士的留恋"));
+
+        let title = EmptyTitleBehavior::Retry
+            .resolve(String::new(), || Ok(String::new()))
+            .unwrap();
+        assert_eq!(title, None);
+    }
+
+    #[test]
+    fn test_record_empty_title() {
+        let title = EmptyTitleBehavior::RecordAsUnknown
+            .resolve(String::new(), || Ok(String::from("角斗士的留恋")))
+            .unwrap();
+        assert_eq!(title.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_non_empty_title_kept() {
+        for behavior in [EmptyTitleBehavior::Skip, EmptyTitleBehavior::Retry, EmptyTitleBehavior::RecordAsUnknown] {
+            let title = behavior.resolve(String::from("角斗士的留恋"), || unreachable!()).unwrap();
+            assert_eq!(title.as_deref(), Some("角斗士的留恋"));
+        }
+    }
+}
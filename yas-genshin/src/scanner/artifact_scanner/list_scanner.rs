@@ -0,0 +1,170 @@
+use image::{GenericImageView, RgbImage};
+use log::warn;
+use serde::Serialize;
+
+use yas::ocr::ImageToText;
+use yas::positioning::{Pos, Rect};
+use yas::utils::{lock_at, row_visible_ratio};
+
+use crate::scanner::artifact_scanner::artifact_scanner_window_info::ArtifactScannerWindowInfo;
+use crate::scanner::artifact_scanner::artifact_scanner_worker::{get_page_locks_from_list_image, parse_level, LEVEL_CHARS};
+
+/// Where the rarity colored background of a cell is sampled, as a fraction of the cell size.
+/// The top left corner is clear of the artifact picture
+const STAR_SAMPLE: Pos<f64> = Pos { x: 0.08, y: 0.06 };
+
+/// The level badge (`+20`) at the bottom of a cell, as a fraction of the cell size
+const LEVEL_RECT: Rect<f64> = Rect { left: 0.0, top: 0.8, width: 1.0, height: 0.2 };
+
+/// What the list view shows of an item without opening its detail panel, see `--list-only`.
+/// The thumbnail has no main or sub stat text, those need the detail panel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListCellInfo {
+    /// position in the inventory, starting from 0
+    pub index: usize,
+    pub star: usize,
+    /// None if the badge could not be read
+    pub level: Option<i32>,
+    pub lock: bool,
+}
+
+fn cell_origin(window_info: &ArtifactScannerWindowInfo, r: i32, c: i32) -> Pos<f64> {
+    let gap = window_info.item_gap_size;
+    let size = window_info.item_size;
+    Pos {
+        x: (gap.width + size.width) * (c as f64),
+        y: (gap.height + size.height) * (r as f64),
+    }
+}
+
+/// Crop `rect` out of `image`, clipped to it. None if nothing of it is inside
+fn crop_clipped(image: &RgbImage, rect: Rect<f64>) -> Option<RgbImage> {
+    let left = rect.left.max(0.0) as u32;
+    let top = rect.top.max(0.0) as u32;
+    let right = ((rect.left + rect.width) as u32).min(image.width());
+    let bottom = ((rect.top + rect.height) as u32).min(image.height());
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(image.view(left, top, right - left, bottom - top).to_image())
+}
+
+/// Read the first `cells` cells (row-major) of a list image, cropped like the list image of the panel scan.
+/// The first cell is item `first_index` of the inventory. Rows cut off by the bottom of the image
+/// by more than `min_visible_ratio` are left out, so fewer than `cells` may be returned
+pub fn read_list_cells(
+    list_image: &RgbImage,
+    window_info: &ArtifactScannerWindowInfo,
+    model: &dyn ImageToText<RgbImage>,
+    first_index: usize,
+    cells: usize,
+    min_visible_ratio: f64,
+) -> Vec<ListCellInfo> {
+    let page_locks = get_page_locks_from_list_image(list_image, window_info, min_visible_ratio, None);
+    let size = window_info.item_size;
+    let col = window_info.col.max(1);
+
+    let mut result = Vec::new();
+    for offset in 0..cells {
+        let (r, c) = ((offset as i32) / col, (offset as i32) % col);
+        let origin = cell_origin(window_info, r, c);
+        if row_visible_ratio(origin.y, size.height, list_image.height()) < min_visible_ratio {
+            break;
+        }
+
+        let sample_x = (origin.x + size.width * STAR_SAMPLE.x) as u32;
+        let sample_y = (origin.y + size.height * STAR_SAMPLE.y) as u32;
+        if sample_x >= list_image.width() || sample_y >= list_image.height() {
+            break;
+        }
+        let star = window_info.match_star_with_confidence(list_image.get_pixel(sample_x, sample_y)).star;
+
+        let level_rect = Rect {
+            left: origin.x + size.width * LEVEL_RECT.left,
+            top: origin.y + size.height * LEVEL_RECT.top,
+            width: size.width * LEVEL_RECT.width,
+            height: size.height * LEVEL_RECT.height,
+        };
+        let level = crop_clipped(list_image, level_rect)
+            .ok_or_else(|| anyhow::anyhow!("level badge outside the list image"))
+            .and_then(|badge| model.image_to_text_constrained(&badge, LEVEL_CHARS))
+            .and_then(|text| parse_level(&text));
+        let level = match level {
+            Ok(level) => Some(level),
+            Err(e) => {
+                warn!("第 {} 个物品的等级无法识别: {}", first_index + offset + 1, e);
+                None
+            },
+        };
+
+        result.push(ListCellInfo {
+            index: first_index + offset,
+            star,
+            level,
+            lock: lock_at(&page_locks, offset, col as usize),
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use image::Rgb;
+    use yas::positioning::Size;
+
+    use super::*;
+
+    /// Reads the level from the badge color: white is `+20`, anything else `+4`
+    struct BadgeOCR;
+
+    impl ImageToText<RgbImage> for BadgeOCR {
+        fn image_to_text(&self, image: &RgbImage, _is_preprocessed: bool) -> Result<String> {
+            match image.get_pixel(0, 0) {
+                Rgb([255, 255, 255]) => Ok(String::from("+20")),
+                _ => Ok(String::from("+4")),
+            }
+        }
+
+        fn get_average_inference_time(&self) -> Option<std::time::Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_read_list_cells() {
+        let window_info = ArtifactScannerWindowInfo {
+            row: 2,
+            col: 2,
+            item_gap_size: Size::new(20.0, 20.0),
+            item_size: Size::new(100.0, 120.0),
+            star_color_4: Rgb([161, 86, 224]),
+            star_color_5: Rgb([188, 105, 50]),
+            list_lock_color: Rgb([255, 138, 117]),
+            list_lock_threshold: 30.0,
+            ..Default::default()
+        };
+
+        let mut image = RgbImage::from_pixel(220, 260, Rgb([188, 105, 50]));
+        // the second cell is 4 star, the first has a white badge
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            if x >= 120 && y < 120 {
+                *pixel = Rgb([161, 86, 224]);
+            }
+            if x < 100 && (96..120).contains(&y) {
+                *pixel = Rgb([255, 255, 255]);
+            }
+        }
+
+        let cells = read_list_cells(&image, &window_info, &BadgeOCR, 40, 3, 0.5);
+        assert_eq!(cells, vec![
+            ListCellInfo { index: 40, star: 5, level: Some(20), lock: false },
+            ListCellInfo { index: 41, star: 4, level: Some(4), lock: false },
+            ListCellInfo { index: 42, star: 5, level: Some(4), lock: false },
+        ]);
+
+        // the second row is cut off
+        let image = image.view(0, 0, 220, 150).to_image();
+        assert_eq!(read_list_cells(&image, &window_info, &BadgeOCR, 0, 4, 0.5).len(), 2);
+    }
+}
@@ -12,31 +12,150 @@
 //!   }
 //! ]
 //! ```
+//!
+//! `LockList::from_mona_extended` builds one from an export, e.g. with the unwanted artifacts removed by another tool.
+
+use serde::{Deserialize, Serialize};
 
-use serde::Deserialize;
+use crate::artifact::{ArtifactIdentity, ArtifactStat, GenshinArtifact};
+use crate::export::artifact::load_artifacts;
 
 use super::scan_result::GenshinArtifactScanResult;
 
+/// Parsed stats of an entry or a scan result, so that matching does not depend on how OCR rendered the text
+#[derive(Debug, Clone)]
+pub struct LockListKey {
+    pub main_stat: Option<ArtifactStat>,
+    pub sub_stat: [Option<ArtifactStat>; 4],
+}
+
+impl LockListKey {
+    pub fn new(main_stat_name: &str, main_stat_value: &str, sub_stat: &[String; 4]) -> Self {
+        LockListKey {
+            main_stat: ArtifactStat::from_zh_cn_raw(&format!("{}+{}", main_stat_name.trim(), main_stat_value.trim())),
+            sub_stat: [
+                ArtifactStat::from_zh_cn_sub_raw(&sub_stat[0]),
+                ArtifactStat::from_zh_cn_sub_raw(&sub_stat[1]),
+                ArtifactStat::from_zh_cn_sub_raw(&sub_stat[2]),
+                ArtifactStat::from_zh_cn_sub_raw(&sub_stat[3]),
+            ],
+        }
+    }
+}
+
+/// Compare two stat lines by their parsed (name, value), falling back to the trimmed text when either side does not parse
+fn stat_matches(a: Option<&ArtifactStat>, b: Option<&ArtifactStat>, raw_a: &str, raw_b: &str) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.name == b.name && (a.value - b.value).abs() <= ArtifactIdentity::DEFAULT_EPSILON,
+        _ => raw_a.trim() == raw_b.trim(),
+    }
+}
+
 /// One artifact entry in the lock list. Matches scan result by name, main_stat_name, main_stat_value, sub_stat.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LockListEntry {
     pub name: String,
     pub main_stat_name: String,
+    /// Empty when the value is not known, e.g. from a GOOD export, then only the main stat name is compared
     pub main_stat_value: String,
     /// Exactly 4 substat strings, order matters.
     pub sub_stat: [String; 4],
+    /// Parsed form of the stats above, filled when the list is loaded
+    #[serde(skip)]
+    pub key: Option<LockListKey>,
+}
+
+impl LockListEntry {
+    /// The entry of `artifact`, whose piece is called `name` (e.g. "杰作的序曲"), with stats written as the game shows them
+    pub fn from_artifact(name: &str, artifact: &GenshinArtifact) -> Self {
+        let sub_stat_raw = |stat: &Option<ArtifactStat>| stat.as_ref().map(|s| s.to_zh_cn_raw()).unwrap_or_default();
+        LockListEntry {
+            name: String::from(name),
+            main_stat_name: String::from(artifact.main_stat.name.to_zh_cn()),
+            main_stat_value: artifact.main_stat.value_to_zh_cn_raw(),
+            sub_stat: [
+                sub_stat_raw(&artifact.sub_stat_1),
+                sub_stat_raw(&artifact.sub_stat_2),
+                sub_stat_raw(&artifact.sub_stat_3),
+                sub_stat_raw(&artifact.sub_stat_4),
+            ],
+            key: None,
+        }
+    }
+
+    fn key(&self) -> LockListKey {
+        match &self.key {
+            Some(key) => key.clone(),
+            None => LockListKey::new(&self.main_stat_name, &self.main_stat_value, &self.sub_stat),
+        }
+    }
+
+    /// True if the scan result has the same name and the same parsed stats as this entry
+    pub fn matches_normalized(&self, r: &GenshinArtifactScanResult, r_key: &LockListKey) -> bool {
+        if self.name.trim() != r.name.trim() {
+            return false;
+        }
+
+        let key = self.key();
+        if self.main_stat_value.trim().is_empty() {
+            if self.main_stat_name.trim() != r.main_stat_name.trim() {
+                return false;
+            }
+        } else {
+            let main_raw = format!("{}+{}", self.main_stat_name.trim(), self.main_stat_value.trim());
+            let r_main_raw = format!("{}+{}", r.main_stat_name.trim(), r.main_stat_value.trim());
+            if !stat_matches(key.main_stat.as_ref(), r_key.main_stat.as_ref(), &main_raw, &r_main_raw) {
+                return false;
+            }
+        }
+
+        (0..4).all(|i| stat_matches(
+            key.sub_stat[i].as_ref(),
+            r_key.sub_stat[i].as_ref(),
+            &self.sub_stat[i],
+            &r.sub_stat[i],
+        ))
+    }
 }
 
 /// Lock list JSON: array of artifacts to lock (mona_extended-style keys).
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LockList(pub Vec<LockListEntry>);
 
 impl LockList {
+    /// One entry per artifact of a Mona (extended) export. Also accepts the other formats `load_artifacts` reads,
+    /// but GOOD keeps no main stat value (it is imported as 0), so its entries leave it empty
+    /// and match the main stat by name only
+    pub fn from_mona_extended(path: &std::path::Path) -> anyhow::Result<Self> {
+        let artifacts = load_artifacts(path)?;
+        let mut list = Vec::with_capacity(artifacts.len());
+        for artifact in artifacts.iter() {
+            let name = artifact.set_name.piece_name_zh_cn(&artifact.slot)
+                .ok_or_else(|| anyhow::anyhow!("no piece name for {:?} {:?}", artifact.set_name, artifact.slot))?;
+            let mut entry = LockListEntry::from_artifact(name, artifact);
+            if artifact.main_stat.value == 0.0 {
+                entry.main_stat_value = String::new();
+            }
+            entry.key = Some(entry.key());
+            list.push(entry);
+        }
+        Ok(LockList(list))
+    }
+
+    /// Write as lock list JSON, readable by `from_json_path`
+    pub fn save(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let s = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, s).with_context(|| format!("write lock list: {}", path.display()))
+    }
+
     pub fn from_json_path(path: &std::path::Path) -> anyhow::Result<Self> {
         let s = std::fs::read_to_string(path)
             .with_context(|| format!("read lock list: {}", path.display()))?;
-        let list: Vec<LockListEntry> = serde_json::from_str(&s)
+        let mut list: Vec<LockListEntry> = serde_json::from_str(&s)
             .with_context(|| "parse lock list JSON (expected array of { name, main_stat_name, main_stat_value, sub_stat: [4] })")?;
+        for entry in list.iter_mut() {
+            entry.key = Some(LockListKey::new(&entry.main_stat_name, &entry.main_stat_value, &entry.sub_stat));
+        }
         Ok(LockList(list))
     }
 
@@ -52,7 +171,196 @@ impl LockList {
                 && e.sub_stat[3].trim() == r.sub_stat[3].trim()
         })
     }
+
+    /// Like `contains`, but compares stats by their parsed (name, value) instead of the OCR text,
+    /// e.g. "暴击伤害+7.8%" matches "暴击伤害 +7.80%". Lines that do not parse are compared as text.
+    pub fn contains_normalized(&self, r: &GenshinArtifactScanResult) -> bool {
+        let r_key = LockListKey::new(&r.main_stat_name, &r.main_stat_value, &r.sub_stat);
+        self.0.iter().any(|e| e.matches_normalized(r, &r_key))
+    }
+}
+
+/// Why a matching scan result must still not be locked, None if it is safe to lock.
+/// A misread stat can match the wrong entry, so every field has to parse and the fields compared
+/// by `matches_normalized` have to be read with at least `min_confidence`
+pub fn lock_skip_reason(r: &GenshinArtifactScanResult, min_confidence: f32) -> Option<String> {
+    if let Err(e) = r.try_into_artifact() {
+        return Some(e.to_string());
+    }
+
+    let uncertain = r.confidence.low_confidence_fields(min_confidence)
+        .into_iter()
+        .filter(|field| !matches!(*field, "level" | "equip"))
+        .collect::<Vec<_>>();
+    if !uncertain.is_empty() {
+        return Some(format!("low confidence: {}", uncertain.join(", ")));
+    }
+
+    None
 }
 
 // for with_context
 use anyhow::Context;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn entry() -> LockListEntry {
+        LockListEntry {
+            name: String::from("杰作的序曲"),
+            main_stat_name: String::from("攻击力"),
+            main_stat_value: String::from("311"),
+            sub_stat: [
+                String::from("生命值+15.2%"),
+                String::from("暴击伤害+7.8%"),
+                String::from("防御力+65"),
+                String::new(),
+            ],
+            key: None,
+        }
+    }
+
+    fn scan_result(crit_damage: &str) -> GenshinArtifactScanResult {
+        GenshinArtifactScanResult {
+            name: String::from("杰作的序曲 "),
+            main_stat_name: String::from("攻击力"),
+            main_stat_value: String::from(" 311"),
+            sub_stat: [
+                String::from("生命值+15.2%"),
+                String::from(crit_damage),
+                String::from("防御力 +65"),
+                String::new(),
+            ],
+            ..fixtures::scan_result()
+        }
+    }
+
+    #[test]
+    fn test_contains_normalized() {
+        let list = LockList(vec![entry()]);
+
+        let r = scan_result("暴击伤害 +7.80%");
+        assert!(!list.contains(&r));
+        assert!(list.contains_normalized(&r));
+
+        assert!(!list.contains_normalized(&scan_result("暴击伤害+6.2%")));
+        assert!(!list.contains_normalized(&scan_result("暴击率+7.8%")));
+    }
+
+    #[test]
+    fn test_unparsed_result_never_locked() {
+        // a garbled line matches an entry with the same text, but must not be locked
+        let mut garbled = entry();
+        garbled.sub_stat[1] = String::from("暴击伤害7.8");
+        let list = LockList(vec![garbled]);
+        let r = scan_result("暴击伤害7.8");
+        assert!(list.contains_normalized(&r));
+        assert_eq!(lock_skip_reason(&r, 0.9).as_deref(), Some("bad sub stat 2 `暴击伤害7.8`"));
+
+        let r = GenshinArtifactScanResult { name: String::from("杰作的序曲"), ..scan_result("暴击伤害+7.8%") };
+        assert_eq!(lock_skip_reason(&r, 0.9), None);
+
+        let mut uncertain = r.clone();
+        uncertain.confidence.sub_stat[1] = 0.5;
+        uncertain.confidence.equip = 0.5;
+        assert_eq!(lock_skip_reason(&uncertain, 0.9).as_deref(), Some("low confidence: sub_stat_2"));
+    }
+
+    #[test]
+    fn test_from_mona_extended_round_trip() {
+        use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStatName};
+
+        let stat = |name: ArtifactStatName, value: f64| serde_json::json!({ "name": name.to_mona(), "value": value });
+        let export = serde_json::json!({
+            "version": "1",
+            "flower": [],
+            "feather": [{
+                "setName": ArtifactSetName::MarechausseeHunter.to_mona(),
+                "position": ArtifactSlot::Feather.to_mona(),
+                "mainTag": stat(ArtifactStatName::Atk, 311.0),
+                "normalTags": [
+                    stat(ArtifactStatName::HpPercentage, 0.152),
+                    stat(ArtifactStatName::CriticalDamage, 0.078),
+                    stat(ArtifactStatName::Def, 65.0),
+                ],
+                "omit": false,
+                "level": 20,
+                "star": 5,
+                "equip": null,
+            }],
+            "sand": [],
+            "cup": [],
+            "head": [],
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let export_path = dir.path().join("mona.json");
+        std::fs::write(&export_path, export.to_string()).unwrap();
+
+        let list = LockList::from_mona_extended(&export_path).unwrap();
+        assert_eq!(list.0.len(), 1);
+        assert_eq!(list.0[0].name, "杰作的序曲");
+        assert!(list.contains_normalized(&scan_result("暴击伤害+7.8%")));
+
+        // and back through the lock list file
+        let list_path = dir.path().join("lock_list.json");
+        list.save(&list_path).unwrap();
+        let reloaded = LockList::from_json_path(&list_path).unwrap();
+        assert!(reloaded.contains_normalized(&scan_result("暴击伤害+7.8%")));
+        assert!(!reloaded.contains_normalized(&scan_result("暴击伤害+6.2%")));
+    }
+
+    #[test]
+    fn test_from_good_round_trip() {
+        use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStatName};
+
+        let stat = |name: ArtifactStatName, value: f64| serde_json::json!({ "key": name.to_good(), "value": value });
+        let export = serde_json::json!({
+            "format": "GOOD",
+            "version": 1,
+            "source": "yas",
+            "artifacts": [{
+                "setKey": ArtifactSetName::MarechausseeHunter.to_good(),
+                "slotKey": ArtifactSlot::Feather.to_good(),
+                "level": 20,
+                "rarity": 5,
+                "mainStatKey": ArtifactStatName::Atk.to_good(),
+                "location": "",
+                "lock": false,
+                "substats": [
+                    stat(ArtifactStatName::HpPercentage, 15.2),
+                    stat(ArtifactStatName::CriticalDamage, 7.8),
+                    stat(ArtifactStatName::Def, 65.0),
+                ],
+            }],
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let export_path = dir.path().join("good.json");
+        std::fs::write(&export_path, export.to_string()).unwrap();
+
+        // GOOD has no main stat value, the entry still matches a real scan
+        let list = LockList::from_mona_extended(&export_path).unwrap();
+        assert_eq!(list.0[0].main_stat_value, "");
+        assert!(list.contains_normalized(&scan_result("暴击伤害+7.8%")));
+        assert!(!list.contains_normalized(&scan_result("暴击伤害+6.2%")));
+        let other_main = GenshinArtifactScanResult { main_stat_name: String::from("生命值"), ..scan_result("暴击伤害+7.8%") };
+        assert!(!list.contains_normalized(&other_main));
+
+        let list_path = dir.path().join("lock_list.json");
+        list.save(&list_path).unwrap();
+        let reloaded = LockList::from_json_path(&list_path).unwrap();
+        assert!(reloaded.contains_normalized(&scan_result("暴击伤害+7.8%")));
+    }
+
+    #[test]
+    fn test_entry_from_artifact() {
+        let r = GenshinArtifactScanResult { name: String::from("杰作的序曲"), ..scan_result("暴击伤害+7.8%") };
+        let artifact = GenshinArtifact::try_from(&r).unwrap();
+        let generated = LockListEntry::from_artifact("杰作的序曲", &artifact);
+
+        assert_eq!(generated.main_stat_value, "311");
+        assert_eq!(generated.sub_stat, entry().sub_stat);
+        assert!(LockList(vec![generated]).contains_normalized(&r));
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+use log::Level;
+
+/// Keeps per-item log lines readable on huge scans: at most one line below `Level::Error` per `interval`,
+/// errors always go through. A zero interval lets every line through
+pub struct LogThrottle {
+    interval: Duration,
+    last: Option<Instant>,
+    /// lines dropped since the last one let through
+    suppressed: usize,
+}
+
+impl LogThrottle {
+    pub fn new(interval: Duration) -> Self {
+        LogThrottle {
+            interval,
+            last: None,
+            suppressed: 0,
+        }
+    }
+
+    pub fn should_log(&mut self, level: Level) -> bool {
+        self.should_log_at(level, Instant::now())
+    }
+
+    fn should_log_at(&mut self, level: Level, now: Instant) -> bool {
+        if level == Level::Error || self.interval.is_zero() {
+            return true;
+        }
+
+        match self.last {
+            Some(last) if now.duration_since(last) < self.interval => {
+                self.suppressed += 1;
+                false
+            },
+            _ => {
+                self.last = Some(now);
+                true
+            },
+        }
+    }
+
+    /// Lines dropped since the last one let through, resets the count
+    pub fn take_suppressed(&mut self) -> usize {
+        std::mem::take(&mut self.suppressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_throttled_errors_not() {
+        let mut throttle = LogThrottle::new(Duration::from_millis(500));
+        let start = Instant::now();
+
+        assert!(throttle.should_log_at(Level::Info, start));
+        assert!(!throttle.should_log_at(Level::Info, start + Duration::from_millis(100)));
+        assert!(!throttle.should_log_at(Level::Warn, start + Duration::from_millis(200)));
+        assert!(throttle.should_log_at(Level::Error, start + Duration::from_millis(300)));
+        assert!(throttle.should_log_at(Level::Error, start + Duration::from_millis(301)));
+        assert_eq!(throttle.take_suppressed(), 2);
+
+        assert!(throttle.should_log_at(Level::Info, start + Duration::from_millis(500)));
+        assert_eq!(throttle.take_suppressed(), 0);
+
+        let mut unlimited = LogThrottle::new(Duration::ZERO);
+        assert!(unlimited.should_log_at(Level::Info, start));
+        assert!(unlimited.should_log_at(Level::Info, start));
+    }
+}
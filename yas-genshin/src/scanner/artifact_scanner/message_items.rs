@@ -5,4 +5,9 @@ pub struct SendItem {
     pub panel_image: RgbImage,
     pub star: usize,
     pub list_image: Option<RgbImage>,
+    /// index of the item in the bag, starting from 0 and counting the items skipped by `--resume`.
+    /// A retry keeps the number of the item it captures again
+    pub seq: usize,
+    /// a new capture of the previous item, whose recognition failed
+    pub retry: bool,
 }
@@ -1,13 +1,41 @@
 pub use artifact_scanner::GenshinArtifactScanner;
-pub use artifact_scanner_config::GenshinArtifactScannerConfig;
-pub use artifact_scanner_worker::{get_page_locks_from_list_image, ArtifactScannerWorker};
-pub use artifact_scanner_window_info::ArtifactScannerWindowInfo;
-pub use scan_result::GenshinArtifactScanResult;
+pub(crate) use artifact_scanner_worker::{crop_panel_rect, get_image_to_text};
+pub use checkpoint::ScanCheckpoint;
+pub use artifact_scanner_config::{GenshinArtifactScannerConfig, GenshinArtifactScannerConfigBuilder};
+pub use artifact_scanner_worker::{get_page_locks_from_list_image, sample_lock_color, ArtifactScannerWorker};
+pub use yas::utils::{locks_flat, LockCell};
+pub use artifact_scanner_window_info::{ArtifactScannerWindowInfo, StarMatch};
+pub use dedup::DedupMode;
+pub use empty_title::EmptyTitleBehavior;
+pub use list_scanner::ListCellInfo;
+pub use offline_scanner::{crop_panel_image, OfflineArtifactScanner};
+pub use result_stream::{read_result_stream, ResultStream};
+pub use scan_event::ScanEvent;
+pub use scan_result::{GenshinArtifactScanResult, ScanConfidence};
+pub use scan_summary::ScanSummary;
+pub use scan_timings::ScanTimings;
+pub use star_detect::StarDetectMode;
 
 mod artifact_scanner;
 mod artifact_scanner_config;
 mod lock_list;
 mod message_items;
 mod scan_result;
+mod scan_event;
 mod artifact_scanner_worker;
 mod artifact_scanner_window_info;
+mod offline_scanner;
+mod list_scanner;
+mod empty_title;
+mod dedup;
+mod star_detect;
+mod pending_detect;
+mod auto_save;
+mod checkpoint;
+mod scan_summary;
+mod scan_timings;
+mod unparsed_lines;
+mod rejects;
+mod result_stream;
+mod log_throttle;
+mod timed_model;
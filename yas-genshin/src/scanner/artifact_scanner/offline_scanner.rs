@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use image::{GenericImageView, RgbImage};
+use log::{error, info, warn};
+
+use yas::game_info::{Platform, UI};
+use yas::positioning::Size;
+use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
+
+use crate::scanner::artifact_scanner::artifact_scanner_window_info::ArtifactScannerWindowInfo;
+use crate::scanner::artifact_scanner::artifact_scanner_worker::ArtifactScannerWorker;
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+use crate::scanner::artifact_scanner::GenshinArtifactScannerConfig;
+
+/// Scans saved full-window screenshots instead of a running game.
+/// The layout is picked per image from its dimensions, one worker is kept per resolution
+pub struct OfflineArtifactScanner {
+    window_info_repo: WindowInfoRepository,
+    config: GenshinArtifactScannerConfig,
+    workers: HashMap<(u32, u32), (ArtifactScannerWindowInfo, ArtifactScannerWorker)>,
+}
+
+fn is_screenshot_file(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => matches!(ext.to_lowercase().as_str(), "png" | "jpg" | "jpeg" | "bmp"),
+        None => false,
+    }
+}
+
+/// Crop the artifact panel out of a full-window image
+pub fn crop_panel_image(image: &RgbImage, window_info: &ArtifactScannerWindowInfo) -> Result<RgbImage> {
+    let rect = &window_info.panel_rect;
+    let (x, y, w, h) = (rect.left as u32, rect.top as u32, rect.width as u32, rect.height as u32);
+
+    if x + w > image.width() || y + h > image.height() {
+        anyhow::bail!(
+            "panel rect ({}, {}, {}, {}) out of image bounds {}x{}",
+            x, y, w, h,
+            image.width(), image.height()
+        );
+    }
+
+    Ok(image.view(x, y, w, h).to_image())
+}
+
+impl OfflineArtifactScanner {
+    pub fn new(window_info_repo: WindowInfoRepository, config: GenshinArtifactScannerConfig) -> Self {
+        OfflineArtifactScanner {
+            window_info_repo,
+            config,
+            workers: HashMap::new(),
+        }
+    }
+
+    /// The layout for a screenshot of this size, None if no profile matches it (even after scaling)
+    fn window_info_for_size(&self, width: u32, height: u32) -> Option<ArtifactScannerWindowInfo> {
+        let size = Size {
+            width: width as usize,
+            height: height as usize,
+        };
+        ArtifactScannerWindowInfo::from_window_info_repository(
+            size,
+            UI::Desktop,
+            Platform::Windows,
+            &self.window_info_repo,
+        ).ok()
+    }
+
+    /// Scan one full-window screenshot. Returns None if its resolution has no known profile
+    pub fn scan_screenshot(&mut self, image: &RgbImage) -> Result<Option<GenshinArtifactScanResult>> {
+        let key = image.dimensions();
+
+        if !self.workers.contains_key(&key) {
+            let window_info = match self.window_info_for_size(key.0, key.1) {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            let worker = ArtifactScannerWorker::new(window_info.clone(), self.config.clone())?;
+            self.workers.insert(key, (window_info, worker));
+        }
+
+        let (window_info, worker) = &self.workers[&key];
+        let panel_image = crop_panel_image(image, window_info)?;
+        worker.scan_panel_image(&panel_image, false).map(Some)
+    }
+
+    /// Scan every screenshot in `dir` in file name order.
+    /// Files whose resolution has no known profile, or that fail to be recognized, are skipped with a log
+    pub fn scan_from_screenshot_dir(&mut self, dir: &Path) -> Result<Vec<GenshinArtifactScanResult>> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .with_context(|| format!("read screenshot dir {:?}", dir))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_screenshot_file(path))
+            .collect();
+        paths.sort();
+
+        info!("共 {} 张截图", paths.len());
+
+        let mut results = Vec::new();
+        for path in paths.iter() {
+            let image = match image::open(path) {
+                Ok(v) => v.to_rgb8(),
+                Err(e) => {
+                    warn!("无法读取截图 {:?}: {}", path, e);
+                    continue;
+                },
+            };
+
+            match self.scan_screenshot(&image) {
+                Ok(Some(result)) => {
+                    if self.config.verbose {
+                        info!("{:?}: {:?}", path, result);
+                    }
+                    results.push(result);
+                },
+                Ok(None) => warn!("截图 {:?} 的分辨率 {}x{} 没有对应的配置，已跳过", path, image.width(), image.height()),
+                Err(e) => error!("识别错误 {:?}: {}", path, e),
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yas::window_info::load_window_info_repo;
+
+    use super::*;
+
+    fn scanner() -> OfflineArtifactScanner {
+        let repo = load_window_info_repo!(
+            "../../../window_info/windows1600x900.json",
+            "../../../window_info/windows1280x960.json",
+        );
+        OfflineArtifactScanner::new(repo, GenshinArtifactScannerConfig::builder().build().unwrap())
+    }
+
+    #[test]
+    fn test_window_info_for_size() {
+        let scanner = scanner();
+
+        assert!(scanner.window_info_for_size(1600, 900).is_some());
+        // same aspect ratio is scaled from 1600x900
+        let scaled = scanner.window_info_for_size(1920, 1080).unwrap();
+        assert_eq!(scaled.col, 8);
+        assert!(scanner.window_info_for_size(1000, 1000).is_none());
+    }
+
+    #[test]
+    fn test_is_screenshot_file() {
+        assert!(is_screenshot_file(Path::new("capture_0001.PNG")));
+        assert!(!is_screenshot_file(Path::new("result.json")));
+        assert!(!is_screenshot_file(Path::new("screenshots")));
+    }
+}
@@ -0,0 +1,87 @@
+use image::{Rgb, RgbImage};
+
+use super::star_detect::hue_saturation;
+
+/// Brightness difference from the background above which a pixel is taken as part of the text
+const TEXT_MIN_CONTRAST: f64 = 30.0;
+/// Normal substat text is dark slate (about #495366) on the light panel, more than 140 darker than it.
+/// Gray (待激活) text is light gray and stays well within this of the background
+const GRAY_TEXT_MAX_CONTRAST: f64 = 110.0;
+/// Gray text has no color of its own, the slate of normal text is bluish
+const GRAY_TEXT_MAX_SATURATION: f64 = 0.15;
+
+fn luma(p: &Rgb<u8>) -> f64 {
+    0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64
+}
+
+/// How the text of a line stands out of its background
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextTone {
+    /// brightness difference of the strongest text pixels (the 90th percentile) from the background
+    pub contrast: f64,
+    /// mean HSV saturation of those text pixels
+    pub saturation: f64,
+}
+
+/// Tone of the text in `image`, a crop of a single line. The background is the median brightness,
+/// a line crop being mostly background. None if nothing stands out of it
+pub fn text_tone(image: &RgbImage) -> Option<TextTone> {
+    let mut lumas: Vec<f64> = image.pixels().map(luma).collect();
+    if lumas.is_empty() {
+        return None;
+    }
+    lumas.sort_by(f64::total_cmp);
+    let background = lumas[lumas.len() / 2];
+
+    let mut text: Vec<(f64, &Rgb<u8>)> = image.pixels()
+        .map(|p| ((luma(p) - background).abs(), p))
+        .filter(|(contrast, _)| *contrast > TEXT_MIN_CONTRAST)
+        .collect();
+    if text.is_empty() {
+        return None;
+    }
+    text.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let contrast = text[(text.len() - 1) * 9 / 10].0;
+
+    // anti-aliased edges take the color of the background, only the stroke cores count
+    let core: Vec<&Rgb<u8>> = text.iter()
+        .filter(|(c, _)| *c >= contrast * 0.7)
+        .map(|(_, p)| *p)
+        .collect();
+    let saturation = core.iter().map(|p| hue_saturation(p).1).sum::<f64>() / core.len() as f64;
+
+    Some(TextTone { contrast, saturation })
+}
+
+/// Whether the line in `image` is written in the light gray of a 待激活 substat
+pub fn is_gray_text(image: &RgbImage) -> bool {
+    text_tone(image).is_some_and(|tone| {
+        tone.contrast < GRAY_TEXT_MAX_CONTRAST && tone.saturation < GRAY_TEXT_MAX_SATURATION
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A substat line on the panel background with a few vertical strokes of `text`
+    fn line_crop(text: Rgb<u8>) -> RgbImage {
+        RgbImage::from_fn(120, 20, |x, y| {
+            if (4..16).contains(&y) && x % 6 < 2 {
+                text
+            } else {
+                Rgb([236, 229, 216])
+            }
+        })
+    }
+
+    #[test]
+    fn test_is_gray_text() {
+        // 待激活
+        assert!(is_gray_text(&line_crop(Rgb([158, 158, 158]))));
+        // an active substat
+        assert!(!is_gray_text(&line_crop(Rgb([73, 83, 102]))));
+        // nothing there
+        assert!(!is_gray_text(&RgbImage::from_pixel(120, 20, Rgb([236, 229, 216]))));
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use image::RgbImage;
+use serde::Serialize;
+
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+
+#[derive(Serialize)]
+struct RejectReport<'a> {
+    /// position of the item in scan order, starting from 1
+    index: usize,
+    error: &'a str,
+    /// the recognized text, absent if recognition itself failed
+    raw: Option<&'a GenshinArtifactScanResult>,
+}
+
+/// Saves every item that could not be recognized or parsed for manual review: its panel image
+/// as `reject_{index}.png` and `reject_{index}.json` with the error and the recognized text.
+/// A failed item captured again by `--retry` overwrites its earlier report
+pub struct RejectsWriter {
+    dir: PathBuf,
+    count: usize,
+}
+
+impl RejectsWriter {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("create rejects dir {:?}", dir))?;
+        Ok(RejectsWriter { dir, count: 0 })
+    }
+
+    /// `seq` is the index of the item in the bag, starting from 0
+    pub fn save(&mut self, seq: usize, panel_image: &RgbImage, raw: Option<&GenshinArtifactScanResult>, error: &str) -> Result<()> {
+        let stem = format!("reject_{:05}", seq + 1);
+        panel_image.save(self.dir.join(format!("{}.png", stem)))
+            .with_context(|| format!("save {}.png to {:?}", stem, self.dir))?;
+
+        let report = RejectReport { index: seq + 1, error, raw };
+        std::fs::write(self.dir.join(format!("{}.json", stem)), serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("save {}.json to {:?}", stem, self.dir))?;
+
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Reports saved so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_reject_saved_with_raw_text() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("rejects");
+        let mut writer = RejectsWriter::new(dir.clone()).unwrap();
+
+        let raw = GenshinArtifactScanResult {
+            name: String::from("角斗士的留念"),
+            ..fixtures::scan_result()
+        };
+        writer.save(2, &RgbImage::new(4, 4), Some(&raw), "unknown set name 角斗士的留念").unwrap();
+        writer.save(6, &RgbImage::new(4, 4), None, "OCR title_rect").unwrap();
+
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("reject_00003.json")).unwrap()).unwrap();
+        let missing: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("reject_00007.json")).unwrap()).unwrap();
+        let image_saved = dir.join("reject_00003.png").exists();
+
+        assert_eq!(writer.count(), 2);
+        assert!(image_saved);
+        assert_eq!(report["index"], 3);
+        assert_eq!(report["error"], "unknown set name 角斗士的留念");
+        assert_eq!(report["raw"]["name"], "角斗士的留念");
+        assert!(missing["raw"].is_null());
+    }
+}
@@ -0,0 +1,83 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+
+/// Appends every result as one JSON object per line the moment it is recognized, flushed after each line.
+/// Unlike `AutoSaver` nothing is rewritten, and the file is kept after the scan
+pub struct ResultStream {
+    path: PathBuf,
+    file: File,
+}
+
+impl ResultStream {
+    /// Open `path` for appending, earlier lines are kept
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open stream output {:?}", path))?;
+        Ok(ResultStream {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    pub fn append(&mut self, result: &GenshinArtifactScanResult) -> Result<()> {
+        let line = serde_json::to_string(result)?;
+        writeln!(self.file, "{}", line)
+            .and_then(|_| self.file.flush())
+            .with_context(|| format!("write stream output {:?}", self.path))
+    }
+}
+
+/// Results in a stream output file. A line cut off by a crash is ignored
+pub fn read_result_stream(path: &Path) -> Result<Vec<GenshinArtifactScanResult>> {
+    let file = File::open(path).with_context(|| format!("open stream output {:?}", path))?;
+    let mut results = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if let Ok(result) = serde_json::from_str(&line) {
+            results.push(result);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn scan_result(level: i32) -> GenshinArtifactScanResult {
+        GenshinArtifactScanResult {
+            level,
+            ..fixtures::scan_result()
+        }
+    }
+
+    #[test]
+    fn test_each_result_on_disk_after_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+
+        let mut stream = ResultStream::open(&path).unwrap();
+        stream.append(&scan_result(4)).unwrap();
+        // readable before the stream is dropped
+        assert_eq!(read_result_stream(&path).unwrap(), vec![scan_result(4)]);
+
+        // reopening appends, and a truncated last line is skipped
+        drop(stream);
+        let mut stream = ResultStream::open(&path).unwrap();
+        stream.append(&scan_result(8)).unwrap();
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap()
+            .write_all(b"{\"name\":\"").unwrap();
+
+        let results = read_result_stream(&path).unwrap();
+        assert_eq!(results, vec![scan_result(4), scan_result(8)]);
+    }
+}
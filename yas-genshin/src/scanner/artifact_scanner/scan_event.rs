@@ -0,0 +1,36 @@
+use std::sync::mpsc::Sender;
+
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+
+/// Progress of a scan, for frontends that would otherwise have to parse the log.
+/// `index` is the position of the item in the inventory, starting from 0.
+/// An item that is re-captured after a failure may report `ParseFailed` before its `Scanned`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    /// The scan starts, `total` items will be visited at most
+    Started { total: usize },
+    /// An item was recognized, duplicates included
+    Scanned { index: usize, result: GenshinArtifactScanResult },
+    /// An item was locked by `--lock`
+    Locked { index: usize },
+    /// An item could not be recognized
+    ParseFailed { index: usize },
+    /// Recognition ended with `unique` distinct items
+    Finished { unique: usize },
+}
+
+/// Sends events if there is someone listening. A receiver that went away does not stop the scan
+#[derive(Clone, Default)]
+pub struct ScanEventSender(Option<Sender<ScanEvent>>);
+
+impl ScanEventSender {
+    pub fn new(tx: Option<Sender<ScanEvent>>) -> Self {
+        ScanEventSender(tx)
+    }
+
+    pub fn send(&self, event: ScanEvent) {
+        if let Some(tx) = self.0.as_ref() {
+            let _ = tx.send(event);
+        }
+    }
+}
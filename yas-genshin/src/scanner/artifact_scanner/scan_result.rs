@@ -1,4 +1,11 @@
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+use std::ops::RangeInclusive;
+
+use crate::artifact::{ArtifactParseError, ArtifactStat, GenshinArtifact};
+
+/// Equality and hashing, as used to find duplicates, cover the recognized text of the artifact:
+/// name, main stat, sub stats, equip, level, star and description.
+/// `lock` is left out, an artifact (auto-)locked between two reads is still the same one, and so is `confidence`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GenshinArtifactScanResult {
     pub name: String,
     pub main_stat_name: String,
@@ -8,4 +15,269 @@ pub struct GenshinArtifactScanResult {
     pub level: i32,
     pub star: i32,
     pub lock: bool,
+    /// Set name line above the set bonus description, only read when the title is not an exact piece name
+    #[serde(default)]
+    pub description: String,
+    /// How sure the OCR model was about each field, not part of the artifact's identity
+    #[serde(default)]
+    pub confidence: ScanConfidence,
+}
+
+impl GenshinArtifactScanResult {
+    fn identity(&self) -> (&str, &str, &str, &[String; 4], &str, i32, i32, &str) {
+        (
+            &self.name,
+            &self.main_stat_name,
+            &self.main_stat_value,
+            &self.sub_stat,
+            &self.equip,
+            self.level,
+            self.star,
+            &self.description,
+        )
+    }
+}
+
+impl PartialEq for GenshinArtifactScanResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for GenshinArtifactScanResult {}
+
+impl std::hash::Hash for GenshinArtifactScanResult {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
+impl GenshinArtifactScanResult {
+    /// Convert to an artifact, failing with the field that cannot be parsed.
+    /// Stricter than `TryFrom`, which leaves out sub stats it cannot parse
+    pub fn try_into_artifact(&self) -> Result<GenshinArtifact, ArtifactParseError> {
+        if !(0..=20).contains(&self.level) {
+            return Err(ArtifactParseError::BadLevel(self.level));
+        }
+        for (index, sub_stat) in self.sub_stat.iter().enumerate() {
+            if !sub_stat.trim().is_empty() && ArtifactStat::from_zh_cn_sub_raw(sub_stat).is_none() {
+                return Err(ArtifactParseError::BadSubStat(index, sub_stat.clone()));
+            }
+        }
+
+        GenshinArtifact::from_scan_result(self)
+    }
+
+    /// Sub stat lines that can be parsed, pending ones included
+    pub fn parsed_sub_stat_count(&self) -> usize {
+        self.sub_stat.iter()
+            .filter(|s| ArtifactStat::from_zh_cn_sub_raw(s).is_some())
+            .count()
+    }
+
+    /// The parsed sub stat count and the expected range, if the count does not fit the star and level.
+    /// Usually means a sub stat, the star or the level was misread
+    pub fn sub_stat_count_mismatch(&self) -> Option<(usize, RangeInclusive<usize>)> {
+        let expected = expected_sub_stat_count(self.star, self.level);
+        let count = self.parsed_sub_stat_count();
+        if expected.contains(&count) {
+            None
+        } else {
+            Some((count, expected))
+        }
+    }
+}
+
+/// How many sub stats an artifact of `star` can have at `level`.
+/// Artifacts drop with `star - 2` or `star - 1` sub stats (at least 0), and every 4 levels
+/// one is added until there are 4
+pub fn expected_sub_stat_count(star: i32, level: i32) -> RangeInclusive<usize> {
+    let initial_min = (star - 2).max(0);
+    let initial_max = (star - 1).max(0);
+    let upgrades = level.max(0) / 4;
+
+    let min = (initial_min + upgrades).min(4) as usize;
+    let max = (initial_max + upgrades).min(4) as usize;
+    min..=max
+}
+
+/// Lowest per-character confidence (0~1) of each recognized field.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ScanConfidence {
+    pub name: f32,
+    pub main_stat_name: f32,
+    pub main_stat_value: f32,
+    pub sub_stat: [f32; 4],
+    pub level: f32,
+    pub equip: f32,
+}
+
+impl Default for ScanConfidence {
+    fn default() -> Self {
+        ScanConfidence {
+            name: 1.0,
+            main_stat_name: 1.0,
+            main_stat_value: 1.0,
+            sub_stat: [1.0; 4],
+            level: 1.0,
+            equip: 1.0,
+        }
+    }
+}
+
+impl ScanConfidence {
+    /// Names of the fields recognized with a confidence below `threshold`
+    pub fn low_confidence_fields(&self, threshold: f32) -> Vec<&'static str> {
+        let fields = [
+            ("name", self.name),
+            ("main_stat_name", self.main_stat_name),
+            ("main_stat_value", self.main_stat_value),
+            ("sub_stat_1", self.sub_stat[0]),
+            ("sub_stat_2", self.sub_stat[1]),
+            ("sub_stat_3", self.sub_stat[2]),
+            ("sub_stat_4", self.sub_stat[3]),
+            ("level", self.level),
+            ("equip", self.equip),
+        ];
+        fields.iter()
+            .filter(|(_, confidence)| *confidence < threshold)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::artifact::ArtifactSetName;
+    use crate::fixtures;
+
+    use super::*;
+
+    fn scan_result(name_confidence: f32) -> GenshinArtifactScanResult {
+        GenshinArtifactScanResult {
+            sub_stat: [
+                String::from("暴击率+3.9%"),
+                String::from("暴击伤害+7.8%"),
+                String::from("攻击力+5.8%"),
+                String::from("元素充能效率+6.5%"),
+            ],
+            confidence: ScanConfidence {
+                name: name_confidence,
+                ..Default::default()
+            },
+            ..fixtures::scan_result()
+        }
+    }
+
+    #[test]
+    fn test_try_into_artifact() {
+        let artifact = scan_result(1.0).try_into_artifact().unwrap();
+        assert_eq!(artifact.set_name, ArtifactSetName::GladiatorFinale);
+        assert_eq!(artifact.level, 20);
+    }
+
+    #[test]
+    fn test_try_into_artifact_errors() {
+        let result = GenshinArtifactScanResult { name: String::from("不存在的圣遗物"), ..scan_result(1.0) };
+        assert_eq!(result.try_into_artifact().unwrap_err(), ArtifactParseError::UnknownSetName(String::from("不存在的圣遗物")));
+
+        // one character off, the set is still matched but the piece is not
+        let result = GenshinArtifactScanResult { name: String::from("角斗士的留念"), ..scan_result(1.0) };
+        assert_eq!(result.try_into_artifact().unwrap_err(), ArtifactParseError::UnknownSlot(String::from("角斗士的留念")));
+
+        let result = GenshinArtifactScanResult { main_stat_value: String::new(), ..scan_result(1.0) };
+        assert_eq!(result.try_into_artifact().unwrap_err(), ArtifactParseError::BadMainStat(String::from("生命值+")));
+
+        let mut result = scan_result(1.0);
+        result.sub_stat[2] = String::from("攻击力5.8%");
+        assert_eq!(result.try_into_artifact().unwrap_err(), ArtifactParseError::BadSubStat(2, String::from("攻击力5.8%")));
+
+        let result = GenshinArtifactScanResult { level: 21, ..scan_result(1.0) };
+        assert_eq!(result.try_into_artifact().unwrap_err(), ArtifactParseError::BadLevel(21));
+    }
+
+    #[test]
+    fn test_sub_stat_count_consistent() {
+        assert_eq!(expected_sub_stat_count(5, 0), 3..=4);
+        assert_eq!(expected_sub_stat_count(5, 4), 4..=4);
+        assert_eq!(expected_sub_stat_count(4, 0), 2..=3);
+        assert_eq!(expected_sub_stat_count(4, 16), 4..=4);
+        assert_eq!(expected_sub_stat_count(1, 0), 0..=0);
+
+        assert_eq!(scan_result(1.0).sub_stat_count_mismatch(), None);
+
+        let mut result = GenshinArtifactScanResult { level: 0, ..scan_result(1.0) };
+        result.sub_stat[3] = String::new();
+        assert_eq!(result.sub_stat_count_mismatch(), None);
+    }
+
+    #[test]
+    fn test_sub_stat_count_inconsistent() {
+        // a level 20 5 star with two sub stats, the other two were not read
+        let mut result = scan_result(1.0);
+        result.sub_stat[2] = String::from("攻击力5.8%");
+        result.sub_stat[3] = String::new();
+        assert_eq!(result.sub_stat_count_mismatch(), Some((2, 4..=4)));
+    }
+
+    #[test]
+    fn test_low_confidence_fields() {
+        let confidence = ScanConfidence {
+            main_stat_value: 0.4,
+            sub_stat: [1.0, 1.0, 0.6, 1.0],
+            ..Default::default()
+        };
+        assert_eq!(confidence.low_confidence_fields(0.8), vec!["main_stat_value", "sub_stat_3"]);
+        assert!(confidence.low_confidence_fields(0.3).is_empty());
+    }
+
+    #[test]
+    fn test_confidence_ignored_for_dedup() {
+        let mut set = HashSet::new();
+        set.insert(scan_result(0.99));
+        assert!(set.contains(&scan_result(0.5)));
+    }
+
+    #[test]
+    fn test_lock_ignored_for_dedup() {
+        let mut locked = scan_result(1.0);
+        locked.lock = true;
+        let mut unlocked = scan_result(1.0);
+        unlocked.lock = false;
+
+        let mut set = HashSet::new();
+        set.insert(locked);
+        set.insert(unlocked);
+        assert_eq!(set.len(), 1);
+
+        let mut other = scan_result(1.0);
+        other.level = 16;
+        set.insert(other);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut result = scan_result(0.5);
+        result.lock = true;
+        result.description = String::from("角斗士的终幕礼");
+
+        let line = serde_json::to_string(&result).unwrap();
+        let read: GenshinArtifactScanResult = serde_json::from_str(&line).unwrap();
+        // equality leaves these out
+        assert_eq!(read, result);
+        assert!(read.lock);
+        assert_eq!(read.confidence.name, 0.5);
+        assert_eq!(read.confidence.sub_stat, [1.0; 4]);
+
+        // lines written before description and confidence were recorded
+        let mut old: serde_json::Value = serde_json::from_str(&line).unwrap();
+        old.as_object_mut().unwrap().remove("description");
+        old.as_object_mut().unwrap().remove("confidence");
+        let read: GenshinArtifactScanResult = serde_json::from_value(old).unwrap();
+        assert_eq!(read.description, "");
+        assert_eq!(read.confidence.name, 1.0);
+    }
 }
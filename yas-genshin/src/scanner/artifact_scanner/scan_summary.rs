@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, GenshinArtifact};
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+
+/// Counts reported by `--dry-run` instead of exporting the results
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScanSummary {
+    /// items recognized, duplicates included
+    pub scanned: usize,
+    /// items in the results
+    pub unique: usize,
+    /// results that could not be converted to an artifact
+    pub parse_failures: usize,
+    /// per field, how many results have a value for it that cannot be parsed
+    pub field_failures: BTreeMap<&'static str, usize>,
+    /// star -> count
+    pub star_distribution: BTreeMap<i32, usize>,
+}
+
+impl ScanSummary {
+    pub fn new(scanned: usize, results: &[GenshinArtifactScanResult]) -> Self {
+        let mut summary = ScanSummary {
+            scanned,
+            unique: results.len(),
+            ..Default::default()
+        };
+
+        for result in results.iter() {
+            *summary.star_distribution.entry(result.star).or_insert(0) += 1;
+
+            if GenshinArtifact::try_from(result).is_err() {
+                summary.parse_failures += 1;
+            }
+            for field in Self::failed_fields(result) {
+                *summary.field_failures.entry(field).or_insert(0) += 1;
+            }
+        }
+
+        summary
+    }
+
+    fn failed_fields(result: &GenshinArtifactScanResult) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+
+        let description = Some(result.description.as_str()).filter(|s| !s.is_empty());
+        if ArtifactSetName::from_zh_cn_with_description(&result.name, description).is_none() {
+            fields.push("set");
+        }
+        if ArtifactSlot::from_zh_cn(&result.name).is_none() {
+            fields.push("slot");
+        }
+        let main_stat = format!("{}+{}", result.main_stat_name, result.main_stat_value);
+        if ArtifactStat::from_zh_cn_raw(&main_stat).is_none() {
+            fields.push("main_stat");
+        }
+
+        // a missing line is fine, low level artifacts have fewer sub stats
+        const SUB_STAT_FIELDS: [&str; 4] = ["sub_stat_1", "sub_stat_2", "sub_stat_3", "sub_stat_4"];
+        for (field, sub_stat) in SUB_STAT_FIELDS.iter().zip(result.sub_stat.iter()) {
+            if !sub_stat.trim().is_empty() && ArtifactStat::from_zh_cn_sub_raw(sub_stat).is_none() {
+                fields.push(field);
+            }
+        }
+
+        fields
+    }
+}
+
+impl Display for ScanSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "识别物品数: {}", self.scanned)?;
+        writeln!(f, "非重复物品数: {}", self.unique)?;
+        writeln!(f, "解析失败数: {}", self.parse_failures)?;
+        for (field, count) in self.field_failures.iter() {
+            writeln!(f, "  {}: {}", field, count)?;
+        }
+        writeln!(f, "星级分布:")?;
+        for (star, count) in self.star_distribution.iter() {
+            writeln!(f, "  {}★: {}", star, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn scan_result(name: &str, star: i32, sub_stat_1: &str) -> GenshinArtifactScanResult {
+        GenshinArtifactScanResult {
+            name: String::from(name),
+            sub_stat: [
+                String::from(sub_stat_1),
+                String::from("暴击伤害+7.8%"),
+                String::from("攻击力+5.8%"),
+                String::new(),
+            ],
+            star,
+            ..fixtures::scan_result()
+        }
+    }
+
+    #[test]
+    fn test_summary_counts() {
+        let results = vec![
+            scan_result("角斗士的留恋", 5, "暴击率+3.9%"),
+            scan_result("角斗士的留恋", 5, "暴击率3.9"),
+            scan_result("魔女的炎之花", 4, "暴击率+3.9%"),
+        ];
+        let summary = ScanSummary::new(5, &results);
+
+        assert_eq!(summary.scanned, 5);
+        assert_eq!(summary.unique, 3);
+        assert_eq!(summary.parse_failures, 0);
+        assert_eq!(summary.field_failures.get("sub_stat_1"), Some(&1));
+        assert_eq!(summary.field_failures.get("sub_stat_4"), None);
+        assert_eq!(summary.star_distribution.get(&5), Some(&2));
+        assert_eq!(summary.star_distribution.get(&4), Some(&1));
+    }
+}
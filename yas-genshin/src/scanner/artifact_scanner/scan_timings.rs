@@ -0,0 +1,105 @@
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// Time spent per stage of a scan, e.g. capture or the OCR of one field, for performance tuning with `--verbose`.
+/// Disabled, nothing is measured or kept
+#[derive(Debug, Default, Clone)]
+pub struct ScanTimings {
+    enabled: bool,
+    /// in the order the stages were first recorded
+    stages: Vec<(&'static str, Vec<Duration>)>,
+}
+
+impl ScanTimings {
+    pub fn new(enabled: bool) -> Self {
+        ScanTimings {
+            enabled,
+            stages: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, stage: &'static str, duration: Duration) {
+        if !self.enabled {
+            return;
+        }
+        match self.stages.iter_mut().find(|(name, _)| *name == stage) {
+            Some((_, durations)) => durations.push(duration),
+            None => self.stages.push((stage, vec![duration])),
+        }
+    }
+
+    /// Run `f`, recording how long it took as `stage`
+    pub fn time<T>(&mut self, stage: &'static str, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+        let start = Instant::now();
+        let result = f();
+        self.record(stage, start.elapsed());
+        result
+    }
+
+    /// Mean and 95th percentile of `stage`, None if it was never recorded
+    pub fn stats(&self, stage: &str) -> Option<(Duration, Duration)> {
+        let (_, durations) = self.stages.iter().find(|(name, _)| *name == stage)?;
+        let mut sorted = durations.clone();
+        sorted.sort();
+        let mean = sorted.iter().sum::<Duration>() / sorted.len() as u32;
+        let p95 = sorted[(sorted.len() * 95).div_ceil(100) - 1];
+        Some((mean, p95))
+    }
+
+    /// Log the breakdown under `title`, if anything was recorded
+    pub fn log(&self, title: &str) {
+        if self.stages.is_empty() {
+            return;
+        }
+        log::info!("{}：", title);
+        for line in self.to_string().lines() {
+            log::info!("{}", line);
+        }
+    }
+}
+
+impl Display for ScanTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (stage, durations) in self.stages.iter() {
+            let (mean, p95) = self.stats(stage).unwrap();
+            writeln!(
+                f,
+                "{}: {} 次，平均 {:.1} ms，p95 {:.1} ms",
+                stage,
+                durations.len(),
+                mean.as_secs_f64() * 1000.0,
+                p95.as_secs_f64() * 1000.0,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_stats() {
+        let mut timings = ScanTimings::new(true);
+        for ms in 1..=20 {
+            timings.record("capture", Duration::from_millis(ms));
+        }
+        timings.record("OCR title", Duration::from_millis(30));
+
+        assert_eq!(timings.stats("capture"), Some((Duration::from_micros(10500), Duration::from_millis(19))));
+        assert_eq!(timings.stats("OCR title"), Some((Duration::from_millis(30), Duration::from_millis(30))));
+        assert_eq!(timings.stats("lock"), None);
+        assert_eq!(timings.to_string().lines().collect::<Vec<_>>(), vec![
+            "capture: 20 次，平均 10.5 ms，p95 19.0 ms",
+            "OCR title: 1 次，平均 30.0 ms，p95 30.0 ms",
+        ]);
+
+        let mut disabled = ScanTimings::new(false);
+        assert_eq!(disabled.time("capture", || 1), 1);
+        assert!(disabled.to_string().is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+use clap::ValueEnum;
+use image::Rgb;
+
+/// How the star is read from the color sampled at `star_pos`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum StarDetectMode {
+    /// The reference color nearest in RGB, within each star's threshold
+    Nearest,
+    /// The reference color nearest in hue, the gray 1 star by its low saturation.
+    /// Both stay the same when gamma or HDR makes the whole screen brighter or darker
+    Hue,
+}
+
+impl Default for StarDetectMode {
+    fn default() -> Self {
+        Self::Nearest
+    }
+}
+
+/// Hue (0~360) and saturation (0~1) of `color` in HSV
+pub fn hue_saturation(color: &Rgb<u8>) -> (f64, f64) {
+    let [r, g, b] = color.0.map(|c| c as f64);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, delta / max)
+}
+
+/// Distance between two hues in degrees, going the shorter way around
+pub fn hue_distance(a: f64, b: f64) -> f64 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hue_saturation() {
+        assert_eq!(hue_saturation(&Rgb([255, 0, 0])), (0.0, 1.0));
+        assert_eq!(hue_saturation(&Rgb([0, 0, 255])), (240.0, 1.0));
+        assert_eq!(hue_saturation(&Rgb([80, 80, 80])), (0.0, 0.0));
+        assert_eq!(hue_distance(350.0, 10.0), 20.0);
+        assert_eq!(hue_distance(10.0, 350.0), 20.0);
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::mpsc::{self, Sender, SyncSender, TrySendError};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+
+use yas::ocr::ImageToText;
+
+enum Line {
+    Normal { is_preprocessed: bool },
+    Pending,
+    Constrained { allowed_chars: String },
+}
+
+struct Request {
+    image: RgbImage,
+    line: Line,
+    reply: Sender<Result<(String, f32)>>,
+}
+
+/// Runs a model on a thread of its own, so that a call gives up after `timeout` instead of blocking forever
+/// when an inference hangs. The hung inference keeps its thread, and while it does every later call fails at once
+pub struct TimedImageToText {
+    requests: SyncSender<Request>,
+    timeout: Duration,
+}
+
+impl TimedImageToText {
+    pub fn new(model: Box<dyn ImageToText<RgbImage> + Send>, timeout: Duration) -> Self {
+        // a single waiting request, a hung model must not queue up every later item
+        let (requests, rx) = mpsc::sync_channel::<Request>(1);
+        std::thread::spawn(move || {
+            for request in rx.into_iter() {
+                let result = match request.line {
+                    Line::Normal { is_preprocessed } => model.image_to_text_with_confidence(&request.image, is_preprocessed),
+                    Line::Pending => model.image_to_text_pending_line_with_confidence(&request.image),
+                    Line::Constrained { allowed_chars } => {
+                        model.image_to_text_constrained_with_confidence(&request.image, &allowed_chars)
+                    },
+                };
+                // the caller may have timed out already
+                let _ = request.reply.send(result);
+            }
+        });
+
+        TimedImageToText {
+            requests,
+            timeout,
+        }
+    }
+
+    fn run(&self, image: &RgbImage, line: Line) -> Result<(String, f32)> {
+        let (reply, rx) = mpsc::channel();
+        let request = Request {
+            image: image.clone(),
+            line,
+            reply,
+        };
+        match self.requests.try_send(request) {
+            Ok(()) => {},
+            Err(TrySendError::Full(_)) => return Err(anyhow!("OCR 推理线程仍被之前超时的识别占用")),
+            Err(TrySendError::Disconnected(_)) => return Err(anyhow!("OCR 推理线程已退出")),
+        }
+
+        match rx.recv_timeout(self.timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(anyhow!("OCR 推理超时（{} ms）", self.timeout.as_millis())),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(anyhow!("OCR 推理线程已退出")),
+        }
+    }
+}
+
+impl ImageToText<RgbImage> for TimedImageToText {
+    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
+        Ok(self.image_to_text_with_confidence(image, is_preprocessed)?.0)
+    }
+
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<(String, f32)> {
+        self.run(image, Line::Normal { is_preprocessed })
+    }
+
+    fn image_to_text_pending_line(&self, image: &RgbImage) -> Result<String> {
+        Ok(self.image_to_text_pending_line_with_confidence(image)?.0)
+    }
+
+    fn image_to_text_pending_line_with_confidence(&self, image: &RgbImage) -> Result<(String, f32)> {
+        self.run(image, Line::Pending)
+    }
+
+    fn image_to_text_constrained_with_confidence(&self, image: &RgbImage, allowed_chars: &str) -> Result<(String, f32)> {
+        self.run(image, Line::Constrained { allowed_chars: String::from(allowed_chars) })
+    }
+
+    /// The model is on another thread, its timing is not available here
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SlowOCR(Duration);
+
+    impl ImageToText<RgbImage> for SlowOCR {
+        fn image_to_text(&self, _image: &RgbImage, _is_preprocessed: bool) -> Result<String> {
+            std::thread::sleep(self.0);
+            Ok(String::from("暴击率+3.9%"))
+        }
+
+        fn get_average_inference_time(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_slow_inference_times_out() {
+        let image = RgbImage::new(4, 4);
+
+        let fast = TimedImageToText::new(Box::new(SlowOCR(Duration::ZERO)), Duration::from_secs(5));
+        assert_eq!(fast.image_to_text(&image, false).unwrap(), "暴击率+3.9%");
+        assert_eq!(fast.image_to_text_pending_line(&image).unwrap(), "暴击率+3.9%");
+
+        let slow = TimedImageToText::new(Box::new(SlowOCR(Duration::from_millis(500))), Duration::from_millis(50));
+        let err = slow.image_to_text(&image, false).unwrap_err();
+        assert!(err.to_string().contains("超时"));
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::artifact::ArtifactStat;
+use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
+
+/// Recognized stat lines that the parser rejects, labeled with the rect they were read from.
+/// Useful to find gaps in the parser or cases the OCR model gets wrong
+#[derive(Debug, Default)]
+pub struct UnparsedLines {
+    lines: Vec<(&'static str, String)>,
+}
+
+impl UnparsedLines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record_if_unparsed(&mut self, label: &'static str, text: &str, parse: fn(&str) -> Option<ArtifactStat>) {
+        if !text.trim().is_empty() && parse(text).is_none() {
+            self.lines.push((label, text.to_string()));
+        }
+    }
+
+    /// Remember the stat lines of `result` that cannot be parsed
+    pub fn collect(&mut self, result: &GenshinArtifactScanResult) {
+        let main_stat = format!("{}+{}", result.main_stat_name, result.main_stat_value);
+        self.record_if_unparsed("main_stat", &main_stat, ArtifactStat::from_zh_cn_raw);
+
+        const SUB_STAT_LABELS: [&str; 4] = ["sub_stat_1", "sub_stat_2", "sub_stat_3", "sub_stat_4"];
+        for (label, text) in SUB_STAT_LABELS.iter().zip(result.sub_stat.iter()) {
+            self.record_if_unparsed(label, text, ArtifactStat::from_zh_cn_sub_raw);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// One `rect<TAB>text` per line
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        let contents: String = self.lines.iter()
+            .map(|(label, text)| format!("{}\t{}\n", label, text))
+            .collect();
+        std::fs::write(path, contents)
+            .with_context(|| format!("write unparsed lines to {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_unparsed_line_written_with_label() {
+        let result = GenshinArtifactScanResult {
+            sub_stat: [
+                String::from("暴击率+3.9%"),
+                String::from("暴击伤害7.8%"),
+                String::from("攻击力+5.8%"),
+                String::new(),
+            ],
+            ..fixtures::scan_result()
+        };
+
+        let mut unparsed = UnparsedLines::new();
+        unparsed.collect(&result);
+        assert_eq!(unparsed.len(), 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("unparsed.txt");
+        unparsed.write_to(&path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+
+        assert_eq!(contents, "sub_stat_2\t暴击伤害7.8%\n");
+    }
+}
@@ -1,9 +1,14 @@
 pub use artifact_scanner::GenshinArtifactScanner;
-pub use artifact_scanner::GenshinArtifactScannerConfig;
-pub use artifact_scanner::GenshinArtifactScanResult;
+pub use artifact_scanner::{GenshinArtifactScannerConfig, GenshinArtifactScannerConfigBuilder};
+pub use artifact_scanner::{GenshinArtifactScanResult, ScanCheckpoint, ScanConfidence, ScanEvent, ScanSummary};
 pub use artifact_scanner::ArtifactScannerWindowInfo;
-pub use artifact_scanner::{get_page_locks_from_list_image, ArtifactScannerWorker};
+pub use artifact_scanner::EmptyTitleBehavior;
+pub use artifact_scanner::ListCellInfo;
+pub use artifact_scanner::{get_page_locks_from_list_image, locks_flat, sample_lock_color, ArtifactScannerWorker, LockCell};
+pub use artifact_scanner::{crop_panel_image, OfflineArtifactScanner};
+pub use weapon_scanner::{GenshinWeaponScanResult, GenshinWeaponScanner, GenshinWeaponScannerConfig, WeaponScannerWindowInfo};
 
 mod artifact_scanner;
+mod weapon_scanner;
 // mod item_scanner;
 
@@ -0,0 +1,10 @@
+/// The recognized text of one weapon panel, see `GenshinWeapon` for the parsed form
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GenshinWeaponScanResult {
+    pub name: String,
+    /// the level line, e.g. `Lv.80/90`
+    pub level: String,
+    /// the refinement line, e.g. `精炼1阶`
+    pub refinement: String,
+    pub equip: String,
+}
@@ -0,0 +1,169 @@
+use std::cell::RefCell;
+use std::ops::{Coroutine, CoroutineState};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use log::{error, info, warn};
+
+use yas::capture::Capturer;
+use yas::game_info::GameInfo;
+use yas::ocr::{preprocess_result, ImageToText};
+use yas::positioning::Rect;
+use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
+
+use crate::scanner::artifact_scanner::{crop_panel_rect, get_image_to_text};
+use crate::scanner::GenshinArtifactScannerConfig;
+use crate::scanner_controller::repository_layout::{
+    GenshinRepositoryScanController, GenshinRepositoryScannerLogicConfig,
+    ReturnResult as GenshinRepositoryControllerReturnResult,
+};
+
+use super::scan_result::GenshinWeaponScanResult;
+use super::weapon_scanner_config::GenshinWeaponScannerConfig;
+use super::weapon_scanner_window_info::WeaponScannerWindowInfo;
+
+/// Title of the weapon inventory tab before its item count, e.g. `武器 215/2000`
+const ITEM_COUNT_KEYWORD: &str = "武器";
+
+/// Opens every weapon of the inventory like `GenshinArtifactScanner` does for artifacts,
+/// and reads name, level, refinement and equip from its detail panel.
+/// Weapons are few, so recognition runs between page turns instead of on a thread of its own
+pub struct GenshinWeaponScanner {
+    scanner_config: GenshinWeaponScannerConfig,
+    window_info: WeaponScannerWindowInfo,
+    game_info: GameInfo,
+    image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
+    controller: Rc<RefCell<GenshinRepositoryScanController>>,
+    capturer: Rc<dyn Capturer<RgbImage>>,
+}
+
+impl GenshinWeaponScanner {
+    pub const MAX_COUNT: usize = 2000;
+
+    pub fn new(
+        window_info_repo: &WindowInfoRepository,
+        config: GenshinWeaponScannerConfig,
+        controller_config: GenshinRepositoryScannerLogicConfig,
+        game_info: GameInfo,
+    ) -> Result<Self> {
+        let window_info = WeaponScannerWindowInfo::from_window_info_repository(
+            game_info.window.to_rect_usize().size(),
+            game_info.ui,
+            game_info.platform,
+            window_info_repo,
+        )?;
+        let model_config = GenshinArtifactScannerConfig::builder()
+            .model_dir(config.model_dir.clone())
+            .build()?;
+
+        Ok(Self {
+            window_info,
+            image_to_text: get_image_to_text(&model_config)?,
+            capturer: controller_config.capturer("weapon")?,
+            controller: Rc::new(RefCell::new(GenshinRepositoryScanController::new(
+                window_info_repo,
+                controller_config,
+                game_info.clone(),
+                false,
+            )?)),
+            scanner_config: config,
+            game_info,
+        })
+    }
+
+    pub fn capture_panel(&self) -> Result<RgbImage> {
+        self.capturer.capture_relative_to(
+            self.window_info.panel_rect.to_rect_i32(),
+            self.game_info.window.origin(),
+        )
+    }
+
+    /// Capture one panel and save it to `path` with the rects it would be read from drawn on it
+    pub fn preview_regions(&self, path: &std::path::Path) -> Result<()> {
+        let mut image = self.capture_panel()?;
+        self.window_info.draw_regions(&mut image);
+        image.save(path)?;
+        Ok(())
+    }
+
+    /// Text of `rect` (in window coordinates) of a panel image, empty for a blank field
+    fn model_inference(&self, rect: Rect<f64>, panel_image: &RgbImage) -> Result<String> {
+        let raw_img = crop_panel_rect(self.window_info.panel_rect, rect, panel_image)?;
+        if preprocess_result(&raw_img, false).is_none() {
+            return Ok(String::new());
+        }
+        self.image_to_text.image_to_text(&raw_img, false)
+    }
+
+    /// Recognize one weapon panel, e.g. cropped from a screenshot
+    pub fn scan_panel_image(&self, panel_image: &RgbImage) -> Result<GenshinWeaponScanResult> {
+        Ok(GenshinWeaponScanResult {
+            name: self.model_inference(self.window_info.title_rect, panel_image)?,
+            level: self.model_inference(self.window_info.level_rect, panel_image)?,
+            refinement: self.model_inference(self.window_info.refinement_rect, panel_image)?,
+            equip: self.model_inference(self.window_info.item_equip_rect, panel_image)?,
+        })
+    }
+
+    pub fn get_item_count(&self) -> Result<i32> {
+        let max_count = Self::MAX_COUNT as i32;
+        if self.scanner_config.number > 0 {
+            return Ok(max_count.min(self.scanner_config.number));
+        }
+
+        let image = self.capturer.capture_relative_to(
+            self.window_info.item_count_rect.to_rect_i32(),
+            self.game_info.window.origin(),
+        )?;
+        let s = self.image_to_text.image_to_text(&image, false)?;
+        info!("物品信息: {}", s);
+
+        match yas::utils::parse_item_count(&s, ITEM_COUNT_KEYWORD) {
+            Some(count) => Ok(count.min(max_count)),
+            None => {
+                warn!("无法解析武器数量，OCR 结果: {:?}，使用最大数量 {}", s, max_count);
+                Ok(max_count)
+            },
+        }
+    }
+
+    pub fn scan(&mut self) -> Result<Vec<GenshinWeaponScanResult>> {
+        info!("开始扫描武器，使用鼠标右键中断扫描");
+        let count = self.get_item_count()?;
+        if count <= 0 {
+            return Err(anyhow!("武器数量为 0"));
+        }
+
+        let mut generator = GenshinRepositoryScanController::get_generator(self.controller.clone(), count as usize);
+        let mut results = Vec::new();
+        loop {
+            match Pin::new(&mut generator).resume(()) {
+                CoroutineState::Yielded(_) => {
+                    let image = self.capture_panel()?;
+                    match self.scan_panel_image(&image) {
+                        Ok(result) => {
+                            if self.scanner_config.verbose {
+                                info!("{:?}", result);
+                            }
+                            results.push(result);
+                        },
+                        Err(e) => error!("识别武器失败: {}", e),
+                    }
+                },
+                CoroutineState::Complete(result) => {
+                    match result {
+                        Err(e) => error!("扫描发生错误：{}", e),
+                        Ok(GenshinRepositoryControllerReturnResult::Interrupted) => info!("用户中断"),
+                        Ok(GenshinRepositoryControllerReturnResult::Finished) => (),
+                    }
+                    break;
+                },
+            }
+        }
+
+        info!("武器扫描结束，共识别 {} 把", results.len());
+        Ok(results)
+    }
+}
@@ -0,0 +1,29 @@
+#[derive(Clone, clap::Args)]
+pub struct GenshinWeaponScannerConfig {
+    /// it will output very verbose messages
+    #[arg(id = "verbose", long, help = "显示详细信息")]
+    pub verbose: bool,
+
+    /// the exact amount to scan
+    #[arg(id = "number", long, help = "指定武器数量", value_name = "NUMBER", default_value_t = -1)]
+    pub number: i32,
+
+    /// Directory with `model_training.onnx` and `index_2_word.json` to use instead of the embedded OCR model
+    #[arg(id = "model-dir", long = "model-dir", help = "OCR 模型目录（包含 model_training.onnx 与 index_2_word.json），默认使用内置模型", value_name = "MODEL_DIR")]
+    pub model_dir: Option<std::path::PathBuf>,
+
+    /// Capture the current panel with the rects to be recognized drawn on it, save it to `weapon_region_preview.png` and exit
+    #[arg(id = "preview-regions", long = "preview-regions", help = "截取当前武器面板并标出各识别区域，保存为 weapon_region_preview.png 后退出，用于扫描前检查区域是否对齐")]
+    pub preview_regions: bool,
+}
+
+impl Default for GenshinWeaponScannerConfig {
+    fn default() -> Self {
+        GenshinWeaponScannerConfig {
+            verbose: false,
+            number: -1,
+            model_dir: None,
+            preview_regions: false,
+        }
+    }
+}
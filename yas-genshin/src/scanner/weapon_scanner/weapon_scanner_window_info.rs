@@ -0,0 +1,74 @@
+use image::{Rgb, RgbImage};
+use yas::draw_capture_region::draw_capture_region::draw_rect;
+use yas::positioning::{Pos, Rect};
+
+/// The weapon detail panel shares its title, equip line and panel with the artifact one,
+/// only the level line and the refinement below it have their own rects
+#[derive(Clone, yas_derive::YasWindowInfo, Debug, Default)]
+pub struct WeaponScannerWindowInfo {
+    /// the weapon name, relative to window
+    #[window_info(rename = "genshin_artifact_title_rect")]
+    pub title_rect: Rect<f64>,
+
+    /// the level line, e.g. `Lv.80/90`, relative to window
+    #[window_info(rename = "genshin_weapon_level_rect")]
+    pub level_rect: Rect<f64>,
+
+    /// the refinement line, e.g. `精炼1阶`, relative to window
+    #[window_info(rename = "genshin_weapon_refinement_rect")]
+    pub refinement_rect: Rect<f64>,
+
+    /// equip status of the weapon relative to window
+    #[window_info(rename = "genshin_artifact_item_equip_rect")]
+    pub item_equip_rect: Rect<f64>,
+
+    /// the count of weapons relative to window
+    #[window_info(rename = "genshin_artifact_item_count_rect")]
+    pub item_count_rect: Rect<f64>,
+
+    /// the whole panel of the weapon, relative to window
+    #[window_info(rename = "genshin_repository_panel_rect")]
+    pub panel_rect: Rect<f64>,
+}
+
+impl WeaponScannerWindowInfo {
+    /// Draw the rects read from the panel onto `panel_image`, a capture of `panel_rect`
+    pub fn draw_regions(&self, panel_image: &mut RgbImage) {
+        let origin = Pos { x: -self.panel_rect.left, y: -self.panel_rect.top };
+        let regions = [
+            (self.title_rect, Rgb([255, 0, 0])),
+            (self.level_rect, Rgb([0, 255, 255])),
+            (self.refinement_rect, Rgb([0, 255, 0])),
+            (self.item_equip_rect, Rgb([0, 0, 255])),
+        ];
+        for (rect, color) in regions {
+            draw_rect(panel_image, &rect.translate(origin), color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yas::game_info::{Platform, UI};
+    use yas::positioning::Size;
+    use yas::window_info::{load_window_info_repo, FromWindowInfoRepository};
+
+    use super::*;
+
+    #[test]
+    fn test_every_profile_has_weapon_rects() {
+        let repo = load_window_info_repo!(
+            "../../../window_info/windows1600x900.json",
+            "../../../window_info/windows1280x960.json",
+            "../../../window_info/windows1440x900.json",
+            "../../../window_info/windows2100x900.json",
+            "../../../window_info/windows3440x1440.json",
+        );
+        for size in repo.available_resolutions(UI::Desktop, Platform::Windows) {
+            let info = WeaponScannerWindowInfo::from_window_info_repository(size, UI::Desktop, Platform::Windows, &repo);
+            assert!(info.is_ok(), "{}x{}: {:#}", size.width, size.height, info.err().unwrap());
+        }
+        // 16:10 is taken from 1440x900
+        assert!(WeaponScannerWindowInfo::from_window_info_repository(Size::new(1680, 1050), UI::Desktop, Platform::Windows, &repo).is_ok());
+    }
+}
@@ -1,4 +1,9 @@
+use std::rc::Rc;
+
+use anyhow::Result;
 use clap::arg;
+use image::RgbImage;
+use yas::capture::{Capturer, GenericCapturer, PipeCapturer, RecordingCapturer};
 
 #[derive(Clone, clap::Args)]
 pub struct GenshinRepositoryScannerLogicConfig {
@@ -24,6 +29,58 @@ pub struct GenshinRepositoryScannerLogicConfig {
     /// The time to wait for switching to the next item in cloud game
     #[arg(id = "cloud-wait-switch-item", long = "cloud-wait-switch-item", help = "云游戏切换物品等待时间（ms）", default_value_t = 300)]
     pub cloud_wait_switch_item: i32,
+
+    /// Where to click inside an item cell to open its detail panel, as a fraction of the cell width
+    #[arg(id = "detail-click-x", long = "detail-click-x", help = "打开物品详情时点击位置在格子内的横向比例（0~1）", default_value_t = 0.5)]
+    pub detail_click_x: f64,
+
+    /// Where to click inside an item cell to open its detail panel, as a fraction of the cell height
+    #[arg(id = "detail-click-y", long = "detail-click-y", help = "打开物品详情时点击位置在格子内的纵向比例（0~1）", default_value_t = 0.25)]
+    pub detail_click_y: f64,
+
+    /// How many times to click again when the detail panel does not open, 0 disables the check
+    #[arg(id = "open-detail-retry", long = "open-detail-retry", help = "详情面板未打开时的重试点击次数（0 为不检查）", default_value_t = 2)]
+    pub open_detail_retry: u32,
+
+    /// Rows of items in one page, overrides the value from window info
+    #[arg(id = "grid-rows", long = "grid-rows", help = "每页物品行数（覆盖默认布局，游戏界面布局变化时使用）", value_parser = clap::value_parser!(i32).range(1..))]
+    pub grid_rows: Option<i32>,
+
+    /// Columns of items in one page, overrides the value from window info
+    #[arg(id = "grid-cols", long = "grid-cols", help = "每页物品列数（覆盖默认布局，游戏界面布局变化时使用）", value_parser = clap::value_parser!(i32).range(1..))]
+    pub grid_cols: Option<i32>,
+
+    /// Save every captured region with its rect into this directory, for debugging capture problems
+    #[arg(id = "record-captures", long = "record-captures", help = "将每次截图及其区域保存到该目录（调试截图问题用）", value_name = "DIR")]
+    pub record_captures: Option<std::path::PathBuf>,
+
+    /// Read screen frames from this named pipe (`-` for stdin) instead of capturing the screen,
+    /// see `PipeCapturer` for the frame format
+    #[arg(id = "capture-pipe", long = "capture-pipe", help = "从该命名管道读取截图帧代替屏幕截图（- 为标准输入）", value_name = "PATH")]
+    pub capture_pipe: Option<std::path::PathBuf>,
+}
+
+impl GenshinRepositoryScannerLogicConfig {
+    /// The screen capturer, or the frames of `--capture-pipe`,
+    /// wrapped to record every capture when `--record-captures` is set
+    pub fn capturer(&self, prefix: &str) -> Result<Rc<dyn Capturer<RgbImage>>> {
+        match &self.capture_pipe {
+            Some(path) => self.recording(PipeCapturer::open(path)?, prefix),
+            None => self.recording(GenericCapturer::new()?, prefix),
+        }
+    }
+
+    fn recording<C: Capturer<RgbImage> + 'static>(&self, capturer: C, prefix: &str) -> Result<Rc<dyn Capturer<RgbImage>>> {
+        Ok(match &self.record_captures {
+            Some(dir) => Rc::new(RecordingCapturer::new(capturer, Some(dir.clone()), prefix)?),
+            None => Rc::new(capturer),
+        })
+    }
+
+    /// The grid size of a page, `row` and `col` from window info unless overridden
+    pub fn grid_size(&self, row: i32, col: i32) -> (i32, i32) {
+        (self.grid_rows.unwrap_or(row), self.grid_cols.unwrap_or(col))
+    }
 }
 
 impl Default for GenshinRepositoryScannerLogicConfig {
@@ -36,6 +93,13 @@ impl Default for GenshinRepositoryScannerLogicConfig {
             // dump_mode: false,
             max_wait_switch_item: 800,
             cloud_wait_switch_item: 300,
+            detail_click_x: 0.5,
+            detail_click_y: 0.25,
+            open_detail_retry: 2,
+            grid_rows: None,
+            grid_cols: None,
+            record_captures: None,
+            capture_pipe: None,
         }
     }
 }
@@ -6,17 +6,39 @@ use std::time::SystemTime;
 use anyhow::{anyhow, Result};
 use clap::{ArgMatches, FromArgMatches};
 use image::RgbImage;
-use log::{error, info};
+use log::{error, info, warn};
 
-use yas::capture::{Capturer, GenericCapturer};
+use yas::capture::Capturer;
 use yas::game_info::GameInfo;
-use yas::positioning::{Pos, Size};
+use yas::positioning::{Pos, Rect, Size};
 use yas::system_control::SystemControl;
 use yas::utils;
+use yas::utils::color_distance;
 use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
 
 use crate::scanner_controller::repository_layout::{GenshinRepositoryScanControllerWindowInfo, GenshinRepositoryScannerLogicConfig, ScrollResult};
 
+/// Where `move_to` points for the item at `row`, `col` of the visible page, relative to the window
+fn item_click_pos(
+    window_info: &GenshinRepositoryScanControllerWindowInfo,
+    config: &GenshinRepositoryScannerLogicConfig,
+    is_artifact: bool,
+    row: usize,
+    col: usize,
+) -> Pos<f64> {
+    let gap = window_info.item_gap_size;
+    let mut margin = window_info.scan_margin_pos;
+    let size = window_info.item_size;
+    if is_artifact {
+        margin = margin + window_info.artifact_panel_offset;
+    }
+
+    Pos {
+        x: margin.x + (gap.width + size.width) * (col as f64) + size.width * config.detail_click_x,
+        y: margin.y + (gap.height + size.height) * (row as f64) + size.height * config.detail_click_y,
+    }
+}
+
 pub struct GenshinRepositoryScanController {
     // to detect whether an item changes
     pool: f64,
@@ -55,15 +77,23 @@ fn calc_pool(row: &[u8]) -> f32 {
     pool
 }
 
-fn get_capturer() -> Result<Rc<dyn Capturer<RgbImage>>> {
-    Ok(Rc::new(GenericCapturer::new()?))
-}
-
-fn color_distance(c1: &image::Rgb<u8>, c2: &image::Rgb<u8>) -> usize {
-    let x = c1.0[0] as i32 - c2.0[0] as i32;
-    let y = c1.0[1] as i32 - c2.0[1] as i32;
-    let z = c1.0[2] as i32 - c2.0[2] as i32;
-    (x * x + y * y + z * z) as usize
+/// Light background of the lower part of the item detail panel
+const DETAIL_PANEL_BACKGROUND: image::Rgb<u8> = image::Rgb([236, 229, 216]);
+
+/// Click to open the detail panel and check it opened, clicking again at most `retry` times.
+/// Returns whether the panel was seen open
+fn open_detail_with_retry(
+    retry: u32,
+    mut click: impl FnMut() -> Result<()>,
+    mut is_opened: impl FnMut() -> bool,
+) -> Result<bool> {
+    for _ in 0..=retry {
+        click()?;
+        if is_opened() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
 }
 
 // constructor
@@ -80,8 +110,11 @@ impl GenshinRepositoryScanController {
             game_info.platform,
             window_info_repo,
         )?;
-        let row = window_info.genshin_repository_item_row;
-        let col = window_info.genshin_repository_item_col;
+        let (row, col) = config.grid_size(
+            window_info.genshin_repository_item_row,
+            window_info.genshin_repository_item_col,
+        );
+        let capturer = config.capturer("controller")?;
 
         Ok(GenshinRepositoryScanController {
             system_control: SystemControl::new(),
@@ -105,7 +138,7 @@ impl GenshinRepositoryScanController {
             game_info,
             scanned_count: 0,
 
-            capturer: get_capturer()?,
+            capturer,
 
             is_artifact,
         })
@@ -133,6 +166,15 @@ pub enum ReturnResult {
 
 impl GenshinRepositoryScanController {
     pub fn get_generator(object: Rc<RefCell<GenshinRepositoryScanController>>, item_count: usize) -> impl Coroutine<Yield=(), Return=Result<ReturnResult>> {
+        Self::get_generator_from(object, item_count, 0)
+    }
+
+    /// Like `get_generator`, but the first `skip` items are scrolled past without being opened or yielded
+    pub fn get_generator_from(
+        object: Rc<RefCell<GenshinRepositoryScanController>>,
+        item_count: usize,
+        skip: usize,
+    ) -> impl Coroutine<Yield=(), Return=Result<ReturnResult>> {
         let generator = #[coroutine] move || {
             let mut scanned_row = 0;
             let mut scanned_count = 0;
@@ -176,17 +218,15 @@ impl GenshinRepositoryScanController {
                         if utils::is_rmb_down() {
                             return Ok(ReturnResult::Interrupted);
                         }
-                        if scanned_count > item_count {
+                        if scanned_count >= item_count {
                             return Ok(ReturnResult::Finished);
                         }
+                        if scanned_count < skip {
+                            scanned_count += 1;
+                            continue;
+                        }
 
-                        object.borrow_mut().move_to(row, col);
-                        object.borrow_mut().system_control.mouse_click().unwrap();
-
-                        #[cfg(target_os = "macos")]
-                        utils::sleep(20);
-
-                        let _ = object.borrow_mut().wait_until_switched();
+                        object.borrow_mut().open_detail(row, col)?;
 
                         // have to make sure at this point no mut ref exists
                         yield;
@@ -272,19 +312,67 @@ impl GenshinRepositoryScanController {
         }
     }
 
-    pub fn move_to(&mut self, row: usize, col: usize) {
-        let (row, col) = (row as u32, col as u32);
-        let origin = self.game_info.window.to_rect_f64().origin();
+    /// Click the item at (row, col) and wait for its detail panel.
+    /// When the panel background is not seen afterwards, the click is retried as configured
+    pub fn open_detail(&mut self, row: usize, col: usize) -> Result<()> {
+        self.move_to(row, col);
 
-        let gap = self.window_info.item_gap_size;
-        let mut margin = self.window_info.scan_margin_pos;
-        let size = self.window_info.item_size;
-        if self.is_artifact {
-            margin = margin + self.window_info.artifact_panel_offset;
+        if self.config.open_detail_retry == 0 {
+            self.click_and_wait_switched()?;
+            return anyhow::Ok(());
+        }
+
+        let retry = self.config.open_detail_retry;
+        let controller = RefCell::new(self);
+        let opened = open_detail_with_retry(
+            retry,
+            || controller.borrow_mut().click_and_wait_switched(),
+            || controller.borrow().is_detail_panel_opened(),
+        )?;
+        if !opened {
+            warn!("第 {} 行第 {} 列物品详情面板似乎未打开，请检查 --detail-click-x/--detail-click-y", row, col);
         }
 
-        let left = origin.x + margin.x + (gap.width + size.width) * (col as f64) + size.width / 2.0;
-        let top = origin.y + margin.y + (gap.height + size.height) * (row as f64) + size.height / 4.0;
+        anyhow::Ok(())
+    }
+
+    fn click_and_wait_switched(&mut self) -> Result<()> {
+        self.system_control.mouse_click()?;
+
+        #[cfg(target_os = "macos")]
+        utils::sleep(20);
+
+        let _ = self.wait_until_switched();
+        anyhow::Ok(())
+    }
+
+    /// Whether the detail panel is showing, judged by the background at its left margin
+    pub fn is_detail_panel_opened(&self) -> bool {
+        let panel = self.window_info.panel_rect;
+        let rect = Rect {
+            left: panel.left + 2.0,
+            top: panel.top + panel.height * 0.5,
+            width: 10.0,
+            height: panel.height * 0.4,
+        };
+        let im = match self.capturer.capture_relative_to(rect.to_rect_i32(), self.game_info.window.origin()) {
+            Ok(im) => im,
+            // cannot tell, do not keep clicking
+            Err(_) => return true,
+        };
+
+        let matched = im.pixels()
+            .filter(|color| color_distance(color, &DETAIL_PANEL_BACKGROUND) < 300)
+            .count();
+        matched * 2 >= (im.width() * im.height()) as usize
+    }
+
+    pub fn move_to(&mut self, row: usize, col: usize) {
+        let origin = self.game_info.window.to_rect_f64().origin();
+        let pos = item_click_pos(&self.window_info, &self.config, self.is_artifact, row, col);
+
+        let left = origin.x + pos.x;
+        let top = origin.y + pos.y;
 
         self.system_control.mouse_move_to(left as i32, top as i32).unwrap();
 
@@ -292,6 +380,12 @@ impl GenshinRepositoryScanController {
         utils::sleep(20);
     }
 
+    /// Click the item at `row`, `col` of the visible page
+    pub fn click_item(&mut self, row: usize, col: usize) -> Result<()> {
+        self.move_to(row, col);
+        self.system_control.mouse_click()
+    }
+
     pub fn scroll_one_row(&mut self) -> ScrollResult {
         let mut state = 0;
         let mut count = 0;
@@ -436,3 +530,63 @@ impl GenshinRepositoryScanController {
         ((self.avg_scroll_one_row * count as f64 - 2.0).round() as i32).max(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_open_detail_retries_until_opened() {
+        let clicks = Cell::new(0);
+        let opened = open_detail_with_retry(
+            2,
+            || {
+                clicks.set(clicks.get() + 1);
+                Ok(())
+            },
+            || clicks.get() >= 2,
+        ).unwrap();
+
+        assert!(opened);
+        assert_eq!(clicks.get(), 2);
+    }
+
+    #[test]
+    fn test_item_click_pos() {
+        use yas::game_info::{Platform, UI};
+        use yas::window_info::load_window_info_repo;
+
+        let repo = load_window_info_repo!("../../../window_info/windows1600x900.json");
+        let window_info = GenshinRepositoryScanControllerWindowInfo::from_window_info_repository(
+            Size::new(1600, 900),
+            UI::Desktop,
+            Platform::Windows,
+            &repo,
+        ).unwrap();
+        let config = GenshinRepositoryScannerLogicConfig::default();
+
+        // margin (99, 101) plus the artifact offset (0, 48.5), items 102x126 with a gap of 20
+        let pos = item_click_pos(&window_info, &config, true, 2, 3);
+        assert_eq!(pos, Pos { x: 99.0 + 122.0 * 3.0 + 51.0, y: 101.0 + 48.5 + 146.0 * 2.0 + 31.5 });
+        let pos = item_click_pos(&window_info, &config, false, 0, 0);
+        assert_eq!(pos, Pos { x: 99.0 + 51.0, y: 101.0 + 31.5 });
+    }
+
+    #[test]
+    fn test_open_detail_gives_up_after_retries() {
+        let clicks = Cell::new(0);
+        let opened = open_detail_with_retry(
+            2,
+            || {
+                clicks.set(clicks.get() + 1);
+                Ok(())
+            },
+            || false,
+        ).unwrap();
+
+        assert!(!opened);
+        assert_eq!(clicks.get(), 3);
+    }
+}
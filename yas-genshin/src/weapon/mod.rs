@@ -0,0 +1,3 @@
+pub use weapon::{ascension_from_level, parse_refinement, parse_weapon_level, GenshinWeapon, WeaponParseError};
+
+mod weapon;
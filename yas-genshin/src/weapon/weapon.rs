@@ -0,0 +1,146 @@
+use std::fmt::{Display, Formatter};
+
+use crate::character::equip_from_zh_cn_raw;
+use crate::scanner::GenshinWeaponScanResult;
+
+/// Max level of a weapon after 0 to 6 ascensions
+const MAX_LEVELS: [i32; 7] = [20, 40, 50, 60, 70, 80, 90];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenshinWeapon {
+    /// Chinese name as shown in the game, e.g. 护摩之杖
+    pub name: String,
+    pub level: i32,
+    /// 0 to 6
+    pub ascension: i32,
+    /// 1 to 5
+    pub refinement: i32,
+    pub equip: Option<String>,
+}
+
+/// Why a weapon scan result could not be converted, with the raw text that failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WeaponParseError {
+    EmptyName,
+    BadLevel(String),
+    BadRefinement(String),
+}
+
+impl Display for WeaponParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeaponParseError::EmptyName => write!(f, "empty weapon name"),
+            WeaponParseError::BadLevel(s) => write!(f, "bad weapon level `{}`", s),
+            WeaponParseError::BadRefinement(s) => write!(f, "bad refinement `{}`", s),
+        }
+    }
+}
+
+impl std::error::Error for WeaponParseError {}
+
+/// Level and max level of the level line, e.g. `Lv.80/90`. The max level is None when OCR lost it
+pub fn parse_weapon_level(s: &str) -> Option<(i32, Option<i32>)> {
+    let numbers = s
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse::<i32>().ok())
+        .collect::<Vec<_>>();
+    let level = *numbers.first()?;
+    if !(1..=90).contains(&level) {
+        return None;
+    }
+    Some((level, numbers.get(1).copied()))
+}
+
+/// Ascensions done by a weapon of `level` whose level line shows `max_level`.
+/// Without a known max level the weapon is taken as not ascended at its level cap, e.g. level 40 is 1
+pub fn ascension_from_level(level: i32, max_level: Option<i32>) -> i32 {
+    let by_max = max_level
+        .and_then(|max| MAX_LEVELS.iter().position(|&m| m == max))
+        .filter(|&ascension| {
+            let min = if ascension == 0 { 1 } else { MAX_LEVELS[ascension - 1] };
+            (min..=MAX_LEVELS[ascension]).contains(&level)
+        });
+    let ascension = by_max.unwrap_or_else(|| MAX_LEVELS.iter().position(|&m| m >= level).unwrap_or(MAX_LEVELS.len() - 1));
+    ascension as i32
+}
+
+/// The refinement of a line like `精炼1阶`
+pub fn parse_refinement(s: &str) -> Option<i32> {
+    s.chars()
+        .find_map(|c| c.to_digit(10))
+        .map(|d| d as i32)
+        .filter(|d| (1..=5).contains(d))
+}
+
+impl TryFrom<&GenshinWeaponScanResult> for GenshinWeapon {
+    type Error = WeaponParseError;
+
+    fn try_from(value: &GenshinWeaponScanResult) -> Result<Self, Self::Error> {
+        let name = value.name.trim();
+        if name.is_empty() {
+            return Err(WeaponParseError::EmptyName);
+        }
+
+        let (level, max_level) = parse_weapon_level(&value.level)
+            .ok_or_else(|| WeaponParseError::BadLevel(value.level.clone()))?;
+        let refinement = parse_refinement(&value.refinement)
+            .ok_or_else(|| WeaponParseError::BadRefinement(value.refinement.clone()))?;
+
+        Ok(GenshinWeapon {
+            name: String::from(name),
+            level,
+            ascension: ascension_from_level(level, max_level),
+            refinement,
+            equip: equip_from_zh_cn_raw(&value.equip),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_weapon_level() {
+        assert_eq!(parse_weapon_level("Lv.80/90"), Some((80, Some(90))));
+        assert_eq!(parse_weapon_level("Lv. 90 / 90"), Some((90, Some(90))));
+        assert_eq!(parse_weapon_level("Lv.1"), Some((1, None)));
+        assert_eq!(parse_weapon_level("Lv."), None);
+        assert_eq!(parse_weapon_level("Lv.900/90"), None);
+    }
+
+    #[test]
+    fn test_ascension_from_level() {
+        assert_eq!(ascension_from_level(1, Some(20)), 0);
+        assert_eq!(ascension_from_level(20, Some(20)), 0);
+        assert_eq!(ascension_from_level(20, Some(40)), 1);
+        assert_eq!(ascension_from_level(80, Some(90)), 6);
+        assert_eq!(ascension_from_level(90, Some(90)), 6);
+        // max level lost or not a possible one for the level
+        assert_eq!(ascension_from_level(40, None), 1);
+        assert_eq!(ascension_from_level(75, None), 5);
+        assert_eq!(ascension_from_level(80, Some(40)), 5);
+    }
+
+    #[test]
+    fn test_weapon_from_scan_result() {
+        let result = GenshinWeaponScanResult {
+            name: String::from("护摩之杖 "),
+            level: String::from("Lv.80/90"),
+            refinement: String::from("精炼1阶"),
+            equip: String::from("胡桃已装备"),
+        };
+        let weapon = GenshinWeapon::try_from(&result).unwrap();
+        assert_eq!(weapon, GenshinWeapon {
+            name: String::from("护摩之杖"),
+            level: 80,
+            ascension: 6,
+            refinement: 1,
+            equip: Some(String::from("胡桃")),
+        });
+
+        let result = GenshinWeaponScanResult { refinement: String::from("精炼阶"), ..result };
+        assert_eq!(GenshinWeapon::try_from(&result), Err(WeaponParseError::BadRefinement(String::from("精炼阶"))));
+    }
+}
@@ -0,0 +1,123 @@
+//! Lock list: JSON of relics to auto-lock when scanning. Match by name, main stat and sub stats.
+//!
+//! JSON format: array of objects, each with `name`, `main_stat_name`, `main_stat_value`,
+//! `sub_stat_name` and `sub_stat_value` (arrays of 4 strings, empty for a missing sub stat).
+//! Example:
+//! ```json
+//! [
+//!   {
+//!     "name": "野穗伴行的快枪手",
+//!     "main_stat_name": "生命值",
+//!     "main_stat_value": "705",
+//!     "sub_stat_name": ["暴击率", "速度", "攻击力", "效果抵抗"],
+//!     "sub_stat_value": ["3.24%", "2", "3.88%", "7.77%"]
+//!   }
+//! ]
+//! ```
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use super::scan_result::StarRailRelicScanResult;
+
+/// One relic entry in the lock list, fields are the scan result's as OCR reads them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelicLockListEntry {
+    pub name: String,
+    pub main_stat_name: String,
+    pub main_stat_value: String,
+    /// Exactly 4, order matters
+    pub sub_stat_name: [String; 4],
+    pub sub_stat_value: [String; 4],
+}
+
+impl RelicLockListEntry {
+    /// True if the scan result has the same name, main stat and sub stats, ignoring surrounding whitespace
+    pub fn matches(&self, r: &StarRailRelicScanResult) -> bool {
+        self.name.trim() == r.name.trim()
+            && self.main_stat_name.trim() == r.main_stat_name.trim()
+            && self.main_stat_value.trim() == r.main_stat_value.trim()
+            && (0..4).all(|i| {
+                self.sub_stat_name[i].trim() == r.sub_stat_name[i].trim()
+                    && self.sub_stat_value[i].trim() == r.sub_stat_value[i].trim()
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelicLockList(pub Vec<RelicLockListEntry>);
+
+impl RelicLockList {
+    pub fn from_json_path(path: &std::path::Path) -> anyhow::Result<Self> {
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("read lock list: {}", path.display()))?;
+        let list: Vec<RelicLockListEntry> = serde_json::from_str(&s)
+            .with_context(|| "parse lock list JSON (expected array of { name, main_stat_name, main_stat_value, sub_stat_name: [4], sub_stat_value: [4] })")?;
+        Ok(RelicLockList(list))
+    }
+
+    pub fn contains(&self, r: &StarRailRelicScanResult) -> bool {
+        self.0.iter().any(|e| e.matches(r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_result() -> StarRailRelicScanResult {
+        StarRailRelicScanResult {
+            name: String::from("野穗伴行的快枪手"),
+            main_stat_name: String::from("生命值"),
+            main_stat_value: String::from("705"),
+            sub_stat_name: ["暴击率", "速度", "攻击力", "效果抵抗"].map(String::from),
+            sub_stat_value: ["3.24%", "2", "3.88%", "7.77%"].map(String::from),
+            equip: String::new(),
+            level: 15,
+            star: 5,
+            lock: false,
+            discard: false,
+        }
+    }
+
+    fn entry() -> RelicLockListEntry {
+        let r = scan_result();
+        RelicLockListEntry {
+            name: format!(" {} ", r.name),
+            main_stat_name: r.main_stat_name,
+            main_stat_value: r.main_stat_value,
+            sub_stat_name: r.sub_stat_name,
+            sub_stat_value: r.sub_stat_value,
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let list = RelicLockList(vec![entry()]);
+        assert!(list.contains(&scan_result()));
+
+        // sub stats are compared in order
+        let mut swapped = scan_result();
+        swapped.sub_stat_name.swap(0, 1);
+        swapped.sub_stat_value.swap(0, 1);
+        assert!(!list.contains(&swapped));
+
+        let mut other_value = scan_result();
+        other_value.main_stat_value = String::from("564");
+        assert!(!list.contains(&other_value));
+
+        assert!(!RelicLockList(Vec::new()).contains(&scan_result()));
+    }
+
+    #[test]
+    fn test_from_json() {
+        let list: Vec<RelicLockListEntry> = serde_json::from_str(r#"[{
+            "name": "野穗伴行的快枪手",
+            "main_stat_name": "生命值",
+            "main_stat_value": "705",
+            "sub_stat_name": ["暴击率", "速度", "攻击力", "效果抵抗"],
+            "sub_stat_value": ["3.24%", "2", "3.88%", "7.77%"]
+        }]"#).unwrap();
+        assert!(RelicLockList(list).contains(&scan_result()));
+    }
+}
@@ -1,6 +1,7 @@
 pub use relic_scanner::{StarRailRelicScanner};
 pub use relic_scanner_config::StarRailRelicScannerConfig;
 pub use scan_result::StarRailRelicScanResult;
+pub use lock_list::{RelicLockList, RelicLockListEntry};
 // pub use relic_scanner_window_info::RelicScannerWindowInfo;
 
 mod relic_scanner;
@@ -10,3 +11,4 @@ mod scan_result;
 mod relic_scanner_worker;
 mod message_items;
 mod match_colors;
+mod lock_list;
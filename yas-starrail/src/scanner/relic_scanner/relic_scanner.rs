@@ -1,17 +1,19 @@
-use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::mpsc::{self, Sender}, time::SystemTime};
+use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::mpsc::{self, Receiver, Sender}, time::SystemTime};
 
 use anyhow::Result;
 use clap::FromArgMatches;
 use image::RgbImage;
-use log::{error, info};
+use log::{error, info, warn};
 
 use yas::capture::{Capturer, GenericCapturer};
 use yas::game_info::GameInfo;
 use yas::ocr::{ImageToText, yas_ocr_model};
-use yas::positioning::Pos;
-use yas::utils::color_distance;
+use yas::positioning::{Pos, Rect};
+use yas::utils;
+use yas::utils::{color_distance, get_page_locks, lock_at, page_start_row, LockCell};
 use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
 
+use crate::scanner::relic_scanner::lock_list::RelicLockList;
 use crate::scanner::relic_scanner::match_colors::{MATCH_COLORS, MatchColors};
 use crate::scanner::relic_scanner::message_items::SendItem;
 use crate::scanner::relic_scanner::relic_scanner_window_info::RelicScannerWindowInfo;
@@ -21,6 +23,9 @@ use crate::scanner_controller::repository_layout::{ReturnResult, StarRailReposit
 
 use super::relic_scanner_config::StarRailRelicScannerConfig;
 
+/// A list row cut off by the bottom of the window is read for lock states if at least this much of it is visible
+const PARTIAL_ROW_RATIO: f64 = 0.75;
+
 pub struct StarRailRelicScanner {
     scanner_config: StarRailRelicScannerConfig,
     window_info: RelicScannerWindowInfo,
@@ -186,15 +191,12 @@ impl StarRailRelicScanner {
 
         info!("物品信息: {}", s);
 
-        if s.starts_with(item_name) {
-            let chars = s.chars().collect::<Vec<char>>();
-            let count_str = chars[4..chars.len() - 5].iter().collect::<String>();
-            Ok(match count_str.parse::<usize>() {
-                Ok(v) => (v as i32).min(max_count),
-                Err(_) => max_count,
-            })
-        } else {
-            Ok(max_count)
+        match utils::parse_item_count(&s, item_name) {
+            Some(v) => Ok(v.min(max_count)),
+            None => {
+                warn!("无法解析物品数量，使用最大数量 {}，OCR 结果: {:?}", max_count, s);
+                Ok(max_count)
+            },
         }
     }
 
@@ -210,10 +212,28 @@ impl StarRailRelicScanner {
             self.scanner_config.clone()
         )?;
 
-        let join_handle = worker.run(rx);
+        let lock_list = self
+            .scanner_config
+            .lock_list_path
+            .as_ref()
+            .map(|p| RelicLockList::from_json_path(p))
+            .transpose()?;
+        if lock_list.is_some() {
+            info!("已加载自动上锁列表");
+        }
+
+        // the main thread waits for each result to auto-lock
+        let (result_tx, result_rx) = if lock_list.is_some() {
+            let (t, r) = mpsc::channel::<Option<StarRailRelicScanResult>>();
+            (Some(t), Some(r))
+        } else {
+            (None, None)
+        };
+
+        let join_handle = worker.run(rx, result_tx);
         info!("Worker created");
 
-        self.send(&tx, count);
+        let sent = self.send(&tx, count, result_rx.as_ref(), lock_list.as_ref());
 
         match tx.send(None) {
             Ok(_) => info!("扫描结束，等待识别线程结束，请勿关闭程序"),
@@ -226,31 +246,92 @@ impl StarRailRelicScanner {
             info!("平均模型推理时间：{} ms", ms);
         }
 
-        match join_handle.join() {
-            Ok(v) => {
-                info!("识别耗时: {:?}", now.elapsed()?);
-                Ok(v)
-            },
-            Err(_) => Err(anyhow::anyhow!("识别线程出现错误")),
-        }
+        let results = match join_handle.join() {
+            Ok(v) => v,
+            Err(_) => return Err(anyhow::anyhow!("识别线程出现错误")),
+        };
+        // the worker is stopped either way, report a failed capture only after it
+        sent?;
+        info!("识别耗时: {:?}", now.elapsed()?);
+        Ok(results)
     }
 
-    fn send(&mut self, tx: &Sender<Option<SendItem>>, count: i32) {
+    /// The list grid from row `start_row` of the visible page down to the bottom of the window
+    fn capture_list_image(&self, start_row: i32) -> Result<RgbImage> {
+        let origin = self.game_info.window;
+        let margin = self.window_info.scan_margin_pos;
+        let gap = self.window_info.item_gap_size;
+        let size = self.window_info.item_size;
+        let skipped = (gap.height + size.height) * start_row as f64;
+
+        let left = (origin.left as f64 + margin.x) as i32;
+        let top = (origin.top as f64 + margin.y + skipped) as i32;
+        let width = (origin.width as f64 - margin.x) as i32;
+        let height = (origin.height as f64 - margin.y - skipped) as i32;
+
+        self.capturer.capture_rect(Rect { left, top, width, height })
+    }
+
+    /// Lock states of the page that starts with item `index` (starting from 0), read from the list view
+    fn get_page_locks(&self, count: i32, index: i32) -> Result<Vec<LockCell>> {
+        // the first page is never scrolled
+        let start_row = if index < self.window_info.page_size() {
+            0
+        } else {
+            page_start_row(count, index, self.window_info.row, self.window_info.col)
+        };
+        let list_image = self.capture_list_image(start_row)?;
+        Ok(get_page_locks(&list_image, &self.window_info.list_lock_grid(), PARTIAL_ROW_RATIO, None))
+    }
+
+    /// Click the lock button of the detail panel, at the position the lock state is read from.
+    /// Call only when list-view lock detection says not locked
+    fn try_lock_relic(&mut self) -> Result<()> {
+        let x = self.game_info.window.left + self.window_info.lock_pos.x as i32;
+        let y = self.game_info.window.top + self.window_info.lock_pos.y as i32;
+
+        let mut controller = self.controller.borrow_mut();
+        controller.system_control_mut().mouse_move_to(x, y)?;
+        utils::sleep(20);
+        controller.system_control_mut().mouse_click()?;
+        utils::sleep(50);
+        info!("已点击上锁");
+        Ok(())
+    }
+
+    fn send(
+        &mut self,
+        tx: &Sender<Option<SendItem>>,
+        count: i32,
+        result_rx: Option<&Receiver<Option<StarRailRelicScanResult>>>,
+        lock_list: Option<&RelicLockList>,
+    ) -> Result<()> {
         let mut generator = StarRailRepositoryScanController::get_generator(
             self.controller.clone(),
             count as usize
         );
+        let mut index: i32 = 0;
+        let mut page_locks: Vec<LockCell> = Vec::new();
+        let mut page_start: i32 = 0;
 
         loop {
             let pinned_generator = Pin::new(&mut generator);
             match pinned_generator.resume(()) {
                 CoroutineState::Yielded(_) => {
                     // let image = self.capture_panel().unwrap();
-                    let panel_image = self.capture_panel().unwrap();
-                    let equip = self.get_equipper().unwrap();
-                    let star = self.get_star().unwrap();
-                    let lock = self.get_lock().unwrap();
-                    let discard = self.get_discard().unwrap();
+                    let panel_image = self.capture_panel()?;
+                    let equip = self.get_equipper()?;
+                    let star = self.get_star()?;
+                    let lock = self.get_lock()?;
+                    let discard = self.get_discard()?;
+
+                    if lock_list.is_some() && index % self.window_info.page_size().max(1) == 0 {
+                        page_locks = self.get_page_locks(count, index)?;
+                        page_start = index;
+                    }
+                    let list_locked = lock_list.is_some()
+                        && lock_at(&page_locks, (index - page_start) as usize, self.window_info.col as usize);
+                    index += 1;
 
                     // todo normalize types
                     if (star as i32) < self.scanner_config.min_star {
@@ -265,6 +346,26 @@ impl StarRailRelicScanner {
                         break;
                     }
 
+                    if let (Some(rx), Some(list)) = (result_rx, lock_list) {
+                        let res = match rx.recv() {
+                            Ok(v) => v,
+                            Err(_) => {
+                                info!("识别线程已结束，停止扫描");
+                                break;
+                            },
+                        };
+
+                        if let Some(res) = res {
+                            if !list.contains(&res) {
+                                info!("自动上锁未匹配: {} {} {}", res.name, res.main_stat_name, res.main_stat_value);
+                            } else if list_locked {
+                                info!("自动上锁跳过（列表已显示为已锁）: {} {}", res.name, res.main_stat_value);
+                            } else if let Err(e) = self.try_lock_relic() {
+                                error!("自动上锁点击失败: {}", e);
+                            }
+                        }
+                    }
+
                     // scanned_count += 1;
                 },
                 CoroutineState::Complete(result) => {
@@ -282,5 +383,7 @@ impl StarRailRelicScanner {
                 }
             }
         }
+
+        Ok(())
     }
 }
\ No newline at end of file
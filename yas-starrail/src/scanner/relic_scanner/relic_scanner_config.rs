@@ -17,4 +17,8 @@ pub struct StarRailRelicScannerConfig {
 
     #[arg(id = "number", long, help = "指定遗器数量", value_name = "NUMBER", default_value_t = -1)]
     pub number: i32,
+
+    /// JSON path of relics to auto-lock (array of { name, main_stat_name, main_stat_value, sub_stat_name: [4], sub_stat_value: [4] })
+    #[arg(long = "lock", help = "自动上锁：读入 JSON 列表，识别到与列表中完全一致的遗器时点击上锁", value_name = "LOCK_JSON")]
+    pub lock_list_path: Option<std::path::PathBuf>,
 }
@@ -1,6 +1,8 @@
-use yas::positioning::{Pos, Rect};
+use image::Rgb;
+use yas::positioning::{Pos, Rect, Size};
+use yas::utils::ListLockGrid;
 
-#[derive(Clone, yas_derive::YasWindowInfo, Debug)]
+#[derive(Clone, yas_derive::YasWindowInfo, Debug, Default)]
 pub struct RelicScannerWindowInfo {
     #[window_info(rename = "starrail_relic_title_rect")]
     pub title_rect: Rect<f64>,
@@ -57,4 +59,85 @@ pub struct RelicScannerWindowInfo {
 
     #[window_info(rename = "starrail_repository_item_col")]
     pub col: i32,
+
+    #[window_info(rename = "starrail_repository_item_row")]
+    pub row: i32,
+
+    #[window_info(rename = "starrail_repository_item_gap_size")]
+    pub item_gap_size: Size<f64>,
+
+    #[window_info(rename = "starrail_repository_item_size")]
+    pub item_size: Size<f64>,
+
+    #[window_info(rename = "starrail_repository_scan_margin_pos")]
+    pub scan_margin_pos: Pos<f64>,
+
+    /// where the lock icon of a list cell is sampled, relative to the top left of the cell
+    #[window_info(rename = "starrail_repository_lock_pos")]
+    pub list_lock_pos: Pos<f64>,
+
+    /// color of the lock icon in the list view, optional in window info
+    #[window_info(rename = "starrail_repository_lock_color", default = "image::Rgb([255, 255, 255])")]
+    pub list_lock_color: Rgb<u8>,
+
+    /// a list cell is locked if a pixel is within this squared RGB distance of `list_lock_color`, optional in window info
+    #[window_info(rename = "starrail_repository_lock_threshold", default = "30.0")]
+    pub list_lock_threshold: f64,
+}
+
+impl RelicScannerWindowInfo {
+    /// Number of items in one page
+    pub fn page_size(&self) -> i32 {
+        self.row * self.col
+    }
+
+    /// The list grid for list-view lock detection, see `yas::utils::get_page_locks`
+    pub fn list_lock_grid(&self) -> ListLockGrid {
+        ListLockGrid {
+            row: self.row,
+            col: self.col,
+            item_size: self.item_size,
+            item_gap_size: self.item_gap_size,
+            lock_pos: self.list_lock_pos,
+            lock_color: self.list_lock_color,
+            lock_threshold: self.list_lock_threshold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbImage;
+    use yas::utils::{get_page_locks, locks_flat};
+
+    use super::*;
+
+    /// The relic grid of a 1920x1080 window
+    fn window_info() -> RelicScannerWindowInfo {
+        RelicScannerWindowInfo {
+            row: 5,
+            col: 9,
+            item_gap_size: Size::new(9.0, 14.0),
+            item_size: Size::new(115.0, 135.0),
+            list_lock_pos: Pos { x: 12.0, y: 14.0 },
+            list_lock_color: Rgb([255, 255, 255]),
+            list_lock_threshold: 30.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_page_locks_on_relic_grid() {
+        let info = window_info();
+        assert_eq!(info.page_size(), 45);
+
+        // two full rows, the lock icon of the second relic in the second row
+        let mut image = RgbImage::new(9 * 124, 2 * 149);
+        image.put_pixel(124 + 12, 149 + 14, Rgb([253, 254, 255]));
+
+        let locks = get_page_locks(&image, &info.list_lock_grid(), 0.75, None);
+        assert_eq!(locks.len(), 18);
+        assert_eq!(locks_flat(&locks).iter().filter(|locked| **locked).count(), 1);
+        assert!(locks[9 + 1].locked);
+    }
 }
@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{Receiver, Sender};
 use std::thread::JoinHandle;
 
 use anyhow::Result;
@@ -110,7 +110,12 @@ impl RelicScannerWorker {
         })
     }
 
-    pub fn run(self, rx: Receiver<Option<SendItem>>) -> JoinHandle<Vec<StarRailRelicScanResult>> {
+    /// Run the worker. If `result_tx` is Some, each scan result (or None on error) is sent back, so the main thread can auto-lock
+    pub fn run(
+        self,
+        rx: Receiver<Option<SendItem>>,
+        result_tx: Option<Sender<Option<StarRailRelicScanResult>>>,
+    ) -> JoinHandle<Vec<StarRailRelicScanResult>> {
         std::thread::spawn(move || {
             let mut results = Vec::new();
             let mut hash = HashSet::new();
@@ -130,10 +135,17 @@ impl RelicScannerWorker {
                     Ok(v) => v,
                     Err(e) => {
                         error!("识别错误: {}", e);
+                        if let Some(tx) = &result_tx {
+                            let _ = tx.send(None);
+                        }
                         continue;
                     },
                 };
 
+                if let Some(tx) = &result_tx {
+                    let _ = tx.send(Some(result.clone()));
+                }
+
                 if is_verbose {
                     info!("{:?}", result);
                 }
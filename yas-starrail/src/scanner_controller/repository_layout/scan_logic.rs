@@ -270,6 +270,11 @@ impl StarRailRepositoryScanController {
         }
     }
 
+    #[inline(always)]
+    pub fn system_control_mut(&mut self) -> &mut SystemControl {
+        &mut self.system_control
+    }
+
     pub fn move_to(&mut self, row: usize, col: usize) {
         let (row, col) = (row as u32, col as u32);
         let origin = self.game_info.window.to_rect_f64().origin();
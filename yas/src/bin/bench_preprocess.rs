@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, ValueEnum};
+use image::{ImageBuffer, Luma, RgbImage};
+
+use yas_core::ocr::{to_gray, PreprocessMode, PreprocessPipeline};
+
+type GrayImage = ImageBuffer<Luma<f32>, Vec<f32>>;
+
+/// The preprocessing to time, see `PreprocessPipeline`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Pipeline {
+    /// `PreprocessPipeline::line`, for every field
+    Normal,
+    /// `PreprocessPipeline::pending_line` with global binarization, for the gray fourth sub stat
+    Pending,
+    /// `PreprocessPipeline::pending_line` with adaptive binarization
+    PendingAdaptive,
+}
+
+impl Pipeline {
+    fn pipeline(&self) -> PreprocessPipeline {
+        match *self {
+            Pipeline::Normal => PreprocessPipeline::line(),
+            Pipeline::Pending => PreprocessPipeline::pending_line(PreprocessMode::Global),
+            Pipeline::PendingAdaptive => PreprocessPipeline::pending_line(PreprocessMode::Adaptive),
+        }
+    }
+}
+
+/// Times every preprocessing stage over a folder of field crops, e.g. the ones saved with YAS_OCR_DEBUG_SAVE
+/// or cut out of a screenshot, as a baseline for optimizing them
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Folder of crops, every png, jpg and bmp in it is used
+    #[arg(long)]
+    dir: PathBuf,
+
+    /// How often every crop goes through each stage, at least once
+    #[arg(long, default_value_t = 100, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    iterations: usize,
+
+    #[arg(long, value_enum, default_value_t = Pipeline::Normal)]
+    pipeline: Pipeline,
+}
+
+fn load_crops(args: &Args) -> Result<Vec<RgbImage>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&args.dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| ["png", "jpg", "jpeg", "bmp"].contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    paths.sort();
+
+    let crops = paths.iter().map(yas_core::utils::load_rgb8).collect::<Result<Vec<_>>>()?;
+    if crops.is_empty() {
+        return Err(anyhow!("no image in {:?}", args.dir));
+    }
+    Ok(crops)
+}
+
+/// Run `f` on a fresh copy of every input `iterations` times, only the calls themselves are timed
+fn time_stage<T: Clone, R>(inputs: &[T], iterations: usize, f: impl Fn(T) -> R) -> Duration {
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let copies = inputs.to_vec();
+        let start = Instant::now();
+        for input in copies {
+            std::hint::black_box(f(input));
+        }
+        total += start.elapsed();
+    }
+    total
+}
+
+fn report(name: &str, elapsed: Duration, calls: usize) {
+    let per_call = elapsed / calls as u32;
+    let throughput = calls as f64 / elapsed.as_secs_f64();
+    println!("{:<20} {:>10.1?} {:>12.2?}/call {:>12.0} calls/s", name, elapsed, per_call, throughput);
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let crops = load_crops(&args)?;
+    let calls = crops.len() * args.iterations;
+    println!("{} crops, {} iterations each, {:?} pipeline", crops.len(), args.iterations, args.pipeline);
+
+    let mut total = time_stage(&crops, args.iterations, |crop| to_gray(&crop));
+    report("to_gray", total, calls);
+
+    let mut images: Vec<GrayImage> = crops.iter().map(to_gray).collect();
+    for (name, step) in args.pipeline.pipeline().split() {
+        let elapsed = time_stage(&images, args.iterations, |im| step.run(im));
+        report(name, elapsed, calls);
+        total += elapsed;
+        // the next stage works on what this one made of the crops
+        images = images.into_iter().map(|im| step.run(im).0).collect();
+    }
+
+    report("total", total, calls);
+    Ok(())
+}
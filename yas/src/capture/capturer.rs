@@ -15,6 +15,12 @@ pub trait Capturer<T: image::GenericImage> {
         Ok(image.get_pixel(0, 0))
     }
 
+    /// The colors at `positions`, in the same order. Capturers where every capture is a system call
+    /// override it with `capture_colors_in_bounding_rect`
+    fn capture_colors(&self, positions: &[Pos<i32>]) -> Result<Vec<T::Pixel>> {
+        positions.iter().map(|&pos| self.capture_color(pos)).collect()
+    }
+
     fn capture_relative_to(&self, rect: Rect<i32>, relative_to: Pos<i32>) -> Result<T> {
         let new_rect = Rect {
             left: rect.left + relative_to.x,
@@ -25,3 +31,67 @@ pub trait Capturer<T: image::GenericImage> {
         self.capture_rect(new_rect)
     }
 }
+
+/// `Capturer::capture_colors` with a single capture of the smallest rect holding all `positions`.
+/// Meant for points close to each other, e.g. the samples of one detector
+pub fn capture_colors_in_bounding_rect<T, C>(capturer: &C, positions: &[Pos<i32>]) -> Result<Vec<T::Pixel>>
+where
+    T: image::GenericImage,
+    C: Capturer<T> + ?Sized,
+{
+    if positions.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let left = positions.iter().map(|p| p.x).min().unwrap();
+    let top = positions.iter().map(|p| p.y).min().unwrap();
+    let right = positions.iter().map(|p| p.x).max().unwrap();
+    let bottom = positions.iter().map(|p| p.y).max().unwrap();
+    let image = capturer.capture_rect(Rect {
+        left,
+        top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    })?;
+
+    Ok(positions.iter()
+        .map(|p| image.get_pixel((p.x - left) as u32, (p.y - top) as u32))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use image::{Rgb, RgbImage};
+
+    use super::*;
+
+    /// Each pixel encodes its screen position, counts the captures
+    struct GradientCapturer(Cell<usize>);
+
+    impl Capturer<RgbImage> for GradientCapturer {
+        fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+            self.0.set(self.0.get() + 1);
+            Ok(RgbImage::from_fn(rect.width as u32, rect.height as u32, |x, y| {
+                Rgb([(rect.left + x as i32) as u8, (rect.top + y as i32) as u8, 0])
+            }))
+        }
+    }
+
+    #[test]
+    fn test_capture_colors() {
+        let positions = [Pos::new(10, 20), Pos::new(3, 25), Pos::new(12, 21)];
+        let expected = vec![Rgb([10, 20, 0]), Rgb([3, 25, 0]), Rgb([12, 21, 0])];
+
+        let capturer = GradientCapturer(Cell::new(0));
+        assert_eq!(capturer.capture_colors(&positions).unwrap(), expected);
+        assert_eq!(capturer.0.get(), 3);
+
+        let capturer = GradientCapturer(Cell::new(0));
+        assert_eq!(capture_colors_in_bounding_rect(&capturer, &positions).unwrap(), expected);
+        assert_eq!(capturer.0.get(), 1);
+
+        assert!(capture_colors_in_bounding_rect(&capturer, &[]).unwrap().is_empty());
+    }
+}
@@ -2,8 +2,8 @@ use anyhow::Result;
 use image::{RgbaImage, RgbImage};
 use image::buffer::ConvertBuffer;
 use libwayshot::{WayshotConnection, CaptureRegion};
-use crate::capture::Capturer;
-use crate::positioning::Rect;
+use crate::capture::{capture_colors_in_bounding_rect, Capturer};
+use crate::positioning::{Pos, Rect};
 
 pub struct LibwayshotCapturer {
     conn: WayshotConnection,
@@ -35,4 +35,9 @@ impl Capturer<RgbImage> for LibwayshotCapturer {
         let rgba_result: RgbaImage = self.capture_rect(rect)?;
         Ok(rgba_result.convert())
     }
+
+    fn capture_colors(&self, positions: &[Pos<i32>]) -> Result<Vec<image::Rgb<u8>>> {
+        // both images are captured by this capturer, pick the one of this impl
+        capture_colors_in_bounding_rect::<RgbImage, _>(self, positions)
+    }
 }
@@ -1,10 +1,14 @@
 pub use stream_capturer::StreamingCapturer;
-pub use capturer::Capturer;
+pub use capturer::{capture_colors_in_bounding_rect, Capturer};
 pub use generic_capturer::GenericCapturer;
+pub use recording_capturer::RecordingCapturer;
+pub use pipe_capturer::{PipeCapturer, PIPE_FRAME_MAGIC};
 
 mod capturer;
 mod generic_capturer;
 mod stream_capturer;
+mod recording_capturer;
+mod pipe_capturer;
 
 // windows
 
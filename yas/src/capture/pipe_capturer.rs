@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+
+use anyhow::{anyhow, Context, Result};
+use image::{GenericImageView, RgbImage};
+use log::{error, info};
+
+use crate::capture::Capturer;
+use crate::positioning::Rect;
+
+/// Magic bytes every piped frame starts with
+pub const PIPE_FRAME_MAGIC: &[u8; 4] = b"YASF";
+
+#[derive(Default)]
+struct PipeState {
+    frame: Option<Arc<RgbImage>>,
+    frames: usize,
+    closed: Option<String>,
+}
+
+/// Captures from frames written to stdin or a named pipe by an external capture tool,
+/// for running where the screen can not be captured directly, e.g. inside a VM.
+///
+/// Each frame is the whole screen: `YASF`, width and height as little endian u32, then `width * height * 3`
+/// bytes of RGB. Frames are read on a thread of their own and every capture crops the latest one,
+/// so the tool can write frames as fast as it likes. A capture before the first frame waits for it
+#[derive(Clone)]
+pub struct PipeCapturer {
+    state: Arc<(Mutex<PipeState>, Condvar)>,
+}
+
+impl PipeCapturer {
+    /// Reads frames from `path`, `-` for stdin. A source can only be read once,
+    /// so opening the same path again returns a capturer sharing the first one's frames
+    pub fn open(path: &Path) -> Result<Self> {
+        static OPENED: OnceLock<Mutex<HashMap<PathBuf, PipeCapturer>>> = OnceLock::new();
+        let mut opened = OPENED.get_or_init(Default::default).lock().unwrap();
+        if let Some(capturer) = opened.get(path) {
+            return Ok(capturer.clone());
+        }
+
+        let capturer = if path == Path::new("-") {
+            info!("从标准输入读取截图帧");
+            Self::from_reader(std::io::stdin())
+        } else {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("open capture pipe {:?}", path))?;
+            info!("从 {:?} 读取截图帧", path);
+            Self::from_reader(file)
+        };
+        opened.insert(path.to_path_buf(), capturer.clone());
+        Ok(capturer)
+    }
+
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Self {
+        let state = Arc::new((Mutex::new(PipeState::default()), Condvar::new()));
+        let thread_state = state.clone();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let reason = loop {
+                match read_frame(&mut reader) {
+                    Ok(Some(frame)) => {
+                        let (lock, cvar) = &*thread_state;
+                        let mut state = lock.lock().unwrap();
+                        state.frame = Some(Arc::new(frame));
+                        state.frames += 1;
+                        cvar.notify_all();
+                    },
+                    Ok(None) => break String::from("end of stream"),
+                    Err(e) => {
+                        error!("读取截图帧失败: {:?}", e);
+                        break format!("{:#}", e);
+                    },
+                }
+            };
+
+            let (lock, cvar) = &*thread_state;
+            lock.lock().unwrap().closed = Some(reason);
+            cvar.notify_all();
+        });
+
+        PipeCapturer { state }
+    }
+
+    /// Frames read so far
+    pub fn frame_count(&self) -> usize {
+        self.state.0.lock().unwrap().frames
+    }
+
+    /// The latest frame, waiting for the first one. After the stream ends the last frame stays available
+    fn latest_frame(&self) -> Result<Arc<RgbImage>> {
+        let (lock, cvar) = &*self.state;
+        let state = cvar.wait_while(lock.lock().unwrap(), |s| s.frame.is_none() && s.closed.is_none()).unwrap();
+        match (&state.frame, &state.closed) {
+            (Some(frame), _) => Ok(frame.clone()),
+            (None, Some(reason)) => Err(anyhow!("capture pipe closed before the first frame: {}", reason)),
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+/// `None` at a clean end of stream, that is before the first byte of a frame
+fn read_frame<R: Read>(reader: &mut R) -> Result<Option<RgbImage>> {
+    let mut header = [0_u8; 12];
+    let mut filled = 0;
+    while filled < header.len() {
+        let n = reader.read(&mut header[filled..])?;
+        if n == 0 {
+            if filled == 0 {
+                return Ok(None);
+            }
+            return Err(anyhow!("truncated frame header"));
+        }
+        filled += n;
+    }
+
+    if &header[0..4] != PIPE_FRAME_MAGIC {
+        return Err(anyhow!("bad frame magic {:?}, expected {:?}", &header[0..4], PIPE_FRAME_MAGIC));
+    }
+    let width = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    let height = u32::from_le_bytes(header[8..12].try_into().unwrap());
+
+    let mut data = vec![0_u8; width as usize * height as usize * 3];
+    reader.read_exact(&mut data)
+        .with_context(|| format!("truncated {}x{} frame", width, height))?;
+    Ok(Some(RgbImage::from_raw(width, height, data).unwrap()))
+}
+
+impl Capturer<RgbImage> for PipeCapturer {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        let frame = self.latest_frame()?;
+        let inside = rect.left >= 0 && rect.top >= 0 && rect.width >= 0 && rect.height >= 0
+            && (rect.left + rect.width) as u32 <= frame.width()
+            && (rect.top + rect.height) as u32 <= frame.height();
+        if !inside {
+            return Err(anyhow!("capture rect {:?} is outside the {}x{} piped frame", rect, frame.width(), frame.height()));
+        }
+
+        Ok(frame.view(rect.left as u32, rect.top as u32, rect.width as u32, rect.height as u32).to_image())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use crate::positioning::Pos;
+
+    use super::*;
+
+    fn frame_bytes(width: u32, height: u32, color: [u8; 3]) -> Vec<u8> {
+        let mut bytes = PIPE_FRAME_MAGIC.to_vec();
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        for _ in 0..width * height {
+            bytes.extend_from_slice(&color);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_crop_latest_frame() {
+        let mut bytes = frame_bytes(8, 6, [1, 2, 3]);
+        bytes.extend(frame_bytes(8, 6, [200, 100, 50]));
+        let capturer = PipeCapturer::from_reader(std::io::Cursor::new(bytes));
+
+        // the stream ends after two frames, the last one stays available
+        while capturer.state.0.lock().unwrap().closed.is_none() {
+            std::thread::yield_now();
+        }
+        assert_eq!(capturer.frame_count(), 2);
+
+        let image = capturer.capture_relative_to(Rect::new(1, 1, 3, 2), Pos::new(2, 2)).unwrap();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(capturer.capture_color(Pos::new(7, 5)).unwrap(), Rgb([200, 100, 50]));
+        assert!(capturer.capture_rect(Rect::new(6, 0, 4, 4)).is_err());
+        assert!(capturer.capture_rect(Rect::new(-1, 0, 2, 2)).is_err());
+    }
+
+    #[test]
+    fn test_bad_stream() {
+        let capturer = PipeCapturer::from_reader(std::io::Cursor::new(Vec::new()));
+        let err = capturer.capture_rect(Rect::new(0, 0, 1, 1)).unwrap_err();
+        assert!(err.to_string().contains("before the first frame"));
+
+        let mut bytes = frame_bytes(2, 2, [0, 0, 0]);
+        bytes.truncate(14);
+        let capturer = PipeCapturer::from_reader(std::io::Cursor::new(bytes));
+        let err = capturer.capture_rect(Rect::new(0, 0, 1, 1)).unwrap_err();
+        assert!(err.to_string().contains("truncated 2x2 frame"), "{}", err);
+
+        let capturer = PipeCapturer::from_reader(std::io::Cursor::new(b"PNG\0\0\0\0\0\0\0\0\0".to_vec()));
+        assert!(capturer.capture_rect(Rect::new(0, 0, 1, 1)).unwrap_err().to_string().contains("bad frame magic"));
+    }
+}
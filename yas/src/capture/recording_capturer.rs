@@ -0,0 +1,110 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Context, Result};
+use image::RgbImage;
+use log::debug;
+
+use crate::capture::Capturer;
+use crate::positioning::Rect;
+
+/// Index of all recorded captures inside the record directory
+pub const RECORD_INDEX_FILE: &str = "captures.tsv";
+
+/// Wraps another capturer and records every capture, for reproducing capture related bugs.
+/// Each capture is logged, and if a directory is given, saved as `{prefix}_{index}.png`
+/// with a line `file<TAB>left<TAB>top<TAB>width<TAB>height` appended to `captures.tsv`
+pub struct RecordingCapturer<C> {
+    inner: C,
+    dir: Option<PathBuf>,
+    prefix: String,
+    count: AtomicUsize,
+}
+
+impl<C: Capturer<RgbImage>> RecordingCapturer<C> {
+    pub fn new(inner: C, dir: Option<PathBuf>, prefix: &str) -> Result<Self> {
+        if let Some(dir) = &dir {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("create capture record dir {:?}", dir))?;
+        }
+        Ok(Self {
+            inner,
+            dir,
+            prefix: String::from(prefix),
+            count: AtomicUsize::new(0),
+        })
+    }
+
+    /// Captures made so far
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn save(&self, dir: &Path, index: usize, rect: Rect<i32>, image: &RgbImage) -> Result<()> {
+        let file_name = format!("{}_{:05}.png", self.prefix, index);
+        image.save(dir.join(&file_name))?;
+
+        let mut index_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(RECORD_INDEX_FILE))?;
+        writeln!(index_file, "{}\t{}\t{}\t{}\t{}", file_name, rect.left, rect.top, rect.width, rect.height)?;
+
+        Ok(())
+    }
+}
+
+impl<C: Capturer<RgbImage>> Capturer<RgbImage> for RecordingCapturer<C> {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        let index = self.count.fetch_add(1, Ordering::Relaxed);
+        debug!("{} capture {}: {:?}", self.prefix, index, rect);
+
+        let image = self.inner.capture_rect(rect)?;
+        if let Some(dir) = &self.dir {
+            self.save(dir, index, rect, &image)
+                .with_context(|| format!("record capture {} to {:?}", index, dir))?;
+        }
+
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::positioning::Pos;
+
+    use super::*;
+
+    struct BlankCapturer;
+
+    impl Capturer<RgbImage> for BlankCapturer {
+        fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+            Ok(RgbImage::new(rect.width as u32, rect.height as u32))
+        }
+    }
+
+    #[test]
+    fn test_every_capture_recorded() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("records");
+
+        let capturer = RecordingCapturer::new(BlankCapturer, Some(dir.clone()), "test").unwrap();
+        capturer.capture_rect(Rect { left: 10, top: 20, width: 30, height: 40 }).unwrap();
+        capturer.capture_color(Pos { x: 5, y: 6 }).unwrap();
+        capturer.capture_relative_to(Rect { left: 1, top: 1, width: 2, height: 2 }, Pos { x: 100, y: 100 }).unwrap();
+
+        let index = std::fs::read_to_string(dir.join(RECORD_INDEX_FILE)).unwrap();
+        let lines: Vec<&str> = index.lines().collect();
+        let saved = (0..3).all(|i| dir.join(format!("test_{:05}.png", i)).exists());
+
+        assert_eq!(capturer.count(), 3);
+        assert!(saved);
+        assert_eq!(lines, vec![
+            "test_00000.png\t10\t20\t30\t40",
+            "test_00001.png\t5\t6\t1\t1",
+            "test_00002.png\t101\t101\t2\t2",
+        ]);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::capture::WinapiCapturer;
 use crate::capture::ScreenshotsCapturer;
-use crate::capture::Capturer;
-use crate::positioning::Rect;
+use crate::capture::{capture_colors_in_bounding_rect, Capturer};
+use crate::positioning::{Pos, Rect};
 use image::RgbImage;
 use anyhow::Result;
 use anyhow::anyhow;
@@ -34,4 +34,8 @@ impl Capturer<RgbImage> for WindowsCapturer {
             return result;
         }
     }
+
+    fn capture_colors(&self, positions: &[Pos<i32>]) -> Result<Vec<image::Rgb<u8>>> {
+        capture_colors_in_bounding_rect(self, positions)
+    }
 }
@@ -33,23 +33,30 @@ impl DrawCaptureRegion for Pos<f64> {
 
 impl DrawCaptureRegion for Rect<f64> {
     fn draw_capture_region(&self, image: &mut image::RgbImage) {
-        let red = image::Rgb([255, 0, 0]);
-
-        let left = self.left as u32;
-        let top = self.top as u32;
-        let width = self.width as u32;
-        let height = self.height as u32;
-        let bottom = top + height;
-        let right = left + width;
-
-        for x in left..right {
-            image.put_pixel(x, top, red);
-            image.put_pixel(x, bottom, red);
-        }
+        draw_rect(image, self, image::Rgb([255, 0, 0]));
+    }
+}
+
+/// Draw the outline of `rect` in `color`, the parts outside the image are left out
+pub fn draw_rect(image: &mut image::RgbImage, rect: &Rect<f64>, color: image::Rgb<u8>) {
+    let left = rect.left as i64;
+    let top = rect.top as i64;
+    let right = (rect.left + rect.width) as i64;
+    let bottom = (rect.top + rect.height) as i64;
 
-        for y in top..bottom {
-            image.put_pixel(left, y, red);
-            image.put_pixel(right, y, red);
+    let mut put = |x: i64, y: i64| {
+        if x >= 0 && y >= 0 && x < image.width() as i64 && y < image.height() as i64 {
+            image.put_pixel(x as u32, y as u32, color);
         }
+    };
+
+    for x in left..=right {
+        put(x, top);
+        put(x, bottom);
+    }
+
+    for y in top..=bottom {
+        put(left, y);
+        put(right, y);
     }
-}
\ No newline at end of file
+}
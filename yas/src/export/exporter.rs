@@ -1,7 +1,8 @@
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use anyhow::{Context, Result};
 use log::error;
 
 use crate::export::{ExportItem, ExportStatistics, StatisticItem};
@@ -53,4 +54,50 @@ impl ExportAssets {
 
         stat
     }
+
+    /// Pack all assets into a single zip file, each asset is stored under its file name
+    pub fn save_bundle(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("无法创建文件 {:?}", path))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        for item in self.assets.iter() {
+            let name = match item.filename.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => item.filename.to_string_lossy().to_string(),
+            };
+            zip.start_file(name, options)?;
+            zip.write_all(&item.contents)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_bundle_contains_all_assets() {
+        let mut assets = ExportAssets::new();
+        assets.add_asset(None, PathBuf::from("out/mona.json"), b"{}".to_vec(), None);
+        assets.add_asset(None, PathBuf::from("out/artifacts.csv"), b"a,b".to_vec(), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.zip");
+        assets.save_bundle(&path).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut names = archive.file_names().map(String::from).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["artifacts.csv", "mona.json"]);
+
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("artifacts.csv").unwrap(), &mut contents).unwrap();
+        assert_eq!(contents, "a,b");
+    }
 }
@@ -1,4 +1,4 @@
-use crate::game_info::{ResolutionFamily, UI};
+use crate::game_info::{ResolutionFamily, UI, WindowMode};
 use crate::game_info::ui::Platform;
 use crate::positioning::Rect;
 
@@ -9,4 +9,5 @@ pub struct GameInfo {
     pub is_cloud: bool,
     pub ui: UI,
     pub platform: Platform,
+    pub window_mode: WindowMode,
 }
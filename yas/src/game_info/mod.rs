@@ -3,8 +3,10 @@ mod os;
 mod game_info_builder;
 mod ui;
 mod resolution_family;
+mod window_mode;
 
 pub use game_info_builder::GameInfoBuilder;
 pub use ui::{UI, Platform};
 pub use resolution_family::ResolutionFamily;
+pub use window_mode::WindowMode;
 pub use game_info::GameInfo;
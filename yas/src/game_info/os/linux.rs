@@ -1,6 +1,6 @@
 use anyhow::{Result, anyhow};
 
-use crate::game_info::{GameInfo, Platform, UI, ResolutionFamily};
+use crate::game_info::{GameInfo, Platform, UI, ResolutionFamily, WindowMode};
 use crate::positioning::Rect;
 
 pub fn get_game_info() -> Result<GameInfo> {
@@ -31,6 +31,23 @@ pub fn get_game_info() -> Result<GameInfo> {
     let height = info.next().unwrap().parse().unwrap();
 
     let rect = Rect::new(left, top, width, height);
+
+    let root_size = String::from_utf8(
+            std::process::Command::new("sh")
+                .arg("-c")
+                .arg(r#" xwininfo -root|grep -E "^ +(Width|Height):"|tr -cd "0-9\n" "#)
+                .output()
+                .unwrap()
+                .stdout,
+        )?;
+    let mut root_info = root_size.split("\n");
+    let root_width = root_info.next().and_then(|s| s.parse().ok()).unwrap_or(width);
+    let root_height = root_info.next().and_then(|s| s.parse().ok()).unwrap_or(height);
+    let window_mode = WindowMode::detect(
+        rect.to_rect_i32(),
+        Rect::new(0, 0, root_width, root_height).to_rect_i32(),
+        false,
+    );
     let rf = ResolutionFamily::new(rect.size()).ok_or(anyhow!("unknown resolution family"))?;
 
     Ok(GameInfo {
@@ -39,5 +56,6 @@ pub fn get_game_info() -> Result<GameInfo> {
         is_cloud: false,
         ui: UI::Desktop,
         platform: Platform::Linux,
+        window_mode,
     })
 }
@@ -1,5 +1,5 @@
 use crate::{common::utils::*, core::ui::Resolution};
-use crate::game_info::{GameInfo, Platform};
+use crate::game_info::{GameInfo, Platform, WindowMode};
 
 pub fn get_game_info() -> GameInfo {
     let (pid, ui) = get_pid_and_ui();
@@ -13,6 +13,7 @@ pub fn get_game_info() -> GameInfo {
         resolution_family: Resolution::new(rect.size),
         is_cloud: false,
         ui,
-        platform: Platform::MacOS
+        platform: Platform::MacOS,
+        window_mode: WindowMode::Windowed,
     }
 }
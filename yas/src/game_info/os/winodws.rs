@@ -1,7 +1,8 @@
 use std::io::stdin;
-use crate::game_info::{GameInfo, ResolutionFamily, UI, Platform};
+use crate::game_info::{GameInfo, ResolutionFamily, UI, Platform, WindowMode};
 use crate::utils;
 use anyhow::{Result, anyhow};
+use log::{info, warn};
 use windows_sys::Win32::Foundation::HWND;
 use windows_sys::Win32::UI::WindowsAndMessaging::*;
 
@@ -78,11 +79,21 @@ pub fn get_game_info(window_names: &[&str]) -> Result<GameInfo> {
         return Err(anyhow!("Resolution not supported: {}x{}", rect.width, rect.height));
     }
 
+    let window_mode = match utils::get_monitor_rect(hwnd) {
+        Ok(monitor) => WindowMode::detect(rect, monitor, utils::is_d3d_fullscreen()),
+        Err(_) => WindowMode::Windowed,
+    };
+    info!("window mode: {:?}, recommended capturer: {}", window_mode, window_mode.recommended_capturer());
+    if !window_mode.is_capturable() {
+        warn!("游戏处于独占全屏模式，截图可能为全黑，建议切换为无边框窗口或窗口模式");
+    }
+
     Ok(GameInfo {
         window: rect,
         resolution_family: resolution_family.unwrap(),
         is_cloud,
         ui: UI::Desktop,
-        platform: Platform::Windows
+        platform: Platform::Windows,
+        window_mode,
     })
 }
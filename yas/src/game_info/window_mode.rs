@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use crate::positioning::Rect;
+
+/// How the game window is presented, which affects what capture backends can see
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum WindowMode {
+    Windowed,
+    Borderless,
+    /// exclusive fullscreen may return black images with GDI based capturers
+    ExclusiveFullscreen,
+}
+
+impl WindowMode {
+    /// Detect the window mode from the client rect of the game window and the rect of the monitor it is on.
+    /// `exclusive` is an OS hint that a D3D application currently owns the display
+    pub fn detect(window: Rect<i32>, monitor: Rect<i32>, exclusive: bool) -> Self {
+        let covers_monitor = window.left <= monitor.left
+            && window.top <= monitor.top
+            && window.left + window.width >= monitor.left + monitor.width
+            && window.top + window.height >= monitor.top + monitor.height;
+
+        if !covers_monitor {
+            WindowMode::Windowed
+        } else if exclusive {
+            WindowMode::ExclusiveFullscreen
+        } else {
+            WindowMode::Borderless
+        }
+    }
+
+    /// Whether screen capture is expected to work in this mode
+    pub fn is_capturable(&self) -> bool {
+        *self != WindowMode::ExclusiveFullscreen
+    }
+
+    /// A human-readable recommendation of the capture backend for this mode
+    pub fn recommended_capturer(&self) -> &'static str {
+        match *self {
+            WindowMode::Windowed | WindowMode::Borderless => "GDI",
+            WindowMode::ExclusiveFullscreen => "Windows Graphics Capture（建议切换为无边框窗口模式）",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: Rect<i32> = Rect { left: 0, top: 0, width: 1920, height: 1080 };
+
+    #[test]
+    fn test_detect_windowed() {
+        let window = Rect::new(100, 100, 1600, 900);
+        assert_eq!(WindowMode::detect(window, MONITOR, false), WindowMode::Windowed);
+        // the exclusive hint alone does not make a smaller window fullscreen
+        assert_eq!(WindowMode::detect(window, MONITOR, true), WindowMode::Windowed);
+    }
+
+    #[test]
+    fn test_detect_fullscreen() {
+        assert_eq!(WindowMode::detect(MONITOR, MONITOR, false), WindowMode::Borderless);
+        assert_eq!(WindowMode::detect(MONITOR, MONITOR, true), WindowMode::ExclusiveFullscreen);
+        assert!(!WindowMode::ExclusiveFullscreen.is_capturable());
+    }
+
+    #[test]
+    fn test_detect_secondary_monitor() {
+        let monitor = Rect::new(1920, 0, 2560, 1440);
+        assert_eq!(WindowMode::detect(monitor, monitor, false), WindowMode::Borderless);
+        assert_eq!(WindowMode::detect(MONITOR, monitor, false), WindowMode::Windowed);
+    }
+}
@@ -4,6 +4,8 @@ mod paddle_paddle_model;
 
 pub use yas_model::yas_ocr_model::YasOCRModel;
 pub use yas_model::yas_ocr_model::yas_ocr_model;
+pub use yas_model::model_cache::SharedYasOCRModel;
+pub use yas_model::preprocess::{preprocess_result, to_gray, PreprocessMode, PreprocessPipeline};
 pub use traits::ImageToText;
 pub use paddle_paddle_model::PPOCRModel;
 pub use paddle_paddle_model::PPOCRChV4RecInfer;
@@ -5,12 +5,36 @@ use anyhow::Result;
 pub trait ImageToText<ImageType> {
     fn image_to_text(&self, image: &ImageType, is_preprocessed: bool) -> Result<String>;
 
+    /// Same as image_to_text, also returns how confident the model is in the text (0~1).
+    /// Models that cannot tell report 1.0
+    fn image_to_text_with_confidence(&self, image: &ImageType, is_preprocessed: bool) -> Result<(String, f32)> {
+        Ok((self.image_to_text(image, is_preprocessed)?, 1.0))
+    }
+
     /// Same as image_to_text but with preprocessing tuned for gray (待激活) substat line.
     /// Use for the fourth substat rect; no hardcoded stat names.
     fn image_to_text_pending_line(&self, image: &ImageType) -> Result<String> {
         self.image_to_text(image, false)
     }
 
+    /// image_to_text_pending_line with a confidence, see image_to_text_with_confidence
+    fn image_to_text_pending_line_with_confidence(&self, image: &ImageType) -> Result<(String, f32)> {
+        Ok((self.image_to_text_pending_line(image)?, 1.0))
+    }
+
+    /// Same as image_to_text, but only characters in `allowed_chars` are recognized,
+    /// e.g. `"0123456789.,%"` for a stat value, so a `0` is not read as `O`
+    fn image_to_text_constrained(&self, image: &ImageType, allowed_chars: &str) -> Result<String> {
+        Ok(self.image_to_text_constrained_with_confidence(image, allowed_chars)?.0)
+    }
+
+    /// image_to_text_constrained with a confidence, see image_to_text_with_confidence.
+    /// Models that cannot constrain their decoding drop the other characters from the result
+    fn image_to_text_constrained_with_confidence(&self, image: &ImageType, allowed_chars: &str) -> Result<(String, f32)> {
+        let (text, confidence) = self.image_to_text_with_confidence(image, false)?;
+        Ok((text.chars().filter(|c| allowed_chars.contains(*c)).collect(), confidence))
+    }
+
     fn get_average_inference_time(&self) -> Option<Duration>;
 }
 
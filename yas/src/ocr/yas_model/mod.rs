@@ -1,3 +1,4 @@
+pub mod model_cache;
 pub mod preprocess;
 pub mod yas_ocr_model;
 
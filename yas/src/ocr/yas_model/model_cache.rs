@@ -0,0 +1,122 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use log::info;
+use once_cell::sync::Lazy;
+
+use super::preprocess::PreprocessMode;
+use super::yas_ocr_model::YasOCRModel;
+use crate::ocr::traits::ImageToText;
+
+/// Models loaded in this process, so that e.g. the genshin and starrail scanners of the `yas` binary,
+/// or a scanner and its recognition thread, load the same model only once
+static MODELS: Lazy<Mutex<HashMap<u64, Arc<Mutex<YasOCRModel>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A `YasOCRModel` from the process level cache. The model is shared by every handle loaded from the same data,
+/// each handle keeps its own pending line mode
+#[derive(Clone)]
+pub struct SharedYasOCRModel {
+    model: Arc<Mutex<YasOCRModel>>,
+    pending_line_mode: PreprocessMode,
+}
+
+fn get_or_load(key: u64, load: impl FnOnce() -> Result<YasOCRModel>) -> Result<SharedYasOCRModel> {
+    let mut models = MODELS.lock().map_err(|_| anyhow!("OCR model cache poisoned"))?;
+
+    let model = match models.get(&key) {
+        Some(model) => {
+            info!("使用已加载的 OCR 模型");
+            model.clone()
+        },
+        None => {
+            let now = SystemTime::now();
+            let model = Arc::new(Mutex::new(load()?));
+            info!("OCR 模型加载耗时 {:?}", now.elapsed().unwrap_or_default());
+            models.insert(key, model.clone());
+            model
+        },
+    };
+
+    Ok(SharedYasOCRModel {
+        model,
+        pending_line_mode: PreprocessMode::default(),
+    })
+}
+
+impl SharedYasOCRModel {
+    /// Keyed by the content, the same embedded model is loaded once even if it is included by several crates
+    pub fn new(model: &[u8], index_to_word: &str) -> Result<Self> {
+        let mut hasher = DefaultHasher::new();
+        model.hash(&mut hasher);
+        index_to_word.hash(&mut hasher);
+        get_or_load(hasher.finish(), || YasOCRModel::new(model, index_to_word))
+    }
+
+    /// Keyed by the paths, see `YasOCRModel::from_files`
+    pub fn from_files(model_path: &Path, index_to_word_path: &Path) -> Result<Self> {
+        let mut hasher = DefaultHasher::new();
+        for path in [model_path, index_to_word_path] {
+            path.canonicalize().unwrap_or_else(|_| path.to_path_buf()).hash(&mut hasher);
+        }
+        get_or_load(hasher.finish(), || YasOCRModel::from_files(model_path, index_to_word_path))
+    }
+
+    /// Use `mode` to binarize the pending (待激活) line with this handle, the default is global thresholding
+    pub fn with_pending_line_mode(mut self, mode: PreprocessMode) -> Self {
+        self.pending_line_mode = mode;
+        self
+    }
+
+    fn with_model<T>(&self, f: impl FnOnce(&YasOCRModel) -> Result<T>) -> Result<T> {
+        let model = self.model.lock().map_err(|_| anyhow!("OCR model poisoned"))?;
+        f(&model)
+    }
+}
+
+impl ImageToText<RgbImage> for SharedYasOCRModel {
+    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
+        self.with_model(|model| model.image_to_text(image, is_preprocessed))
+    }
+
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<(String, f32)> {
+        self.with_model(|model| model.image_to_text_with_confidence(image, is_preprocessed))
+    }
+
+    fn image_to_text_pending_line(&self, image: &RgbImage) -> Result<String> {
+        Ok(self.image_to_text_pending_line_with_confidence(image)?.0)
+    }
+
+    fn image_to_text_pending_line_with_confidence(&self, image: &RgbImage) -> Result<(String, f32)> {
+        self.with_model(|model| model.pending_line_with_confidence(image, self.pending_line_mode))
+    }
+
+    fn image_to_text_constrained_with_confidence(&self, image: &RgbImage, allowed_chars: &str) -> Result<(String, f32)> {
+        self.with_model(|model| model.image_to_text_constrained_with_confidence(image, allowed_chars))
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.model.lock().ok()?.get_average_inference_time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_model_loaded_once() {
+        let model = include_bytes!("../paddle_paddle_model/ch_PP-OCRv4_rec_infer.onnx");
+        let a = SharedYasOCRModel::new(model, r#"{"0": "-"}"#).unwrap();
+        let b = SharedYasOCRModel::new(model, r#"{"0": "-"}"#).unwrap();
+        assert!(Arc::ptr_eq(&a.model, &b.model));
+
+        let other = SharedYasOCRModel::new(model, r#"{"0": "-", "1": "a"}"#).unwrap();
+        assert!(!Arc::ptr_eq(&a.model, &other.model));
+    }
+}
@@ -11,21 +11,35 @@ pub const BINARIZE_THRESHOLD: f32 = 0.53;
 /// Global binarization threshold for pending (待激活) line; often lower to keep gray as text.
 pub const BINARIZE_THRESHOLD_PENDING: f32 = 0.5;
 
-/// convert rgb image to f32 gray image
+/// Side length of the window the local mean is taken over in adaptive binarization (on the 32px high image).
+pub const ADAPTIVE_WINDOW: u32 = 15;
+
+/// Adaptive binarization: a pixel is text if it is brighter than its local mean by at least this much.
+pub const ADAPTIVE_OFFSET: f32 = 0.1;
+
+/// How a preprocessed line is binarized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreprocessMode {
+    /// one threshold for the whole image
+    #[default]
+    Global,
+    /// threshold from the mean of each pixel's neighbourhood, for unevenly lit backgrounds
+    Adaptive,
+}
+
+/// convert rgb image to f32 gray image.
+/// Goes over the pixel buffer in row-major order, so there is no per-pixel bounds check and the loop can be vectorized
 pub fn to_gray(raw: &RgbImage) -> ImageBuffer<Luma<f32>, Vec<f32>> {
-    let mut new_gray: ImageBuffer<Luma<f32>, Vec<f32>> = ImageBuffer::new(raw.width(), raw.height());
-    for x in 0..raw.width() {
-        for y in 0..raw.height() {
-            let rgb = raw.get_pixel(x, y);
+    let data: Vec<f32> = raw.as_raw()
+        .chunks_exact(3)
+        .map(|rgb| {
             let r = rgb[0] as f32 / 255.0;
             let g = rgb[1] as f32 / 255.0;
             let b = rgb[2] as f32 / 255.0;
-            let gray = r * 0.2989 + g * 0.5870 + b * 0.1140;
-            let grayp = new_gray.get_pixel_mut(x, y);
-            grayp[0] = gray;
-        }
-    }
-    new_gray
+            r * 0.2989 + g * 0.5870 + b * 0.1140
+        })
+        .collect();
+    ImageBuffer::from_raw(raw.width(), raw.height(), data).unwrap()
 }
 
 /// normalize an f32 gray image
@@ -103,8 +117,8 @@ fn crop(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32>, Vec<f32
     im.view(min_col, min_row, new_width, new_height).to_image()
 }
 
-/// resize an f32 gray image to 384 * 32, if not wide enough, then pad with background
-fn resize_and_pad(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+/// resize an f32 gray image to `height` keeping the aspect ratio, then pad with background to `width` * `height`
+fn resize_and_pad(im: &ImageBuffer<Luma<f32>, Vec<f32>>, width: u32, height: u32) -> ImageBuffer<Luma<f32>, Vec<f32>> {
     let w = im.width();
     let h = im.height();
     // Hacking: incomplete image seems produce correct result.
@@ -113,11 +127,11 @@ fn resize_and_pad(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32
     // } else {
     //     std::cmp::min((32.0 / h as f64 * w as f64) as u32, 384)
     // };
-    let new_width = w * 32 / h;
-    let new_height = 32;
+    let new_width = w * height / h;
+    let new_height = height;
     let img = imageops::resize(im, new_width, new_height, image::imageops::FilterType::Triangle);
-    let data: Vec<f32> = vec![0.0; 32 * 384];
-    let mut padded_im = ImageBuffer::from_vec(384, 32, data).unwrap();
+    let data: Vec<f32> = vec![0.0; (width * height) as usize];
+    let mut padded_im = ImageBuffer::from_vec(width, height, data).unwrap();
     imageops::overlay(&mut padded_im, &img, 0, 0);
     padded_im
 }
@@ -129,6 +143,47 @@ fn binarize(im: &mut ImageBuffer<Luma<f32>, Vec<f32>>, threshold: f32) {
     }
 }
 
+/// Local threshold binarization: pixel >= mean of the `window` x `window` neighbourhood + `offset` -> 1, else 0.
+/// The mean is computed from an integral image, the window is clamped at the borders.
+pub fn binarize_adaptive(im: &mut ImageBuffer<Luma<f32>, Vec<f32>>, window: u32, offset: f32) {
+    let width = im.width() as usize;
+    let height = im.height() as usize;
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    // integral[(y + 1) * (width + 1) + (x + 1)] = sum of pixels in [0, x] x [0, y]
+    let stride = width + 1;
+    let mut integral = vec![0.0_f64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0.0;
+        for x in 0..width {
+            row_sum += im.get_pixel(x as u32, y as u32)[0] as f64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let half = (window / 2) as usize;
+    let mut result = im.clone();
+    for y in 0..height {
+        let top = y.saturating_sub(half);
+        let bottom = (y + half + 1).min(height);
+        for x in 0..width {
+            let left = x.saturating_sub(half);
+            let right = (x + half + 1).min(width);
+
+            let sum = integral[bottom * stride + right] - integral[top * stride + right]
+                - integral[bottom * stride + left] + integral[top * stride + left];
+            let mean = (sum / ((bottom - top) * (right - left)) as f64) as f32;
+
+            let p = im.get_pixel(x as u32, y as u32)[0];
+            result.get_pixel_mut(x as u32, y as u32)[0] = if p >= mean + offset { 1.0 } else { 0.0 };
+        }
+    }
+
+    *im = result;
+}
+
 static DEBUG_SAVE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 /// If env YAS_OCR_DEBUG_SAVE=1 (or "true"), save binarized image to dir from YAS_OCR_DEBUG_DIR (default: debug_binarized).
@@ -152,31 +207,305 @@ pub fn save_binarized_for_debug_if_enabled(im: &ImageBuffer<Luma<f32>, Vec<f32>>
     eprintln!("[YAS OCR] saved binarized debug image: {}", path.display());
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PreprocessStep {
+    Normalize { auto_inverse: bool },
+    Crop,
+    Resize { width: u32, height: u32 },
+    Binarize { threshold: f32 },
+    BinarizeAdaptive { window: u32, offset: f32 },
+}
+
+/// A sequence of preprocessing steps applied to an f32 gray image, e.g.
+/// `PreprocessPipeline::new().normalize(true).resize(384, 32).binarize(0.5)` to skip cropping an already tight line.
+/// `pre_process` and `pre_process_pending_line` are the presets `line` and `pending_line`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PreprocessPipeline {
+    steps: Vec<PreprocessStep>,
+    debug_label: Option<&'static str>,
+}
+
+impl PreprocessPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The preset of `pre_process`
+    pub fn line() -> Self {
+        Self::new()
+            .normalize(true)
+            .crop()
+            .normalize(false)
+            .resize(384, 32)
+            .binarize(BINARIZE_THRESHOLD)
+            .debug_label("normal")
+    }
+
+    /// The preset of `pre_process_pending_line_with_mode`
+    pub fn pending_line(mode: PreprocessMode) -> Self {
+        let pipeline = Self::new()
+            .normalize(true)
+            .crop()
+            .normalize(false)
+            .resize(384, 32);
+        let pipeline = match mode {
+            PreprocessMode::Global => pipeline.binarize(BINARIZE_THRESHOLD_PENDING),
+            PreprocessMode::Adaptive => pipeline.binarize_adaptive(ADAPTIVE_WINDOW, ADAPTIVE_OFFSET),
+        };
+        pipeline.debug_label("pending")
+    }
+
+    /// Stretch the values to 0~1, inverted if `auto_inverse` and the background is bright
+    pub fn normalize(mut self, auto_inverse: bool) -> Self {
+        self.steps.push(PreprocessStep::Normalize { auto_inverse });
+        self
+    }
+
+    /// Cut the image down to where there is text
+    pub fn crop(mut self) -> Self {
+        self.steps.push(PreprocessStep::Crop);
+        self
+    }
+
+    /// Scale to `height` keeping the aspect ratio and pad with background to `width`
+    pub fn resize(mut self, width: u32, height: u32) -> Self {
+        self.steps.push(PreprocessStep::Resize { width, height });
+        self
+    }
+
+    /// Global binarization, see `BINARIZE_THRESHOLD`
+    pub fn binarize(mut self, threshold: f32) -> Self {
+        self.steps.push(PreprocessStep::Binarize { threshold });
+        self
+    }
+
+    /// Local binarization, see `binarize_adaptive`
+    pub fn binarize_adaptive(mut self, window: u32, offset: f32) -> Self {
+        self.steps.push(PreprocessStep::BinarizeAdaptive { window, offset });
+        self
+    }
+
+    /// Save the result under this label when YAS_OCR_DEBUG_SAVE is set, see `save_binarized_for_debug_if_enabled`
+    pub fn debug_label(mut self, label: &'static str) -> Self {
+        self.debug_label = Some(label);
+        self
+    }
+
+    /// Each step as a pipeline of its own with the name of the step, for timing them one by one.
+    /// Running them in order gives the same image as `run`
+    pub fn split(&self) -> Vec<(&'static str, PreprocessPipeline)> {
+        self.steps.iter()
+            .map(|&step| {
+                let name = match step {
+                    PreprocessStep::Normalize { .. } => "normalize",
+                    PreprocessStep::Crop => "crop",
+                    PreprocessStep::Resize { .. } => "resize_and_pad",
+                    PreprocessStep::Binarize { .. } => "binarize",
+                    PreprocessStep::BinarizeAdaptive { .. } => "binarize_adaptive",
+                };
+                (name, PreprocessPipeline { steps: vec![step], debug_label: None })
+            })
+            .collect()
+    }
+
+    /// Apply the steps in order. The flag is false, and the image returned as is,
+    /// if a normalize that comes first finds a uniform image, i.e. there is nothing to read
+    pub fn run(&self, im: ImageBuffer<Luma<f32>, Vec<f32>>) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+        let mut im = im;
+        for (index, step) in self.steps.iter().enumerate() {
+            match *step {
+                PreprocessStep::Normalize { auto_inverse } => {
+                    if !normalize(&mut im, auto_inverse) && index == 0 {
+                        return (im, false);
+                    }
+                },
+                PreprocessStep::Crop => im = crop(&im),
+                PreprocessStep::Resize { width, height } => im = resize_and_pad(&im, width, height),
+                PreprocessStep::Binarize { threshold } => binarize(&mut im, threshold),
+                PreprocessStep::BinarizeAdaptive { window, offset } => binarize_adaptive(&mut im, window, offset),
+            }
+        }
+        if let Some(label) = self.debug_label {
+            save_binarized_for_debug_if_enabled(&im, label);
+        }
+        (im, true)
+    }
+}
+
 /// transform an f32 gray image to a preprocessed image
 pub fn pre_process(im: ImageBuffer<Luma<f32>, Vec<f32>>) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
-    let mut im = im;
-    if !normalize(&mut im, true) {
-        return (im, false);
-    }
-    let mut im = crop(&im);
-    normalize(&mut im, false);
-    let mut im = resize_and_pad(&im);
-    binarize(&mut im, BINARIZE_THRESHOLD);
-    save_binarized_for_debug_if_enabled(&im, "normal");
-    (im, true)
+    PreprocessPipeline::line().run(im)
 }
 
 /// Same as `pre_process` but uses BINARIZE_THRESHOLD_PENDING for the fourth substat (e.g. 待激活).
 pub fn pre_process_pending_line(im: ImageBuffer<Luma<f32>, Vec<f32>>) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
-    let mut im = im;
-    if !normalize(&mut im, true) {
-        return (im, false);
-    }
-    let mut im = crop(&im);
-    // println!("shape after first crop: {:?} x {:?}", im.width(), im.height());
-    normalize(&mut im, false);
-    let mut im = resize_and_pad(&im);
-    binarize(&mut im, BINARIZE_THRESHOLD_PENDING);
-    save_binarized_for_debug_if_enabled(&im, "pending");
-    (im, true)
+    pre_process_pending_line_with_mode(im, PreprocessMode::Global)
+}
+
+/// `pre_process_pending_line` with a choice of binarization, see `PreprocessMode`.
+pub fn pre_process_pending_line_with_mode(im: ImageBuffer<Luma<f32>, Vec<f32>>, mode: PreprocessMode) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+    PreprocessPipeline::pending_line(mode).run(im)
+}
+
+/// Run only the preprocessing of `raw` (the pending-line variant if `pending`) and return the binarized 384x32 image.
+/// None if the crop is blank (uniform color), so callers can tell "nothing there" from "OCR read nothing"
+pub fn preprocess_result(raw: &RgbImage, pending: bool) -> Option<RgbImage> {
+    let gray = to_gray(raw);
+    let (im, ok) = if pending {
+        pre_process_pending_line(gray)
+    } else {
+        pre_process(gray)
+    };
+    if !ok {
+        return None;
+    }
+
+    Some(RgbImage::from_fn(im.width(), im.height(), |x, y| {
+        let v = (im.get_pixel(x, y)[0].clamp(0.0, 1.0) * 255.0) as u8;
+        image::Rgb([v, v, v])
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dark-to-bright background with a faint stroke on the dark side and a bright stroke on the bright side,
+    /// like gray text fading over an unevenly lit panel
+    fn uneven_line() -> ImageBuffer<Luma<f32>, Vec<f32>> {
+        ImageBuffer::from_fn(60, 20, |x, _| {
+            let background = x as f32 / 60.0 * 0.6;
+            let v = match x {
+                10 | 11 => 0.45,
+                50 | 51 => 0.95,
+                _ => background,
+            };
+            Luma([v])
+        })
+    }
+
+    fn text_columns(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Vec<u32> {
+        (0..im.width()).filter(|&x| im.get_pixel(x, 10)[0] == 1.0).collect()
+    }
+
+    #[test]
+    fn test_global_binarize_fails_on_uneven_background() {
+        let mut im = uneven_line();
+        binarize(&mut im, BINARIZE_THRESHOLD_PENDING);
+        let columns = text_columns(&im);
+
+        // the faint stroke is lost and the bright background is taken as text
+        assert!(!columns.contains(&10));
+        assert!(columns.contains(&55));
+    }
+
+    #[test]
+    fn test_adaptive_binarize_keeps_strokes_only() {
+        let mut im = uneven_line();
+        binarize_adaptive(&mut im, ADAPTIVE_WINDOW, ADAPTIVE_OFFSET);
+
+        assert_eq!(text_columns(&im), vec![10, 11, 50, 51]);
+    }
+
+    #[test]
+    fn test_preprocess_result_uniform_crop_is_none() {
+        let blank = RgbImage::from_pixel(120, 24, image::Rgb([236, 229, 216]));
+        assert!(preprocess_result(&blank, false).is_none());
+        assert!(preprocess_result(&blank, true).is_none());
+
+        let mut text = blank.clone();
+        for y in 6..18 {
+            text.put_pixel(30, y, image::Rgb([60, 60, 60]));
+        }
+        let processed = preprocess_result(&text, false).unwrap();
+        assert_eq!(processed.dimensions(), (384, 32));
+    }
+
+    /// `to_gray` before it went over the buffer directly
+    fn old_to_gray(raw: &RgbImage) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+        let mut new_gray: ImageBuffer<Luma<f32>, Vec<f32>> = ImageBuffer::new(raw.width(), raw.height());
+        for x in 0..raw.width() {
+            for y in 0..raw.height() {
+                let rgb = raw.get_pixel(x, y);
+                let r = rgb[0] as f32 / 255.0;
+                let g = rgb[1] as f32 / 255.0;
+                let b = rgb[2] as f32 / 255.0;
+                new_gray.get_pixel_mut(x, y)[0] = r * 0.2989 + g * 0.5870 + b * 0.1140;
+            }
+        }
+        new_gray
+    }
+
+    #[test]
+    fn test_to_gray_matches_old() {
+        let images = [
+            RgbImage::from_fn(120, 24, |x, y| image::Rgb([(x * 2) as u8, (y * 10) as u8, ((x * y) % 256) as u8])),
+            RgbImage::from_pixel(7, 3, image::Rgb([236, 229, 216])),
+            RgbImage::from_fn(1, 50, |_, y| image::Rgb([255 - y as u8, 0, y as u8 * 5])),
+            RgbImage::new(0, 0),
+        ];
+        for image in images.iter() {
+            // bit for bit, not just close
+            let new: Vec<u32> = to_gray(image).as_raw().iter().map(|v| v.to_bits()).collect();
+            let old: Vec<u32> = old_to_gray(image).as_raw().iter().map(|v| v.to_bits()).collect();
+            assert_eq!(new, old);
+            assert_eq!(to_gray(image).dimensions(), image.dimensions());
+        }
+    }
+
+    /// `pre_process` before it was a pipeline
+    fn old_pre_process(im: ImageBuffer<Luma<f32>, Vec<f32>>, threshold: f32) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+        let mut im = im;
+        if !normalize(&mut im, true) {
+            return (im, false);
+        }
+        let mut im = crop(&im);
+        normalize(&mut im, false);
+        let mut im = resize_and_pad(&im, 384, 32);
+        binarize(&mut im, threshold);
+        (im, true)
+    }
+
+    /// A dark stroke on a light background, with some noise
+    fn text_line() -> ImageBuffer<Luma<f32>, Vec<f32>> {
+        ImageBuffer::from_fn(120, 24, |x, y| {
+            let v = if (30..34).contains(&x) && (6..18).contains(&y) {
+                0.2
+            } else {
+                0.9 - ((x * 7 + y * 13) % 10) as f32 / 100.0
+            };
+            Luma([v])
+        })
+    }
+
+    #[test]
+    fn test_presets_match_old_preprocessing() {
+        for im in [text_line(), uneven_line(), ImageBuffer::from_pixel(40, 20, Luma([0.5]))] {
+            assert_eq!(pre_process(im.clone()), old_pre_process(im.clone(), BINARIZE_THRESHOLD));
+            assert_eq!(pre_process_pending_line(im.clone()), old_pre_process(im, BINARIZE_THRESHOLD_PENDING));
+        }
+
+        // skipping the crop is possible
+        let (im, ok) = PreprocessPipeline::new().normalize(true).resize(384, 32).binarize(0.5).run(text_line());
+        assert!(ok);
+        assert_eq!(im.dimensions(), (384, 32));
+    }
+
+    #[test]
+    fn test_split_runs_like_whole_pipeline() {
+        let pipeline = PreprocessPipeline::line();
+        let names: Vec<&str> = pipeline.split().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["normalize", "crop", "normalize", "resize_and_pad", "binarize"]);
+
+        let im = uneven_line();
+        let by_step = pipeline.split().iter().fold(im.clone(), |im, (_, step)| step.run(im).0);
+        assert_eq!(by_step, pipeline.run(im).0);
+    }
+
+    #[test]
+    fn test_pending_line_default_mode_unchanged() {
+        let (global, _) = pre_process_pending_line(uneven_line());
+        let (explicit, _) = pre_process_pending_line_with_mode(uneven_line(), PreprocessMode::Global);
+        assert_eq!(global, explicit);
+    }
 }
@@ -5,7 +5,7 @@ use std::{cell::RefCell, time::Duration};
 use super::preprocess;
 use crate::common::image_ext::*;
 use crate::ocr::traits::ImageToText;
-use anyhow::Result;
+use anyhow::{Context, Result};
 #[cfg(feature = "tract_onnx")]
 use tract_onnx::prelude::*;
 
@@ -35,6 +35,17 @@ impl YasOCRModel {
         }
     }
 
+    /// Load the ONNX model and its index-to-word JSON from files instead of embedding them
+    pub fn from_files(model_path: &std::path::Path, index_to_word_path: &std::path::Path) -> Result<YasOCRModel> {
+        let model = std::fs::read(model_path)
+            .with_context(|| format!("failed to load OCR model: {}", model_path.display()))?;
+        let content = std::fs::read_to_string(index_to_word_path)
+            .with_context(|| format!("failed to load OCR index JSON: {}", index_to_word_path.display()))?;
+
+        Self::new(&model, &content)
+            .with_context(|| format!("failed to initialize OCR model: {}", model_path.display()))
+    }
+
     pub fn new(model: &[u8], content: &str) -> Result<YasOCRModel> {
         #[cfg(feature = "ort")]
         let model = RefCell::new(
@@ -71,7 +82,65 @@ impl YasOCRModel {
         })
     }
 
+    /// Recognize a pending (待激活) line binarized with `mode`
+    pub fn pending_line_with_confidence(&self, image: &RgbImage, mode: preprocess::PreprocessMode) -> Result<(String, f32)> {
+        let gray = preprocess::to_gray(image);
+        let (result, non_mono) = preprocess::pre_process_pending_line_with_mode(gray, mode);
+        if !non_mono {
+            return Ok((String::new(), 1.0));
+        }
+        self.inference_string_with_confidence(&result)
+    }
+
     pub fn inference_string(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<String> {
+        Ok(self.inference_string_with_confidence(img)?.0)
+    }
+
+    /// Also returns the lowest probability among the recognized characters, 1.0 if nothing is recognized
+    pub fn inference_string_with_confidence(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<(String, f32)> {
+        self.inference_string_constrained(img, None)
+    }
+
+    /// Greedy CTC decoding of `step_count` steps, `prob(i, j)` is the probability of word `j` at step `i`.
+    /// With `allowed_chars`, words with other characters are never picked, only the blank and the allowed ones
+    fn decode(&self, step_count: usize, prob: impl Fn(usize, usize) -> f32, allowed_chars: Option<&str>) -> (String, f32) {
+        let allowed = |word: &str| match allowed_chars {
+            Some(chars) => word == "-" || word.chars().all(|c| chars.contains(c)),
+            None => true,
+        };
+        let candidates = (0..self.index_to_word.len())
+            .filter(|&j| allowed(&self.index_to_word[j]))
+            .collect::<Vec<_>>();
+
+        let mut ans = String::new();
+        let mut confidence = 1.0_f32;
+        let mut last_word = "";
+        for i in 0..step_count {
+            let mut max_index = 0;
+            let mut max_value = -1.0_f32;
+            for &j in candidates.iter() {
+                let value = prob(i, j);
+                if value > max_value {
+                    max_value = value;
+                    max_index = j;
+                }
+            }
+            let word = self.index_to_word[max_index].as_str();
+            if word != last_word && word != "-" {
+                ans += word;
+                confidence = confidence.min(max_value.clamp(0.0, 1.0));
+            }
+            last_word = word;
+        }
+        (ans, confidence)
+    }
+
+    /// inference_string_with_confidence, only recognizing the characters in `allowed_chars` if given
+    pub fn inference_string_constrained(
+        &self,
+        img: &ImageBuffer<Luma<f32>, Vec<f32>>,
+        allowed_chars: Option<&str>,
+    ) -> Result<(String, f32)> {
         let now = SystemTime::now();
 
         #[cfg(feature = "ort")]
@@ -90,26 +159,7 @@ impl YasOCRModel {
             let mut session = self.model.borrow_mut();
             let result = session.run(ort::inputs![ort::value::Tensor::from_array(tensor)?])?;
             let arr = result[0].try_extract_array::<f32>()?;
-            let shape = arr.shape();
-            let mut ans = String::new();
-            let mut last_word = String::new();
-            for i in 0..shape[0] {
-                let mut max_index = 0;
-                let mut max_value = -1.0_f32;
-                for j in 0..self.index_to_word.len() {
-                    let value = arr[[i, 0, j]];
-                    if value > max_value {
-                        max_value = value;
-                        max_index = j;
-                    }
-                }
-                let word = &self.index_to_word[max_index];
-                if *word != last_word && word != "-" {
-                    ans = ans + word;
-                }
-                last_word.clone_from(word);
-            }
-            ans
+            self.decode(arr.shape()[0], |i, j| arr[[i, 0, j]], allowed_chars)
         };
         #[cfg(feature = "tract_onnx")]
         let result = self.model.run(tvec!(tensor.into()))?;
@@ -118,27 +168,7 @@ impl YasOCRModel {
         #[cfg(feature = "tract_onnx")]
         let shape = arr.shape();
         #[cfg(feature = "tract_onnx")]
-        let ans = {
-            let mut s = String::new();
-            let mut last_word = String::new();
-            for i in 0..shape[0] {
-                let mut max_index = 0;
-                let mut max_value = -1.0_f32;
-                for j in 0..self.index_to_word.len() {
-                    let value = arr[[i, 0, j]];
-                    if value > max_value {
-                        max_value = value;
-                        max_index = j;
-                    }
-                }
-                let word = &self.index_to_word[max_index];
-                if *word != last_word && word != "-" {
-                    s = s + word;
-                }
-                last_word.clone_from(word);
-            }
-            s
-        };
+        let ans = self.decode(shape[0], |i, j| arr[[i, 0, j]], allowed_chars);
 
         let time = now.elapsed()?;
 
@@ -151,27 +181,40 @@ impl YasOCRModel {
 
 impl ImageToText<RgbImage> for YasOCRModel {
     fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
+        Ok(self.image_to_text_with_confidence(image, is_preprocessed)?.0)
+    }
+
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<(String, f32)> {
         assert!(!is_preprocessed);
 
         let gray_image_float = preprocess::to_gray(image);
         let (result, non_mono) = preprocess::pre_process(gray_image_float);
 
         if !non_mono {
-            return Ok(String::new());
+            return Ok((String::new(), 1.0));
         }
 
-        let string_result = self.inference_string(&result)?;
-
-        Ok(string_result)
+        self.inference_string_with_confidence(&result)
     }
 
     fn image_to_text_pending_line(&self, image: &RgbImage) -> Result<String> {
-        let gray = preprocess::to_gray(image);
-        let (result, non_mono) = preprocess::pre_process_pending_line(gray);
+        Ok(self.image_to_text_pending_line_with_confidence(image)?.0)
+    }
+
+    /// Global thresholding, the mode is chosen per `SharedYasOCRModel` handle, see `pending_line_with_confidence`
+    fn image_to_text_pending_line_with_confidence(&self, image: &RgbImage) -> Result<(String, f32)> {
+        self.pending_line_with_confidence(image, preprocess::PreprocessMode::Global)
+    }
+
+    fn image_to_text_constrained_with_confidence(&self, image: &RgbImage, allowed_chars: &str) -> Result<(String, f32)> {
+        let gray_image_float = preprocess::to_gray(image);
+        let (result, non_mono) = preprocess::pre_process(gray_image_float);
+
         if !non_mono {
-            return Ok(String::new());
+            return Ok((String::new(), 1.0));
         }
-        self.inference_string(&result)
+
+        self.inference_string_constrained(&result, Some(allowed_chars))
     }
 
     fn get_average_inference_time(&self) -> Option<Duration> {
@@ -217,9 +260,41 @@ impl ImageToText<GrayImage> for YasOCRModel {
     }
 }
 
+/// Load an embedded model through the process level cache, see `SharedYasOCRModel`
 pub macro yas_ocr_model($model_name:literal, $index_to_word:literal) {{
     let model_bytes = include_bytes!($model_name);
     let index_to_word = include_str!($index_to_word);
 
-    YasOCRModel::new(model_bytes, index_to_word)
+    $crate::ocr::SharedYasOCRModel::new(model_bytes, index_to_word)
 }}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_files_names_missing_path() {
+        let dir = std::env::temp_dir().join("yas_test_missing_model");
+        let model_path = dir.join("model_training.onnx");
+        let result = YasOCRModel::from_files(&model_path, &dir.join("index_2_word.json"));
+
+        let message = format!("{:#}", result.err().unwrap());
+        assert!(message.contains(&model_path.display().to_string()));
+    }
+
+    #[test]
+    fn test_decode_constrained() {
+        let model_bytes = include_bytes!("../paddle_paddle_model/ch_PP-OCRv4_rec_infer.onnx");
+        let model = YasOCRModel::new(model_bytes, r#"{"0": "-", "1": "O", "2": "0", "3": "%"}"#).unwrap();
+        // "O" is the most likely at step 0, "0" the second
+        let probs = [
+            [0.0, 0.9, 0.6, 0.0],
+            [0.9, 0.0, 0.0, 0.1],
+            [0.1, 0.0, 0.0, 0.8],
+        ];
+        let prob = |i: usize, j: usize| probs[i][j];
+
+        assert_eq!(model.decode(3, prob, None), (String::from("O%"), 0.8));
+        assert_eq!(model.decode(3, prob, Some("0123456789.%")), (String::from("0%"), 0.6));
+    }
+}
@@ -0,0 +1,43 @@
+use image::Rgb;
+
+/// Calculate the square of the distance (not the distance itself)
+pub fn color_distance(c1: &Rgb<u8>, c2: &Rgb<u8>) -> usize {
+    let x = c1.0[0] as i32 - c2.0[0] as i32;
+    let y = c1.0[1] as i32 - c2.0[1] as i32;
+    let z = c1.0[2] as i32 - c2.0[2] as i32;
+    (x * x + y * y + z * z) as usize
+}
+
+/// The euclidean distance between two colors in RGB
+pub fn color_distance_sqrt(c1: &Rgb<u8>, c2: &Rgb<u8>) -> f64 {
+    (color_distance(c1, c2) as f64).sqrt()
+}
+
+/// Whether the euclidean distance between two colors is less than `threshold`
+pub fn is_color_near(c1: &Rgb<u8>, c2: &Rgb<u8>, threshold: f64) -> bool {
+    color_distance_sqrt(c1, c2) < threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_distance() {
+        let black = Rgb([0, 0, 0]);
+        assert_eq!(color_distance(&black, &Rgb([3, 4, 0])), 25);
+        assert_eq!(color_distance(&Rgb([3, 4, 0]), &black), 25);
+        assert_eq!(color_distance_sqrt(&black, &Rgb([3, 4, 0])), 5.0);
+        assert_eq!(color_distance(&black, &Rgb([255, 255, 255])), 3 * 255 * 255);
+    }
+
+    #[test]
+    fn test_is_color_near_boundary() {
+        let black = Rgb([0, 0, 0]);
+        // exactly at the threshold is not near
+        assert!(!is_color_near(&black, &Rgb([3, 4, 0]), 5.0));
+        assert!(is_color_near(&black, &Rgb([3, 4, 0]), 5.001));
+        assert!(is_color_near(&black, &black, 0.001));
+        assert!(!is_color_near(&black, &black, 0.0));
+    }
+}
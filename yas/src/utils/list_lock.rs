@@ -0,0 +1,228 @@
+use std::path::Path;
+
+use image::{GenericImageView, Rgb, RgbImage};
+use log::{trace, warn};
+
+use crate::positioning::{Pos, Size};
+use crate::utils::color_distance;
+
+/// Lock state of one cell of a list page. `row` and `col` count from the top left cell of the list image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockCell {
+    pub row: usize,
+    pub col: usize,
+    pub locked: bool,
+}
+
+/// The item grid of a list view and how its lock icon looks, in pixels of the list image
+#[derive(Debug, Clone, Copy)]
+pub struct ListLockGrid {
+    pub row: i32,
+    pub col: i32,
+    pub item_size: Size<f64>,
+    pub item_gap_size: Size<f64>,
+    /// where the lock icon is sampled, relative to the top left of a cell
+    pub lock_pos: Pos<f64>,
+    pub lock_color: Rgb<u8>,
+    /// a cell is locked if a pixel is within this squared RGB distance (see `color_distance`) of `lock_color`
+    pub lock_threshold: f64,
+}
+
+/// Horizontal offsets around a cell's lock sample position searched for the lock icon color
+const LOCK_SEARCH_DX: std::ops::Range<i32> = -1..1;
+/// Vertical offsets around a cell's lock sample position searched for the lock icon color
+const LOCK_SEARCH_DY: std::ops::Range<i32> = -10..10;
+
+/// Lock states of `cells` in row-major order, the shape `get_page_locks` used to return
+pub fn locks_flat(cells: &[LockCell]) -> Vec<bool> {
+    cells.iter().map(|cell| cell.locked).collect()
+}
+
+/// Lock state of the item `offset` (starting from 0) items after the first item of its page, in a list of `col` columns.
+/// Unlocked, with a warning, when the list image of the page gave no state for that cell, e.g. a missed list capture
+pub fn lock_at(page_locks: &[LockCell], offset: usize, col: usize) -> bool {
+    let col = col.max(1);
+    let (row, col) = (offset / col, offset % col);
+    match page_locks.iter().find(|cell| cell.row == row && cell.col == col) {
+        Some(cell) => cell.locked,
+        None => {
+            warn!("第 {} 行第 {} 列的物品没有锁定状态（本页共 {} 个），记为未锁定", row + 1, col + 1, page_locks.len());
+            false
+        },
+    }
+}
+
+/// Fraction (0~1) of a list row that lies inside an image of `image_height` pixels
+pub fn row_visible_ratio(row_top: f64, row_height: f64, image_height: u32) -> f64 {
+    if row_height <= 0.0 {
+        return 0.0;
+    }
+    let visible = (image_height as f64 - row_top).clamp(0.0, row_height);
+    visible / row_height
+}
+
+/// The first visible row of the page holding item `cur_index` (starting from 0), out of `max_count` items.
+/// The last page is scrolled only as far as its remaining rows, so it may start below the top row
+pub fn page_start_row(max_count: i32, cur_index: i32, row: i32, col: i32) -> i32 {
+    let page_size = row * col;
+    if max_count - cur_index >= page_size {
+        0
+    } else {
+        let remain = max_count - cur_index;
+        let remain_row = (remain + col - 1) / col;
+        let scroll_row = remain_row.min(row);
+        row - scroll_row
+    }
+}
+
+/// Where the lock icon of cell (`r`, `c`) is sampled, relative to the list image
+fn cell_lock_pos(grid: &ListLockGrid, r: i32, c: i32) -> Pos<f64> {
+    let gap = grid.item_gap_size;
+    let size = grid.item_size;
+    Pos {
+        x: (gap.width + size.width) * (c as f64) + grid.lock_pos.x,
+        y: (gap.height + size.height) * (r as f64) + grid.lock_pos.y,
+    }
+}
+
+/// Pixels of the lock icon search box around `pos` that lie inside the image
+fn lock_search_pixels(list_image: &RgbImage, pos: Pos<f64>) -> impl Iterator<Item = &Rgb<u8>> + '_ {
+    LOCK_SEARCH_DX
+        .flat_map(|dx| LOCK_SEARCH_DY.map(move |dy| (dx, dy)))
+        .filter_map(move |(dx, dy)| {
+            let x = pos.x as i32 + dx;
+            let y = pos.y as i32 + dy;
+            if x < 0 || y < 0 || x as u32 >= list_image.width() || y as u32 >= list_image.height() {
+                return None;
+            }
+            Some(list_image.get_pixel(x as u32, y as u32))
+        })
+}
+
+/// The pixel of cell (`row`, `col`)'s lock search box closest to `grid.lock_color`, with its distance.
+/// Sampled from a locked item, this is the lock color of the current UI brightness. None if the box is off the image
+pub fn sample_lock_color(list_image: &RgbImage, grid: &ListLockGrid, row: i32, col: i32) -> Option<(Rgb<u8>, usize)> {
+    let pos = cell_lock_pos(grid, row, col);
+    lock_search_pixels(list_image, pos)
+        .map(|color| (*color, color_distance(color, &grid.lock_color)))
+        .min_by_key(|&(_, distance)| distance)
+}
+
+/// List-view lock detection: from a cropped list grid image, return the lock state of each cell (row-major).
+/// Looks for `grid.lock_color` around the lock position of each cell. Caller crops the list region
+/// (e.g. scan_margin_pos + first-page rect) from the window image.
+/// A row cut off by the bottom of the image is included only if at least `min_visible_ratio` of it is visible.
+/// If `debug_dir` is Some, dumps the sampled pixel region image for each (row, col) as `r{row}_c{col}.png`.
+pub fn get_page_locks(
+    list_image: &RgbImage,
+    grid: &ListLockGrid,
+    min_visible_ratio: f64,
+    debug_dir: Option<&Path>,
+) -> Vec<LockCell> {
+    let mut result = Vec::new();
+    let gap = grid.item_gap_size;
+    let size = grid.item_size;
+
+    if let Some(dir) = debug_dir {
+        let _ = std::fs::create_dir_all(dir);
+        let path = dir.join("qwq.png");
+        let _ = list_image.save(&path);
+    }
+
+    for r in 0..grid.row {
+        let row_top = (gap.height + size.height) * (r as f64);
+        if row_visible_ratio(row_top, size.height, list_image.height()) < min_visible_ratio {
+            break;
+        }
+        for c in 0..grid.col {
+            let pos = cell_lock_pos(grid, r, c);
+            trace!("lock sample position of ({}, {}): {}, {}", r, c, pos.x, pos.y);
+
+            if let Some(dir) = debug_dir {
+                let px = pos.x as i32;
+                let py = pos.y as i32;
+                let left = px.saturating_sub(2).max(0) as u32;
+                let top = py.saturating_sub(10).max(0) as u32;
+                let w = (list_image.width()).saturating_sub(left).min(6);
+                let h = (list_image.height()).saturating_sub(top).min(22);
+                if w > 0 && h > 0 {
+                    let crop = list_image.view(left, top, w, h).to_image();
+                    let path = dir.join(format!("r{}_c{}.png", r, c));
+                    let _ = crop.save(&path);
+                }
+            }
+
+            let locked = lock_search_pixels(list_image, pos).any(|color| {
+                (color_distance(color, &grid.lock_color) as f64) < grid.lock_threshold
+            });
+            result.push(LockCell {
+                row: r as usize,
+                col: c as usize,
+                locked,
+            });
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(row: i32, col: i32) -> ListLockGrid {
+        ListLockGrid {
+            row,
+            col,
+            item_size: Size { width: 100.0, height: 120.0 },
+            item_gap_size: Size { width: 10.0, height: 20.0 },
+            lock_pos: Pos { x: 15.0, y: 30.0 },
+            lock_color: Rgb([255, 255, 255]),
+            lock_threshold: 30.0,
+        }
+    }
+
+    #[test]
+    fn test_lock_at_missing_state() {
+        // a partial last page: 2 columns, the second row holds a single item
+        let cell = |row, col, locked| LockCell { row, col, locked };
+        let page_locks = vec![cell(0, 0, false), cell(0, 1, true), cell(1, 0, true)];
+        assert!(lock_at(&page_locks, 1, 2));
+        assert!(lock_at(&page_locks, 2, 2));
+        assert!(!lock_at(&page_locks, 3, 2));
+        assert!(!lock_at(&[], 0, 2));
+        assert_eq!(locks_flat(&page_locks), vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_row_visible_ratio() {
+        assert_eq!(row_visible_ratio(0.0, 100.0, 200), 1.0);
+        assert_eq!(row_visible_ratio(120.0, 100.0, 200), 0.8);
+        assert_eq!(row_visible_ratio(250.0, 100.0, 200), 0.0);
+    }
+
+    #[test]
+    fn test_page_locks_follow_grid() {
+        let grid = grid(2, 2);
+        let mut image = RgbImage::new(220, 260);
+        // the second cell of the second row, a few pixels above its sample position
+        image.put_pixel(110 + 15, 140 + 30 - 4, Rgb([253, 253, 253]));
+
+        let locks = get_page_locks(&image, &grid, 0.75, None);
+        assert_eq!(locks_flat(&locks), vec![false, false, false, true]);
+        assert_eq!(locks[3], LockCell { row: 1, col: 1, locked: true });
+        assert_eq!(sample_lock_color(&image, &grid, 1, 1), Some((Rgb([253, 253, 253]), 12)));
+
+        // the second row is only half visible
+        let image = RgbImage::new(220, 200);
+        assert_eq!(get_page_locks(&image, &grid, 0.75, None).len(), 2);
+    }
+
+    #[test]
+    fn test_page_start_row() {
+        // 5 rows of 9, a full page left
+        assert_eq!(page_start_row(100, 0, 5, 9), 0);
+        assert_eq!(page_start_row(100, 45, 5, 9), 0);
+        // 10 items left take 2 rows at the bottom of the page
+        assert_eq!(page_start_row(100, 90, 5, 9), 3);
+    }
+}
@@ -1,13 +1,94 @@
 use std::io::Read;
+use std::path::Path;
 
-/// Calculate the square of the distance (not the distance itself)
-pub fn color_distance(c1: &image::Rgb<u8>, c2: &image::Rgb<u8>) -> usize {
-    let x = c1.0[0] as i32 - c2.0[0] as i32;
-    let y = c1.0[1] as i32 - c2.0[1] as i32;
-    let z = c1.0[2] as i32 - c2.0[2] as i32;
-    (x * x + y * y + z * z) as usize
-}
+use anyhow::{anyhow, Context};
 
 pub fn press_any_key_to_continue() {
     let _ = std::io::stdin().read(&mut [0u8]).unwrap();
 }
+
+/// Decode the image at `path` in any format the image crate reads (PNG, JPEG, WEBP, ...) and convert it to RGB8.
+/// Grayscale, alpha and 16 bit images are converted explicitly, so that OCR gets the layout it expects
+pub fn load_rgb8<P: AsRef<Path>>(path: P) -> anyhow::Result<image::RgbImage> {
+    let path = path.as_ref();
+    let reader = image::io::Reader::open(path)
+        .with_context(|| format!("open image {:?}", path))?
+        .with_guessed_format()
+        .with_context(|| format!("read image {:?}", path))?;
+    let format = reader.format()
+        .ok_or_else(|| anyhow!("unknown image format of {:?}", path))?;
+
+    let image = reader.decode()
+        .with_context(|| format!("decode image {:?} (detected format: {:?})", path, format))?;
+    if image.color() != image::ColorType::Rgb8 {
+        log::debug!("image {:?} is {:?}, converted to Rgb8", path, image.color());
+    }
+    Ok(image.to_rgb8())
+}
+
+/// The first number after `item_name`, e.g. 1800 in `圣遗物 1,800/2400` or 1500 in `遗器数量1500/2000`.
+/// Once the number started, thousands separators are skipped and O is read as 0,
+/// anything else (the `/`, or whatever OCR made of it) ends it. None if `/` comes before any digit
+pub fn parse_item_count(s: &str, item_name: &str) -> Option<i32> {
+    let rest = &s[s.find(item_name)? + item_name.len()..];
+
+    let mut digits = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            'O' | 'o' if !digits.is_empty() => digits.push('0'),
+            ',' | '.' if !digits.is_empty() => {},
+            '/' if digits.is_empty() => return None,
+            _ if digits.is_empty() => {},
+            _ => break,
+        }
+    }
+    digits.parse::<i32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_item_count() {
+        assert_eq!(parse_item_count("圣遗物1800/2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("圣遗物 1,800 /2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("圣遗物", "圣遗物"), None);
+        assert_eq!(parse_item_count("", "圣遗物"), None);
+        assert_eq!(parse_item_count("遗器数量1500/2000", "遗器数量"), Some(1500));
+        assert_eq!(parse_item_count("遗器数量1500/2000", "圣遗物"), None);
+
+        // realistic OCR renderings
+        assert_eq!(parse_item_count("圣遗物 1800 / 2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("圣遗物：1800/2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("圣遗物1800|2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("圣遗物1800 2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("圣遗物18O0/2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("背包 圣遗物 1800/2400", "圣遗物"), Some(1800));
+        assert_eq!(parse_item_count("遗器数量 1,5O0 / 2000", "遗器数量"), Some(1500));
+        // a dropped digit is read as is
+        assert_eq!(parse_item_count("圣遗物 180/2400", "圣遗物"), Some(180));
+        assert_eq!(parse_item_count("圣遗物1800/240", "圣遗物"), Some(1800));
+        // the count itself is missing, the capacity must not be taken for it
+        assert_eq!(parse_item_count("圣遗物/2400", "圣遗物"), None);
+        assert_eq!(parse_item_count("圣遗 1800/2400", "圣遗物"), None);
+        assert_eq!(parse_item_count("遗器数量/2000", "遗器数量"), None);
+    }
+
+    #[test]
+    fn test_load_rgb8_converts_color_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gray.png");
+        image::GrayImage::from_pixel(3, 2, image::Luma([200])).save(&path).unwrap();
+
+        let image = load_rgb8(&path).unwrap();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(*image.get_pixel(1, 1), image::Rgb([200, 200, 200]));
+
+        let garbage = dir.path().join("garbage.bin");
+        std::fs::write(&garbage, b"not an image").unwrap();
+        assert!(load_rgb8(&garbage).is_err());
+        assert!(load_rgb8(dir.path().join("missing.png")).is_err());
+    }
+}
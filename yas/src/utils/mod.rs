@@ -7,6 +7,8 @@ use serde::Deserialize;
 use std::io::stdin;
 use std::process;
 pub use misc::*;
+pub use color::*;
+pub use list_lock::*;
 
 #[cfg(target_os = "macos")]
 mod macos;
@@ -19,6 +21,8 @@ mod windows;
 pub use windows::*;
 
 mod misc;
+pub mod color;
+mod list_lock;
 
 pub fn sleep(ms: u32) {
     thread::sleep(Duration::from_millis(ms as u64));
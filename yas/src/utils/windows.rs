@@ -9,9 +9,10 @@ use std::ptr::{null, null_mut, slice_from_raw_parts_mut};
 use anyhow::{anyhow, Result};
 use log::{info, warn};
 use windows_sys::Win32::Foundation::*;
-use windows_sys::Win32::Graphics::Gdi::ClientToScreen;
+use windows_sys::Win32::Graphics::Gdi::{ClientToScreen, GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
 use windows_sys::Win32::Security::*;
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use windows_sys::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_RUNNING_D3D_FULL_SCREEN};
 use windows_sys::Win32::UI::WindowsAndMessaging::*;
 use windows_sys::Win32::System::SystemServices::*;
 use windows_sys::Win32::System::LibraryLoader::*;
@@ -89,6 +90,39 @@ pub fn get_client_rect(hwnd: HWND) -> Result<Rect<i32>> {
     unsafe { get_client_rect_unsafe(hwnd) }
 }
 
+unsafe fn get_monitor_rect_unsafe(hwnd: HWND) -> Result<Rect<i32>> {
+    let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    if monitor.is_null() {
+        return Err(anyhow!("cannot find monitor"));
+    }
+
+    let mut info: MONITORINFO = std::mem::zeroed();
+    info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+    if GetMonitorInfoW(monitor, &mut info) == 0 {
+        return Err(anyhow!("GetMonitorInfoW failed"));
+    }
+
+    let rect = info.rcMonitor;
+    Ok(Rect {
+        left: rect.left,
+        top: rect.top,
+        width: rect.right - rect.left,
+        height: rect.bottom - rect.top,
+    })
+}
+
+/// Get the rect of the monitor the window is (mostly) on
+pub fn get_monitor_rect(hwnd: HWND) -> Result<Rect<i32>> {
+    unsafe { get_monitor_rect_unsafe(hwnd) }
+}
+
+/// Whether a D3D application is running in exclusive fullscreen mode
+pub fn is_d3d_fullscreen() -> bool {
+    let mut state = 0;
+    let hr = unsafe { SHQueryUserNotificationState(&mut state) };
+    hr == 0 && state == QUNS_RUNNING_D3D_FULL_SCREEN
+}
+
 unsafe fn is_admin_unsafe() -> bool {
     let mut authority: SID_IDENTIFIER_AUTHORITY = SID_IDENTIFIER_AUTHORITY {
         Value: [0, 0, 0, 0, 0, 5],
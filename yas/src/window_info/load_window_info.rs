@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use crate::game_info::{Platform, UI};
 use crate::positioning::Size;
@@ -14,7 +15,36 @@ pub struct WindowInfoTemplatePerSize {
     pub data: HashMap<String, WindowInfoType>
 }
 
+/// `WindowInfoTemplatePerSize` with the entries left unparsed, so that a bad entry can be reported by its key
+#[derive(Deserialize)]
+struct RawWindowInfoTemplatePerSize {
+    current_resolution: Size<usize>,
+    platform: Platform,
+    ui: UI,
+    data: BTreeMap<String, serde_json::Value>,
+}
+
 impl WindowInfoTemplatePerSize {
+    /// Parse a `windowsWxH.json` file, an entry that is not a `WindowInfoType` fails with its key
+    pub fn from_json_str(s: &str) -> anyhow::Result<Self> {
+        let raw: RawWindowInfoTemplatePerSize = serde_json::from_str(s)
+            .context("expected { current_resolution, platform, ui, data }")?;
+
+        let mut data = HashMap::new();
+        for (key, value) in raw.data {
+            let parsed: WindowInfoType = serde_json::from_value(value)
+                .with_context(|| format!("malformed window info key \"{}\"", key))?;
+            data.insert(key, parsed);
+        }
+
+        Ok(WindowInfoTemplatePerSize {
+            current_resolution: raw.current_resolution,
+            platform: raw.platform,
+            ui: raw.ui,
+            data,
+        })
+    }
+
     pub fn inject_into_window_info_repo(&self, repo: &mut WindowInfoRepository) {
         for (name, value) in self.data.iter() {
             repo.add(name, self.current_resolution, self.ui, self.platform, *value);
@@ -28,7 +58,8 @@ pub macro load_window_info_repo($($filename:literal),+ $(,)?) {
         $(
             {
                 let s = include_str!($filename);
-                let f: WindowInfoTemplatePerSize = serde_json::from_str(&s).unwrap();
+                let f = WindowInfoTemplatePerSize::from_json_str(s)
+                    .unwrap_or_else(|e| panic!("{}: {:#}", $filename, e));
                 f.inject_into_window_info_repo(&mut result);
             }
         )*
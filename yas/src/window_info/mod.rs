@@ -4,6 +4,6 @@ mod load_window_info;
 mod from_window_info_repository;
 
 pub use from_window_info_repository::FromWindowInfoRepository;
-pub use window_info_repository::WindowInfoRepository;
+pub use window_info_repository::{content_box, WindowInfoRepository};
 pub use window_info_type::WindowInfoType;
 pub use load_window_info::load_window_info_repo;
\ No newline at end of file
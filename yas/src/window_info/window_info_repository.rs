@@ -1,10 +1,13 @@
 use std::collections::HashMap;
+use std::path::Path;
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use crate::game_info::{Platform, UI};
-use crate::positioning::{Pos, Scalable, Size};
+use crate::positioning::{Pos, Rect, Scalable, Size};
 
 use crate::window_info::WindowInfoType;
+use crate::window_info::load_window_info::WindowInfoTemplatePerSize;
 
 /// Maps a window-info-key to a list of entries
 /// where entries consist of a size where the value is recorded, and accordingly a value
@@ -21,6 +24,27 @@ impl WindowInfoRepository {
         }
     }
 
+    /// Read every `windowsWxH.json` in `dir`, the files `load_window_info_repo!` embeds at build time
+    pub fn load_from_dir(dir: &Path) -> anyhow::Result<WindowInfoRepository> {
+        let mut paths = std::fs::read_dir(dir)
+            .with_context(|| format!("read window info dir {:?}", dir))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(is_window_info_file_name))
+            .collect::<Vec<_>>();
+        paths.sort();
+        if paths.is_empty() {
+            return Err(anyhow::anyhow!("no windowsWxH.json in {:?}", dir));
+        }
+
+        let mut repo = WindowInfoRepository::new();
+        for path in paths.iter() {
+            let s = std::fs::read_to_string(path).with_context(|| format!("read {:?}", path))?;
+            let template = WindowInfoTemplatePerSize::from_json_str(&s).with_context(|| format!("parse {:?}", path))?;
+            template.inject_into_window_info_repo(&mut repo);
+        }
+        Ok(repo)
+    }
+
     pub fn add(&mut self, name: &str, size: Size<usize>, ui: UI, platform: Platform, value: WindowInfoType) {
         self.data
             .entry(String::from(name))
@@ -65,26 +89,244 @@ impl WindowInfoRepository {
     }
 
     /// Get window info by name and size
-    /// if window size does not exists exactly, this function will search for the same resolution family and scale the result
+    /// if window size does not exists exactly, the value is taken from `base_resolution` (chosen from all keys) and scaled.
+    /// None if the key is not recorded at that base, see `missing_key_error`
     pub fn get_auto_scale<T>(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<T> where WindowInfoType: TryInto<T> {
-        if self.data.contains_key(name) {
-            if self.data[name].contains_key(&(window_size, ui, platform)) {
-                return self.data[name][&(window_size, ui, platform)].try_into().ok();
-            } else {
-                // todo find a biggest size which can be scaled, this will reduce error
-                // find if a resolution can be scaled
-                for (k, value) in self.data[name].iter() {
-                    let size = &k.0;
-                    if size.width * window_size.height == size.height * window_size.width
-                        && k.1 == ui && k.2 == platform
-                    {
-                        let factor: f64 = window_size.width as f64 / size.width as f64;
-                        return value.scale(factor).try_into().ok();
-                    }
-                }
-            }
+        let base = self.base_resolution(window_size, ui, platform)?;
+        let value = *self.data.get(name)?.get(&(base, ui, platform))?;
+
+        if base == window_size {
+            return value.try_into().ok();
+        }
+        let (factor, offset) = placement(base, window_size);
+        match offset {
+            Some(offset) => value.scale(factor).translate(offset).try_into().ok(),
+            None => value.scale(factor).try_into().ok(),
         }
+    }
 
-        None
+    /// The recorded resolution window info for `window_size` is taken from, looking at all keys
+    pub fn base_resolution(&self, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<Size<usize>> {
+        select_base_resolution(self.data.values().flat_map(|entries| entries.keys()), window_size, ui, platform)
+    }
+
+    /// The recorded resolutions for `ui` and `platform`, smallest first
+    pub fn available_resolutions(&self, ui: UI, platform: Platform) -> Vec<Size<usize>> {
+        let mut sizes = self.data.values()
+            .flat_map(|entries| entries.keys())
+            .filter(|k| k.1 == ui && k.2 == platform)
+            .map(|k| k.0)
+            .collect::<Vec<_>>();
+        sizes.sort_by_key(|size| (size.width, size.height));
+        sizes.dedup();
+        sizes
+    }
+
+    /// The base resolution for `window_size` and the factor its values are scaled by,
+    /// or an error listing the recorded resolutions if none fits
+    pub fn select_profile(&self, window_size: Size<usize>, ui: UI, platform: Platform) -> anyhow::Result<(Size<usize>, f64)> {
+        match self.base_resolution(window_size, ui, platform) {
+            Some(base) => Ok((base, placement(base, window_size).0)),
+            None => Err(self.no_profile_error(window_size, ui, platform)),
+        }
+    }
+
+    fn no_profile_error(&self, window_size: Size<usize>, ui: UI, platform: Platform) -> anyhow::Error {
+        let available = self.available_resolutions(ui, platform)
+            .iter()
+            .map(|size| format!("{}x{}", size.width, size.height))
+            .collect::<Vec<_>>();
+        anyhow::anyhow!(
+            "no window info for window {}x{} ({:?}, {:?}), available: [{}]",
+            window_size.width, window_size.height, ui, platform, available.join(", ")
+        )
+    }
+
+    /// An error naming the key that `get_auto_scale` could not find, and the base resolution it was looked up at
+    pub fn missing_key_error(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> anyhow::Error {
+        match self.base_resolution(window_size, ui, platform) {
+            Some(base) => anyhow::anyhow!(
+                "window info key \"{}\" is missing at base resolution {}x{} ({:?}, {:?}), needed for window {}x{}",
+                name, base.width, base.height, ui, platform, window_size.width, window_size.height
+            ),
+            None => anyhow::anyhow!("{}, needed for key \"{}\"", self.no_profile_error(window_size, ui, platform), name),
+        }
+    }
+}
+
+/// `windows{width}x{height}.json`
+fn is_window_info_file_name(name: &str) -> bool {
+    let size = match name.strip_prefix("windows").and_then(|s| s.strip_suffix(".json")) {
+        Some(size) => size,
+        None => return false,
+    };
+    match size.split_once('x') {
+        Some((width, height)) => width.parse::<usize>().is_ok() && height.parse::<usize>().is_ok(),
+        None => false,
+    }
+}
+
+/// Aspect ratios closer than this (relative difference) are treated as the same, e.g. 1366x768 and 16:9
+const ASPECT_TOLERANCE: f64 = 0.005;
+
+fn aspect_distance(a: Size<usize>, b: Size<usize>) -> f64 {
+    let ratio_a = a.width as f64 / a.height as f64;
+    let ratio_b = b.width as f64 / b.height as f64;
+    (ratio_a - ratio_b).abs() / ratio_b
+}
+
+/// Pick the recorded resolution to use for `window_size`: the same size, then the nearest aspect ratio
+/// within `ASPECT_TOLERANCE` (the largest of equally near ones, scaling it down loses the least),
+/// then the largest 16:9 one, which is placed in the content box
+fn select_base_resolution<'a>(
+    recorded: impl Iterator<Item = &'a (Size<usize>, UI, Platform)>,
+    window_size: Size<usize>,
+    ui: UI,
+    platform: Platform,
+) -> Option<Size<usize>> {
+    let candidates: Vec<Size<usize>> = recorded
+        .filter(|k| k.1 == ui && k.2 == platform)
+        .map(|k| k.0)
+        .collect();
+
+    candidates.iter().find(|size| **size == window_size)
+        .or_else(|| candidates.iter()
+            .filter(|size| aspect_distance(**size, window_size) <= ASPECT_TOLERANCE)
+            .min_by(|a, b| aspect_distance(**a, window_size)
+                .total_cmp(&aspect_distance(**b, window_size))
+                .then(b.width.cmp(&a.width))))
+        .or_else(|| candidates.iter()
+            .filter(|size| size.width * 9 == size.height * 16)
+            .max_by_key(|size| size.width))
+        .copied()
+}
+
+/// How values recorded at `base` are placed in a window of `window_size`: the scale factor,
+/// and the offset added after scaling when the base is placed in the content box
+pub fn placement(base: Size<usize>, window_size: Size<usize>) -> (f64, Option<Pos<f64>>) {
+    if aspect_distance(base, window_size) <= ASPECT_TOLERANCE {
+        (window_size.width as f64 / base.width as f64, None)
+    } else {
+        // no recorded resolution of this aspect ratio, the game renders its UI in a
+        // centered 16:9 box (ultrawide, 16:10), so place a 16:9 entry in that box
+        let content = content_box(window_size);
+        (content.width / base.width as f64, Some(content.origin()))
+    }
+}
+
+/// The largest 16:9 area centered in the window, where the game UI is drawn
+pub fn content_box(window_size: Size<usize>) -> Rect<f64> {
+    let width = window_size.width as f64;
+    let height = window_size.height as f64;
+
+    if width * 9.0 > height * 16.0 {
+        let content_width = height * 16.0 / 9.0;
+        Rect::new((width - content_width) / 2.0, 0.0, content_width, height)
+    } else {
+        let content_height = width * 9.0 / 16.0;
+        Rect::new(0.0, (height - content_height) / 2.0, width, content_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_key_error() {
+        let mut repo = WindowInfoRepository::new();
+        repo.add("rect", Size::new(1600, 900), UI::Desktop, Platform::Windows, WindowInfoType::InvariantInt(1));
+
+        let error = repo.missing_key_error("other", Size::new(1920, 1080), UI::Desktop, Platform::Windows);
+        assert!(error.to_string().contains("\"other\" is missing at base resolution 1600x900"));
+
+        let error = repo.missing_key_error("other", Size::new(1920, 1080), UI::Mobile, Platform::Windows);
+        assert!(error.to_string().starts_with("no window info for window 1920x1080"));
+    }
+
+    #[test]
+    fn test_auto_scale_uses_one_base() {
+        let mut repo = WindowInfoRepository::new();
+        repo.add("int", Size::new(1600, 900), UI::Desktop, Platform::Windows, WindowInfoType::InvariantInt(1));
+        repo.add("pos", Size::new(1280, 960), UI::Desktop, Platform::Windows, WindowInfoType::Pos(Pos { x: 10.0, y: 20.0 }));
+
+        let window_size = Size::new(1920, 1080);
+        assert_eq!(repo.base_resolution(window_size, UI::Desktop, Platform::Windows), Some(Size::new(1600, 900)));
+        let int: Option<i32> = repo.get_auto_scale("int", window_size, UI::Desktop, Platform::Windows);
+        assert_eq!(int, Some(1));
+        // only recorded at 1280x960, not looked up there and placed in the content box
+        let pos: Option<Pos<f64>> = repo.get_auto_scale("pos", window_size, UI::Desktop, Platform::Windows);
+        assert_eq!(pos, None);
+        let error = repo.missing_key_error("pos", window_size, UI::Desktop, Platform::Windows);
+        assert!(error.to_string().contains("\"pos\" is missing at base resolution 1600x900"), "{}", error);
+    }
+
+    #[test]
+    fn test_load_from_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let file = |width: usize, height: usize, entry: &str| format!(
+            r#"{{"current_resolution": {{"width": {}, "height": {}}}, "platform": "Windows", "ui": "Desktop", "data": {{{}}}}}"#,
+            width, height, entry
+        );
+        std::fs::write(dir.join("windows1600x900.json"), file(1600, 900, r#""count": {"InvariantInt": 3}"#)).unwrap();
+        std::fs::write(dir.join("windows1280x960.json"), file(1280, 960, r#""pos": {"Pos": {"x": 1.0, "y": 2.0}}"#)).unwrap();
+        std::fs::write(dir.join("notes.json"), "not window info").unwrap();
+
+        let repo = WindowInfoRepository::load_from_dir(dir).unwrap();
+        assert_eq!(repo.available_resolutions(UI::Desktop, Platform::Windows), vec![Size::new(1280, 960), Size::new(1600, 900)]);
+        assert_eq!(repo.get_exact::<i32>("count", Size::new(1600, 900), UI::Desktop, Platform::Windows), Some(3));
+
+        std::fs::write(dir.join("windows2100x900.json"), file(2100, 900, r#""rect": {"Rect": {"left": 1.0}}"#)).unwrap();
+        let error = WindowInfoRepository::load_from_dir(dir).unwrap_err();
+        assert!(format!("{:#}", error).contains("malformed window info key \"rect\""), "{:#}", error);
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let mut repo = WindowInfoRepository::new();
+        for (width, height) in [(1280, 720), (1600, 900), (1440, 900), (1280, 960)] {
+            repo.add("int", Size::new(width, height), UI::Desktop, Platform::Windows, WindowInfoType::InvariantInt(1));
+        }
+
+        let select = |width, height| repo.select_profile(Size::new(width, height), UI::Desktop, Platform::Windows).unwrap();
+        assert_eq!(select(1600, 900), (Size::new(1600, 900), 1.0));
+        // the largest of the same aspect ratio
+        assert_eq!(select(1920, 1080), (Size::new(1600, 900), 1.2));
+        // close enough to 16:9
+        assert_eq!(select(1366, 768).0, Size::new(1600, 900));
+        assert_eq!(select(1680, 1050), (Size::new(1440, 900), 1680.0 / 1440.0));
+        // no near aspect ratio, the 16:9 one in the content box
+        assert_eq!(select(2560, 1080), (Size::new(1600, 900), 1.2));
+
+        let error = repo.select_profile(Size::new(1920, 1080), UI::Mobile, Platform::Windows).unwrap_err();
+        assert!(error.to_string().contains("available: []"), "{}", error);
+
+        let mut repo = WindowInfoRepository::new();
+        repo.add("int", Size::new(1280, 960), UI::Desktop, Platform::Windows, WindowInfoType::InvariantInt(1));
+        let error = repo.select_profile(Size::new(2560, 1080), UI::Desktop, Platform::Windows).unwrap_err();
+        assert!(error.to_string().contains("available: [1280x960]"), "{}", error);
+    }
+
+    #[test]
+    fn test_content_box() {
+        assert_eq!(content_box(Size::new(1920, 1080)), Rect::new(0.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(content_box(Size::new(2560, 1080)), Rect::new(320.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(content_box(Size::new(1920, 1200)), Rect::new(0.0, 60.0, 1920.0, 1080.0));
+    }
+
+    #[test]
+    fn test_letterboxed_auto_scale() {
+        let mut repo = WindowInfoRepository::new();
+        let size = Size::new(1280, 720);
+        repo.add("rect", size, UI::Desktop, Platform::Windows, WindowInfoType::Rect(Rect::new(100.0, 50.0, 200.0, 100.0)));
+        repo.add("int", size, UI::Desktop, Platform::Windows, WindowInfoType::InvariantInt(8));
+
+        let rect: Rect<f64> = repo.get_auto_scale("rect", Size::new(2560, 1080), UI::Desktop, Platform::Windows).unwrap();
+        // scaled by 1920 / 1280, then moved right by the 320 pixel bar
+        assert_eq!(rect, Rect::new(470.0, 75.0, 300.0, 150.0));
+        let int: i32 = repo.get_auto_scale("int", Size::new(2560, 1080), UI::Desktop, Platform::Windows).unwrap();
+        assert_eq!(int, 8);
     }
 }
@@ -11,6 +11,8 @@ pub enum WindowInfoType {
     /// when window size scales, these amount will not scale
     InvariantInt(i32),
     InvariantFloat(f64),
+    /// RGB color, does not scale
+    Color([u8; 3]),
 }
 
 // due to orphan rule, we implement TryInto instead of TryFrom
@@ -70,6 +72,28 @@ impl TryInto<Size<f64>> for WindowInfoType {
     }
 }
 
+impl TryInto<image::Rgb<u8>> for WindowInfoType {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<image::Rgb<u8>, Self::Error> {
+        match self {
+            WindowInfoType::Color(color) => Ok(image::Rgb(color)),
+            _ => Err(anyhow!(String::from("not a color type"))),
+        }
+    }
+}
+
+impl WindowInfoType {
+    /// Move positions and rects by `offset`, other values are unchanged
+    pub fn translate(&self, offset: Pos<f64>) -> Self {
+        match *self {
+            WindowInfoType::Rect(rect) => WindowInfoType::Rect(rect.translate(offset)),
+            WindowInfoType::Pos(pos) => WindowInfoType::Pos(Pos { x: pos.x + offset.x, y: pos.y + offset.y }),
+            other => other,
+        }
+    }
+}
+
 impl Scalable for WindowInfoType {
     fn scale(&self, factor: f64) -> Self {
         let result = match *self {
@@ -79,6 +103,7 @@ impl Scalable for WindowInfoType {
             WindowInfoType::Float(v) => WindowInfoType::Float(v.scale(factor)),
             WindowInfoType::InvariantInt(v) => WindowInfoType::InvariantInt(v),
             WindowInfoType::InvariantFloat(v) => WindowInfoType::InvariantFloat(v),
+            WindowInfoType::Color(v) => WindowInfoType::Color(v),
         };
         result
     }